@@ -0,0 +1,535 @@
+use clap::{
+    crate_description,
+    crate_name,
+    crate_version,
+    value_t_or_exit,
+    App,
+    Arg,
+};
+use serde_json::{json, Value};
+use solana_clap_utils::input_validators::{
+    is_url,
+    is_valid_pubkey,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::from_account,
+    clock::{Clock, UnixTimestamp},
+    commitment_config::CommitmentConfig,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar,
+};
+use solong_lottery::draw::numbers_of;
+use solong_lottery::state::{AwardBill, AwardState, AwardStatus, LotteryState, PlayerState, SponsorBill, TicketEntry};
+use std::str::FromStr;
+use tiny_http::{Header, Method, Response, Server};
+
+/// default page size of the billboard
+const DEFAULT_LIMIT: usize = 20;
+/// max page size of the billboard
+const MAX_LIMIT: usize = 100;
+
+/// Error answered to the client
+#[derive(Debug)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new<T: ToString>(status: u16, message: T) -> Self {
+        ApiError {
+            status,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Read-only view of one lottery pool
+struct Api {
+    client: RpcClient,
+    pool: Pubkey,
+}
+
+impl Api {
+    fn load<T: Pack + IsInitialized>(&self, address: &Pubkey) -> Result<T, ApiError> {
+        let data = self
+            .client
+            .get_account_data(address)
+            .map_err(|err| ApiError::new(502, err))?;
+        T::unpack(&data).map_err(|err| ApiError::new(502, format!("decode {} failed: {}", address, err)))
+    }
+
+    /// Time of the cluster as the program sees it, from the Clock sysvar.
+    fn now(&self) -> Result<UnixTimestamp, ApiError> {
+        let account = self
+            .client
+            .get_account(&sysvar::clock::id())
+            .map_err(|err| ApiError::new(502, err))?;
+        let clock: Clock = from_account(&account).ok_or_else(|| ApiError::new(502, "decode clock sysvar failed"))?;
+        Ok(clock.unix_timestamp)
+    }
+
+    /// Loads the pool header and its ticket entries.
+    fn load_pool(&self) -> Result<(LotteryState, Vec<TicketEntry>), ApiError> {
+        let data = self
+            .client
+            .get_account_data(&self.pool)
+            .map_err(|err| ApiError::new(502, err))?;
+        let decode_err = |err| ApiError::new(502, format!("decode {} failed: {}", self.pool, err));
+        let pool = LotteryState::unpack_from_slice(&data).map_err(decode_err)?;
+        let entries = LotteryState::unpack_entries(&data).map_err(decode_err)?;
+        Ok((pool, entries))
+    }
+
+    /// Loads the record of `player`, none if the player was never seen.
+    fn load_record(&self, player: &Pubkey) -> Result<Option<PlayerState>, ApiError> {
+        let program_id = self
+            .client
+            .get_account(&self.pool)
+            .map_err(|err| ApiError::new(502, err))?
+            .owner;
+        let (address, _) = PlayerState::find_address(&program_id, &self.pool, player);
+        let account = self
+            .client
+            .get_account_with_commitment(&address, self.client.commitment())
+            .map_err(|err| ApiError::new(502, err))?
+            .value;
+        match account {
+            Some(account) if account.owner == program_id => PlayerState::unpack(&account.data)
+                .map(Some)
+                .map_err(|err| ApiError::new(502, format!("decode {} failed: {}", address, err))),
+            _ => Ok(None),
+        }
+    }
+
+    fn pool_state(&self) -> Result<(LotteryState, AwardState), ApiError> {
+        let (pool, _) = self.load_pool()?;
+        let billboard: AwardState = self.load(&pool.billboard)?;
+        Ok((pool, billboard))
+    }
+
+    /// GET /pool
+    fn pool(&self) -> Result<Value, ApiError> {
+        let (pool, entries) = self.load_pool()?;
+        let billboard: AwardState = self.load(&pool.billboard)?;
+        Ok(json!({
+            "pool": self.pool.to_string(),
+            "campaign_id": pool.campaign_id,
+            "mint": key_or_null(&pool.mint),
+            "vault": key_or_null(&pool.vault),
+            "roll_interval": pool.roll_interval,
+            "last_roll": pool.last_roll,
+            "billboard": pool.billboard.to_string(),
+            "round": billboard.billboard.len(),
+            "fund": pool.fund,
+            "paused": pool.paused,
+            "cancelled": pool.cancelled,
+            "paid_entries": pool.paid_entries,
+            "threshold": {
+                "min_players": pool.min_players,
+                "min_tickets": pool.min_tickets,
+                "extension": pool.threshold_extension,
+                "max_extensions": pool.max_extensions,
+                "round_players": pool.round_players,
+                "extensions": pool.round_extensions,
+                "extended_until": pool.extended_until,
+            },
+            "referral": {
+                "tickets": pool.referral_tickets,
+                "ticket_cap": pool.referral_ticket_cap,
+                "bps": pool.referral_bps,
+                "cap": pool.referral_cap,
+            },
+            "raffle": pool.raffle,
+            "lotto": if pool.is_lotto() {
+                json!({
+                    "picks": pool.lotto_picks,
+                    "range": pool.lotto_range,
+                    "tier_bps": pool.lotto_tier_bps[..pool.lotto_picks as usize].to_vec(),
+                    "drawn": if pool.lotto_drawn == 0 {
+                        Value::Null
+                    } else {
+                        json!(numbers_of(pool.lotto_drawn))
+                    },
+                    "draw_slot": if pool.lotto_slot == 0 {
+                        Value::Null
+                    } else {
+                        json!(pool.lotto_slot)
+                    },
+                })
+            } else {
+                Value::Null
+            },
+            "scratch": if pool.is_scratch() {
+                json!({
+                    "tiers": pool
+                        .scratch_tiers
+                        .iter()
+                        .filter(|tier| tier.odds > 0)
+                        .map(|tier| json!({ "odds": tier.odds, "multiplier": tier.multiplier }))
+                        .collect::<Vec<_>>(),
+                    "liability": pool.scratch_liability,
+                })
+            } else {
+                Value::Null
+            },
+            "player_count": pool.player_count,
+            "tickets": entries.last().map_or(0, |entry| entry.cumulative),
+            "rollover_bps": pool.rollover_bps,
+            "rollover_cap": pool.rollover_cap,
+            "claim_window": pool.claim_window,
+            "price": pool.price,
+            "fee_bps": pool.fee_bps,
+            "prize_fee_bps": pool.prize_fee_bps,
+            "treasury": pool.treasury.to_string(),
+            "fees": pool.fees,
+            "min_balance": pool.min_balance,
+            "min_age_slots": pool.min_age_slots,
+            "utc_offset": pool.utc_offset,
+            "max_tickets_per_round": pool.max_tickets_per_round,
+            "max_daily_spend": pool.max_daily_spend,
+            "gm_delay": pool.gm_delay,
+            "admin_threshold": pool.admin_threshold,
+            "admins": pool
+                .admins
+                .iter()
+                .filter(|admin| **admin != Pubkey::default())
+                .map(|admin| admin.to_string())
+                .collect::<Vec<_>>(),
+            "roles": {
+                "roller": key_or_null(&pool.roller),
+                "treasurer": key_or_null(&pool.treasurer),
+                "pauser": key_or_null(&pool.pauser),
+            },
+            "pending_gm": if pool.pending_at == 0 {
+                Value::Null
+            } else {
+                json!({
+                    "fund": pool.pending_fund,
+                    "price": pool.pending_price,
+                    "effective": pool.pending_at,
+                })
+            },
+            "streak_schedule": pool
+                .streak_schedule
+                .iter()
+                .filter(|tier| tier.days > 0)
+                .map(|tier| json!({ "days": tier.days, "bonus": tier.bonus }))
+                .collect::<Vec<_>>(),
+            "sponsors": sponsors_of(&billboard, billboard.billboard.len()),
+        }))
+    }
+
+    /// GET /players/<PUBKEY>
+    fn player(&self, player: &str) -> Result<Value, ApiError> {
+        let player = Pubkey::from_str(player).map_err(|err| ApiError::new(400, err))?;
+        let (pool, entries) = self.load_pool()?;
+        let today = pool.day_of(self.now()?);
+        let record = self.load_record(&player)?;
+        Ok(json!({
+            "player": player.to_string(),
+            "signed_in": entries.iter().any(|entry| entry.owner == player),
+            "tickets": tickets_of(&entries, &player),
+            "refunds": if pool.cancelled {
+                refunds_of(&entries, &player)
+            } else {
+                Vec::new()
+            },
+            "lotto_picks": entries
+                .iter()
+                .filter(|entry| entry.owner == player && entry.picks != 0)
+                .map(|entry| numbers_of(entry.picks))
+                .collect::<Vec<_>>(),
+            "first_seen_slot": record.as_ref().map(|record| record.first_seen_slot),
+            "last_signin_day": record.as_ref().map(|record| record.last_signin_day),
+            "streak": record.as_ref().map(|record| record.streak),
+            "balance": record.as_ref().map_or(0, |record| record.balance),
+            "scratch_cards": record.as_ref().map_or(0, |record| record.scratch_cards),
+            "referrer": record.as_ref().map_or(Value::Null, |record| key_or_null(&record.referrer)),
+            "referrals": record.as_ref().map_or(0, |record| record.referrals),
+            "bonus_tickets": record.as_ref().map_or(0, |record| record.bonus_tickets),
+            "referral_tickets": record.as_ref().map_or(0, |record| record.referral_tickets),
+            "referral_earned": record.as_ref().map_or(0, |record| record.referral_earned),
+            "tickets_left": match &record {
+                Some(record) => record.tickets_left(&pool),
+                None => PlayerState::default().tickets_left(&pool),
+            },
+            "spend_left": match &record {
+                Some(record) => record.spend_left(&pool, today),
+                None => PlayerState::default().spend_left(&pool, today),
+            },
+        }))
+    }
+
+    /// GET /billboard?offset=&limit=
+    fn billboard(&self, query: &str) -> Result<Value, ApiError> {
+        let mut offset = 0;
+        let mut limit = DEFAULT_LIMIT;
+        for (key, value) in parse_query(query) {
+            let value = value
+                .parse::<usize>()
+                .map_err(|_| ApiError::new(400, format!("invalid {}: {}", key, value)))?;
+            match key {
+                "offset" => offset = value,
+                "limit" => limit = value.min(MAX_LIMIT),
+                _ => {}
+            }
+        }
+        let (_, billboard) = self.pool_state()?;
+        Ok(billboard_page(&billboard, offset, limit))
+    }
+
+    fn route(&self, method: &Method, url: &str) -> Result<Value, ApiError> {
+        if *method != Method::Get {
+            return Err(ApiError::new(405, "method not allowed"));
+        }
+        let (path, query) = match url.find('?') {
+            Some(i) => (&url[..i], &url[i + 1..]),
+            None => (url, ""),
+        };
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        match segments.as_slice() {
+            ["pool"] => self.pool(),
+            ["players", player] => self.player(player),
+            ["billboard"] => self.billboard(query),
+            _ => Err(ApiError::new(404, "not found")),
+        }
+    }
+}
+
+fn parse_query(query: &str) -> impl Iterator<Item = (&str, &str)> {
+    query.split('&').filter(|s| !s.is_empty()).map(|pair| match pair.find('=') {
+        Some(i) => (&pair[..i], &pair[i + 1..]),
+        None => (pair, ""),
+    })
+}
+
+/// A configured key, `null` while unset.
+fn key_or_null(key: &Pubkey) -> Value {
+    if *key == Pubkey::default() {
+        Value::Null
+    } else {
+        Value::String(key.to_string())
+    }
+}
+
+/// Tickets held by `player` in the current round.
+fn tickets_of(entries: &[TicketEntry], player: &Pubkey) -> u64 {
+    let mut previous = 0;
+    let mut tickets = 0;
+    for entry in entries {
+        if entry.owner == *player {
+            tickets += entry.cumulative - previous;
+        }
+        previous = entry.cumulative;
+    }
+    tickets
+}
+
+/// Entries of `player` a cancelled round still owes a refund, with their index
+/// for the Refund instruction and the referral share it takes back.
+fn refunds_of(entries: &[TicketEntry], player: &Pubkey) -> Vec<Value> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.owner == *player && entry.paid > 0 && !entry.refunded)
+        .map(|(index, entry)| json!({ "index": index, "amount": entry.paid, "referral": entry.referral }))
+        .collect()
+}
+
+fn sponsor_to_json(sponsor: &SponsorBill) -> Value {
+    json!({
+        "account": sponsor.account.to_string(),
+        "amount": sponsor.amount,
+        "timestamp": sponsor.timestamp,
+    })
+}
+
+fn sponsors_of(billboard: &AwardState, round: usize) -> Vec<Value> {
+    billboard
+        .sponsors
+        .iter()
+        .filter(|sponsor| sponsor.round as usize == round)
+        .map(sponsor_to_json)
+        .collect()
+}
+
+fn status_name(status: AwardStatus) -> &'static str {
+    match status {
+        AwardStatus::Unclaimed => "unclaimed",
+        AwardStatus::Rewarded => "rewarded",
+        AwardStatus::RolledOver => "rolled_over",
+        AwardStatus::Expired => "expired",
+        AwardStatus::Prize => "prize",
+        AwardStatus::Cancelled => "cancelled",
+    }
+}
+
+fn bill_to_json(billboard: &AwardState, round: usize, bill: &AwardBill) -> Value {
+    json!({
+        "round": round,
+        "account": bill.account.to_string(),
+        "award": bill.award,
+        "status": status_name(bill.status),
+        "timestamp": bill.timestamp,
+        "extensions": bill.extensions,
+        "deadline": bill.deadline,
+        "sponsors": sponsors_of(billboard, round),
+    })
+}
+
+fn billboard_page(billboard: &AwardState, offset: usize, limit: usize) -> Value {
+    let bills: Vec<Value> = billboard
+        .billboard
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(limit)
+        .map(|(round, bill)| bill_to_json(billboard, round, bill))
+        .collect();
+    json!({
+        "total": billboard.billboard.len(),
+        "offset": offset,
+        "limit": limit,
+        "bills": bills,
+    })
+}
+
+fn main() {
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .arg(
+            Arg::with_name("json_rpc_url")
+                .long("url")
+                .value_name("URL")
+                .takes_value(true)
+                .default_value("http://localhost:8899")
+                .validator(is_url)
+                .help("JSON RPC URL for the cluster"),
+        )
+        .arg(
+            Arg::with_name("pool")
+                .long("pool")
+                .value_name("POOL_ADDRESS")
+                .takes_value(true)
+                .required(true)
+                .validator(is_valid_pubkey)
+                .help("Address of the lottery pool account"),
+        )
+        .arg(
+            Arg::with_name("bind")
+                .long("bind")
+                .value_name("HOST:PORT")
+                .takes_value(true)
+                .default_value("127.0.0.1:8080")
+                .help("Address to listen on"),
+        )
+        .get_matches();
+
+    let url = value_t_or_exit!(matches, "json_rpc_url", String);
+    let pool = value_t_or_exit!(matches, "pool", Pubkey);
+    let bind = value_t_or_exit!(matches, "bind", String);
+
+    let api = Api {
+        client: RpcClient::new_with_commitment(url, CommitmentConfig::confirmed()),
+        pool,
+    };
+    let server = Server::http(&bind).unwrap_or_else(|err| {
+        eprintln!("listen on {} failed: {}", bind, err);
+        std::process::exit(1);
+    });
+    println!("listening on {}", bind);
+
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    for request in server.incoming_requests() {
+        let (status, body) = match api.route(request.method(), request.url()) {
+            Ok(body) => (200, body),
+            Err(err) => (err.status, json!({ "error": err.message })),
+        };
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        if let Err(err) = request.respond(response) {
+            eprintln!("respond failed: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        let pairs: Vec<_> = parse_query("offset=10&limit=5&flag").collect();
+        assert_eq!(pairs, vec![("offset", "10"), ("limit", "5"), ("flag", "")]);
+        assert_eq!(parse_query("").count(), 0);
+    }
+
+    #[test]
+    fn test_tickets_of() {
+        let alice = Pubkey::new_from_array([1; 32]);
+        let bob = Pubkey::new_from_array([2; 32]);
+        let entries = vec![
+            TicketEntry { owner: alice, cumulative: 2, picks: 0, paid: 0, refunded: false, referral: 0, fee: 0 },
+            TicketEntry { owner: bob, cumulative: 3, picks: 0, paid: 100, refunded: false, referral: 0, fee: 0 },
+            TicketEntry { owner: alice, cumulative: 4, picks: 0, paid: 100, refunded: false, referral: 0, fee: 0 },
+        ];
+        assert_eq!(tickets_of(&entries, &alice), 3);
+        assert_eq!(tickets_of(&entries, &bob), 1);
+        assert_eq!(tickets_of(&entries, &Pubkey::default()), 0);
+    }
+
+    #[test]
+    fn test_refunds_of() {
+        let alice = Pubkey::new_from_array([1; 32]);
+        let bob = Pubkey::new_from_array([2; 32]);
+        let entries = vec![
+            TicketEntry { owner: alice, cumulative: 2, picks: 0, paid: 0, refunded: false, referral: 0, fee: 0 },
+            TicketEntry { owner: bob, cumulative: 3, picks: 0, paid: 100, refunded: true, referral: 0, fee: 0 },
+            TicketEntry { owner: alice, cumulative: 4, picks: 0, paid: 100, refunded: false, referral: 5, fee: 0 },
+        ];
+        assert_eq!(refunds_of(&entries, &alice), vec![json!({ "index": 2, "amount": 100, "referral": 5 })]);
+        assert!(refunds_of(&entries, &bob).is_empty());
+    }
+
+    #[test]
+    fn test_billboard_page() {
+        let mut billboard = AwardState::default();
+        for i in 0..3u8 {
+            billboard.billboard.push(AwardBill {
+                account: Pubkey::new_from_array([i; 32]),
+                award: i as u64,
+                status: AwardStatus::Unclaimed,
+                timestamp: 1608273769,
+                extensions: i,
+                deadline: 1608273769 + 86400,
+            });
+        }
+        billboard.sponsors.push(SponsorBill {
+            account: Pubkey::new_from_array([9; 32]),
+            amount: 9527,
+            round: 1,
+            timestamp: 1608273769,
+        });
+        let page = billboard_page(&billboard, 1, 1);
+        assert_eq!(page["total"], 3);
+        assert_eq!(page["bills"].as_array().unwrap().len(), 1);
+        assert_eq!(page["bills"][0]["round"], 1);
+        assert_eq!(page["bills"][0]["award"], 1);
+        assert_eq!(page["bills"][0]["status"], "unclaimed");
+        assert_eq!(page["bills"][0]["extensions"], 1);
+        assert_eq!(page["bills"][0]["deadline"], 1608273769 + 86400);
+        assert_eq!(page["bills"][0]["sponsors"][0]["amount"], 9527);
+
+        billboard.billboard[2].status = AwardStatus::Expired;
+        let page = billboard_page(&billboard, 2, 1);
+        assert_eq!(page["bills"][0]["status"], "expired");
+
+        let page = billboard_page(&billboard, 5, 20);
+        assert_eq!(page["bills"].as_array().unwrap().len(), 0);
+    }
+}
@@ -38,11 +38,42 @@ use solana_clap_utils::{
     },
     ArgConstant,
 };
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    rpc_filter::RpcFilterType,
+};
+use solana_sdk::{
+    account::from_account,
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Signature,
+    sysvar,
+};
+use solana_transaction_status::UiTransactionEncoding;
+use solong_lottery::{
+    draw::{numbers_of, winner_index, winning_ticket},
+    event::LotteryEvent,
+    state::{AwardState, LotteryState, PlayerState, LOTTERY_STATE_LEN},
+};
 use std::fmt::Display;
 use std::str::FromStr;
 
 
 static WARNING: Emoji = Emoji("⚠️", "!");
+static CHECK: Emoji = Emoji("✅", "ok");
+static CROSS: Emoji = Emoji("❌", "x");
+
+type Error = Box<dyn std::error::Error>;
+type CommandResult = Result<(), Error>;
+
+struct Config {
+    rpc_client: RpcClient,
+    verbose: bool,
+}
 
 pub fn is_fund<T>(found: T) -> Result<(), String>
 where
@@ -58,6 +89,410 @@ where
     }
 }
 
+/// Returns the lottery events logged by `program` in every successful
+/// transaction touching `address`, oldest first.
+fn lottery_events(config: &Config, program: &Pubkey, address: &Pubkey) -> Result<Vec<LotteryEvent>, Error> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = config.rpc_client.get_signatures_for_address_with_config(
+            address,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: None,
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )?;
+        if page.is_empty() {
+            break;
+        }
+        for status in page {
+            let signature = Signature::from_str(&status.signature)?;
+            before = Some(signature);
+            if status.err.is_none() {
+                signatures.push(signature);
+            }
+        }
+    }
+    signatures.reverse();
+
+    let invoke_prefix = format!("Program {} invoke", program);
+    let mut events = Vec::new();
+    for signature in signatures {
+        let tx = config.rpc_client.get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        let logs: Option<Vec<String>> = match tx.transaction.meta {
+            Some(meta) => meta.log_messages.into(),
+            None => None,
+        };
+        // only trust lines logged while the lottery program is running
+        let mut stack: Vec<bool> = Vec::new();
+        for line in logs.unwrap_or_default() {
+            if line.starts_with("Program ") && line.contains(" invoke [") {
+                stack.push(line.starts_with(&invoke_prefix));
+            } else if line.starts_with("Program ") && (line.contains(" success") || line.contains(" failed")) {
+                stack.pop();
+            } else if stack.last() == Some(&true) {
+                if let Some(event) = LotteryEvent::decode(&line) {
+                    if config.verbose {
+                        println!("{} {:?}", signature, event);
+                    }
+                    events.push(event);
+                }
+            }
+        }
+    }
+    Ok(events)
+}
+
+fn command_verify_draw(config: &Config, pool: &Pubkey, round: usize) -> CommandResult {
+    let pool_account = config.rpc_client.get_account(pool)?;
+    let program = pool_account.owner;
+    let pool_state = LotteryState::unpack_from_slice(&pool_account.data)?;
+    let billboard = AwardState::unpack(&config.rpc_client.get_account_data(&pool_state.billboard)?)?;
+    let bill = billboard
+        .billboard
+        .get(round)
+        .ok_or_else(|| format!("round {} not found, {} rounds drawn", round, billboard.billboard.len()))?;
+
+    // replay the events: players get tickets, every roll draws among them and starts over.
+    // each bill takes a round, so events are matched to bills by round number
+    let mut players: Vec<Pubkey> = Vec::new();
+    let mut cumulative: Vec<u64> = Vec::new();
+    let mut next = 0;
+    let mut lotto = None;
+    for event in lottery_events(config, &program, pool)? {
+        let entry = match event {
+            LotteryEvent::SignedIn { player, tickets, .. } => Some((player, tickets as u64)),
+            LotteryEvent::TicketBought { player, .. } => Some((player, 1)),
+            _ => None,
+        };
+        if let Some((player, tickets)) = entry {
+            players.push(player);
+            cumulative.push(cumulative.last().copied().unwrap_or(0) + tickets);
+            continue;
+        }
+        match event {
+            LotteryEvent::RolledOver { fund, timestamp } => {
+                if next < round {
+                    next += 1;
+                    continue;
+                }
+                println!("round       : {}", round);
+                println!("timestamp   : {}", timestamp);
+                if lotto.is_some() {
+                    println!("{} no lotto ticket won, rolled over to {}", CHECK, fund);
+                } else {
+                    println!("{} nobody played, rolled over to {}", CHECK, fund);
+                }
+                return Ok(());
+            }
+            LotteryEvent::Rolled { winner, award, timestamp } => {
+                // a lotto settlement pays a bill to every winning ticket
+                if let Some(numbers) = lotto {
+                    if next < round {
+                        next += 1;
+                        continue;
+                    }
+                    println!("round       : {}", round);
+                    println!("timestamp   : {}", timestamp);
+                    println!("drawn       : {:?}", numbers_of(numbers));
+                    println!("winner      : {} award {}", winner, award);
+                    println!("billboard   : {} award {}", bill.account, bill.award);
+                    println!("{} lotto prize, won by matching numbers rather than a single-winner draw", WARNING);
+                    return Ok(());
+                }
+                if next < round {
+                    next += 1;
+                    players.clear();
+                    cumulative.clear();
+                    continue;
+                }
+                if players.is_empty() {
+                    return Err(format!("no player found before roll of round {}", round).into());
+                }
+                let total = *cumulative.last().unwrap();
+                let index = winner_index(timestamp, &cumulative);
+                let expected = players[index];
+                println!("round       : {}", round);
+                println!("timestamp   : {}", timestamp);
+                println!("entries     : {}", players.len());
+                println!("tickets     : {}", total);
+                println!("ticket      : {}", winning_ticket(timestamp, total));
+                println!("index       : {}", index);
+                println!("recomputed  : {}", expected);
+                println!("billboard   : {} award {}", bill.account, bill.award);
+                let valid = expected == winner
+                    && expected == bill.account
+                    && timestamp == bill.timestamp
+                    && award == bill.award;
+                if valid {
+                    println!("{} draw is valid", CHECK);
+                    return Ok(());
+                }
+                println!("{} draw does NOT match", CROSS);
+                return Err("draw mismatch".into());
+            }
+            // a raffle roll pays a bill to every prize, the next round starts after the last
+            LotteryEvent::PrizeDrawn { round: prize_round, winner, mint } => {
+                if prize_round as usize == round {
+                    println!("round       : {}", round);
+                    println!("winner      : {} prize {}", winner, mint);
+                    println!("billboard   : {}", bill.account);
+                    println!("{} raffle prize, drawn with the other prizes of its roll rather than a single-winner draw", WARNING);
+                    return Ok(());
+                }
+                next = prize_round as usize + 1;
+                players.clear();
+                cumulative.clear();
+            }
+            // the bill of a cancelled round is pushed by its last refund, nothing is drawn
+            LotteryEvent::RoundCancelled { round: cancelled } => {
+                if cancelled as usize == round {
+                    println!("round       : {}", round);
+                    println!("{} round cancelled, its tickets were refunded and nothing was drawn", WARNING);
+                    return Ok(());
+                }
+                next = cancelled as usize + 1;
+                players.clear();
+                cumulative.clear();
+            }
+            LotteryEvent::LottoDrawn { numbers } => lotto = Some(numbers),
+            LotteryEvent::LottoSettled { .. } => {
+                lotto = None;
+                players.clear();
+                cumulative.clear();
+            }
+            _ => {}
+        }
+        if next > round {
+            break;
+        }
+    }
+    Err(format!("roll transaction of round {} not found", round).into())
+}
+
+fn command_show(config: &Config, pool: &Pubkey) -> CommandResult {
+    let data = config.rpc_client.get_account_data(pool)?;
+    let pool_state = LotteryState::unpack_from_slice(&data)?;
+    let entries = LotteryState::unpack_entries(&data)?;
+
+    println!("pool            : {}", pool);
+    println!("campaign        : {}", pool_state.campaign_id);
+    if pool_state.is_token_pool() {
+        println!("mint            : {}", pool_state.mint);
+        println!("vault           : {}", pool_state.vault);
+    } else {
+        println!("mint            : lamports");
+    }
+    if pool_state.paused {
+        println!("status          : {} paused, sign in / buy / roll stopped", WARNING);
+    } else if pool_state.cancelled {
+        println!("status          : {} cancelled, {} tickets to refund", WARNING, pool_state.paid_entries);
+    } else {
+        println!("status          : {} running", CHECK);
+    }
+    if pool_state.raffle {
+        println!("raffle          : next roll draws the NFT prizes");
+    }
+    if pool_state.is_lotto() {
+        println!("lotto           : pick {} of 1..{}", pool_state.lotto_picks, pool_state.lotto_range);
+        for (i, bps) in pool_state.lotto_tier_bps.iter().enumerate().filter(|(_, bps)| **bps > 0) {
+            println!("  match {}       : {} bps", i + 1, bps);
+        }
+        if pool_state.lotto_drawn != 0 {
+            println!(
+                "drawn           : {:?}, settling {} {}/{}",
+                numbers_of(pool_state.lotto_drawn),
+                if pool_state.lotto_paying { "payouts" } else { "winners" },
+                pool_state.lotto_cursor,
+                pool_state.player_count,
+            );
+        } else if pool_state.lotto_slot != 0 {
+            println!("drawn           : sales closed, draws from slot {}", pool_state.lotto_slot);
+        }
+    }
+    if pool_state.is_scratch() {
+        println!("scratch         : liability {}", pool_state.scratch_liability);
+        for tier in pool_state.scratch_tiers.iter().filter(|tier| tier.odds > 0) {
+            println!("  1 in {:<9}: {}x", tier.odds, tier.multiplier);
+        }
+    }
+    println!("billboard       : {}", pool_state.billboard);
+    println!("round           : {}", pool_state.round);
+    println!("fund            : {}", pool_state.fund);
+    println!("price           : {}", pool_state.price);
+    println!("entries         : {}", pool_state.player_count);
+    println!("players         : {}", pool_state.round_players);
+    println!("tickets         : {}", entries.last().map_or(0, |entry| entry.cumulative));
+    println!("fee             : {} bps, prize {} bps", pool_state.fee_bps, pool_state.prize_fee_bps);
+    println!("fees            : {}", pool_state.fees);
+    println!("treasury        : {}", pool_state.treasury);
+    println!("rollover        : {} bps, cap {}", pool_state.rollover_bps, pool_state.rollover_cap);
+    println!("claim window    : {} s", pool_state.claim_window);
+    println!("roll interval   : {} s, last roll at {}", pool_state.roll_interval, pool_state.last_roll);
+    if pool_state.min_players > 0 || pool_state.min_tickets > 0 {
+        println!(
+            "minimum         : {} players, {} tickets, {}",
+            pool_state.min_players,
+            pool_state.min_tickets,
+            if pool_state.threshold_extension > 0 {
+                format!(
+                    "else extended by {} s up to {} times",
+                    pool_state.threshold_extension, pool_state.max_extensions
+                )
+            } else {
+                "else cancelled".to_string()
+            },
+        );
+        if pool_state.round_extensions > 0 {
+            println!(
+                "extended        : {} times, until {}",
+                pool_state.round_extensions, pool_state.extended_until
+            );
+        }
+    }
+    println!("utc offset      : {} s", pool_state.utc_offset);
+    println!("gm delay        : {} s", pool_state.gm_delay);
+    if pool_state.admin_threshold == 0 {
+        println!("admins          : program admin key");
+    } else {
+        let admins: Vec<String> = pool_state
+            .admins
+            .iter()
+            .filter(|admin| **admin != Pubkey::default())
+            .map(|admin| admin.to_string())
+            .collect();
+        println!("admins          : {} of {}", pool_state.admin_threshold, admins.len());
+        for admin in admins {
+            println!("                  {}", admin);
+        }
+    }
+    for (name, holder) in [
+        ("roller", &pool_state.roller),
+        ("treasurer", &pool_state.treasurer),
+        ("pauser", &pool_state.pauser),
+    ] {
+        if *holder == Pubkey::default() {
+            println!("{:<16}: admins only", name);
+        } else {
+            println!("{:<16}: {}", name, holder);
+        }
+    }
+    if pool_state.pending_at != 0 {
+        println!(
+            "pending gm      : {} fund {}, price {}, effective at {}",
+            WARNING, pool_state.pending_fund, pool_state.pending_price, pool_state.pending_at
+        );
+    }
+    Ok(())
+}
+
+fn command_pools(config: &Config, program: &Pubkey) -> CommandResult {
+    // only the headers, the ticket entries make every pool account large
+    let accounts = config.rpc_client.get_program_accounts_with_config(
+        program,
+        RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(LOTTERY_STATE_LEN as u64)]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig {
+                    offset: 0,
+                    length: LotteryState::LEN,
+                }),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?;
+    let mut pools = accounts
+        .iter()
+        .map(|(address, account)| Ok((*address, LotteryState::unpack_from_slice(&account.data)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    pools.sort_by_key(|(_, pool)| pool.campaign_id);
+
+    println!("{:<10} {:<44} {:>6} {:>14} {:>12} {:>8}  status", "campaign", "pool", "round", "fund", "price", "entries");
+    for (address, pool) in &pools {
+        // pools created before campaigns were keypair accounts
+        let campaign = if LotteryState::find_address(program, pool.campaign_id).0 == *address {
+            pool.campaign_id.to_string()
+        } else {
+            "-".to_string()
+        };
+        println!(
+            "{:<10} {:<44} {:>6} {:>14} {:>12} {:>8}  {}",
+            campaign,
+            address,
+            pool.round,
+            pool.fund,
+            pool.price,
+            pool.player_count,
+            if pool.paused { "paused" } else { "running" },
+        );
+    }
+    if config.verbose {
+        println!("{} pools", pools.len());
+    }
+    Ok(())
+}
+
+fn command_player(config: &Config, pool: &Pubkey, player: &Pubkey) -> CommandResult {
+    let pool_account = config.rpc_client.get_account(pool)?;
+    let program = pool_account.owner;
+    let pool_state = LotteryState::unpack_from_slice(&pool_account.data)?;
+    let (address, _) = PlayerState::find_address(&program, pool, player);
+    let account = config
+        .rpc_client
+        .get_account_with_commitment(&address, CommitmentConfig::confirmed())?
+        .value;
+    let record = match account {
+        Some(account) if account.owner == program => PlayerState::unpack(&account.data)?,
+        _ => {
+            println!("{} {} has never signed in", WARNING, player);
+            return Ok(());
+        }
+    };
+
+    println!("player          : {}", player);
+    println!("record          : {}", address);
+    println!("first seen slot : {}", record.first_seen_slot);
+    println!("last sign in day: {}", record.last_signin_day);
+    println!("streak          : {} days", record.streak);
+    // the streak survives until the end of the day after the last sign in
+    // the cluster's clock as the program sees it, the latest block may have no time yet
+    let clock: Clock = from_account(&config.rpc_client.get_account(&sysvar::clock::id())?)
+        .ok_or("decode clock sysvar failed")?;
+    let today = pool_state.day_of(clock.unix_timestamp);
+    if record.last_signin_day < today-1 {
+        println!("{} streak broken, next sign in starts over", CROSS);
+    } else {
+        let next = if record.last_signin_day == today { record.streak } else { record.streak+1 };
+        println!("next bonus      : {} tickets", pool_state.streak_bonus(next));
+    }
+    match record.tickets_left(&pool_state) {
+        Some(left) => println!("tickets left    : {} this round", left),
+        None => println!("tickets left    : unlimited"),
+    }
+    match record.spend_left(&pool_state, today) {
+        Some(left) => println!("spend left      : {} lamports today", left),
+        None => println!("spend left      : unlimited"),
+    }
+    if record.referrer != Pubkey::default() {
+        println!("referred by     : {}", record.referrer);
+    }
+    println!("referrals       : {}", record.referrals);
+    println!("referral bonus  : {} tickets earned, {} for the next sign in", record.referral_tickets, record.bonus_tickets);
+    println!("referral share  : {} earned, {} to claim", record.referral_earned, record.balance);
+    Ok(())
+}
+
 fn main() {
     let app_matches = App::new(crate_name!())
     .about(crate_description!())
@@ -104,17 +539,116 @@ fn main() {
                 .help("fundaental for pool"),
         ),
     )
+    .subcommand(SubCommand::with_name("verify-draw").about("Verify the winner of a round")
+        .arg(
+            Arg::with_name("pool")
+                .long("pool")
+                .validator(is_valid_pubkey)
+                .value_name("POOL_ADDRESS")
+                .takes_value(true)
+                .required(true)
+                .help("address of the lottery pool"),
+        )
+        .arg(
+            Arg::with_name("round")
+                .validator(is_parsable::<usize>)
+                .value_name("ROUND")
+                .takes_value(true)
+                .index(1)
+                .required(true)
+                .help("round to verify, index in the billboard"),
+        ),
+    )
+    .subcommand(SubCommand::with_name("show").about("Show a lottery pool")
+        .arg(
+            Arg::with_name("pool")
+                .long("pool")
+                .validator(is_valid_pubkey)
+                .value_name("POOL_ADDRESS")
+                .takes_value(true)
+                .required(true)
+                .help("address of the lottery pool"),
+        ),
+    )
+    .subcommand(SubCommand::with_name("pools").about("List the lottery pools of a program")
+        .arg(
+            Arg::with_name("program")
+                .long("program")
+                .validator(is_valid_pubkey)
+                .value_name("PROGRAM_ID")
+                .takes_value(true)
+                .required(true)
+                .help("address of the lottery program"),
+        ),
+    )
+    .subcommand(SubCommand::with_name("player").about("Show a player's sign in record and streak")
+        .arg(
+            Arg::with_name("pool")
+                .long("pool")
+                .validator(is_valid_pubkey)
+                .value_name("POOL_ADDRESS")
+                .takes_value(true)
+                .required(true)
+                .help("address of the lottery pool"),
+        )
+        .arg(
+            Arg::with_name("player")
+                .validator(is_valid_pubkey)
+                .value_name("PLAYER_ADDRESS")
+                .takes_value(true)
+                .index(1)
+                .required(true)
+                .help("address of the player"),
+        ),
+    )
     .get_matches();
         
     let (sub_command, sub_matches) = app_matches.subcommand();
     let matches = sub_matches.unwrap();
     let verbose = matches.is_present("verbose");
-    println!("verbose:{}", verbose);
 
-    let _ = match (sub_command, sub_matches) {
-        _ => println!("default subcommand"),
+    let json_rpc_url = if let Some(url) = matches.value_of("json_rpc_url") {
+        url.to_string()
+    } else {
+        let cli_config = if let Some(config_file) = matches.value_of("config_file") {
+            solana_cli_config::Config::load(config_file).unwrap_or_default()
+        } else {
+            solana_cli_config::Config::default()
+        };
+        cli_config.json_rpc_url
+    };
+    let config = Config {
+        rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
+        verbose,
     };
 
+    let result = match (sub_command, sub_matches) {
+        ("verify-draw", Some(arg_matches)) => {
+            let pool = value_t_or_exit!(arg_matches, "pool", Pubkey);
+            let round = value_t_or_exit!(arg_matches, "round", usize);
+            command_verify_draw(&config, &pool, round)
+        }
+        ("show", Some(arg_matches)) => {
+            let pool = value_t_or_exit!(arg_matches, "pool", Pubkey);
+            command_show(&config, &pool)
+        }
+        ("pools", Some(arg_matches)) => {
+            let program = value_t_or_exit!(arg_matches, "program", Pubkey);
+            command_pools(&config, &program)
+        }
+        ("player", Some(arg_matches)) => {
+            let pool = value_t_or_exit!(arg_matches, "pool", Pubkey);
+            let player = value_t_or_exit!(arg_matches, "player", Pubkey);
+            command_player(&config, &pool, &player)
+        }
+        _ => {
+            println!("default subcommand");
+            Ok(())
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("{} {}", WARNING, err);
+        std::process::exit(1);
+    }
 
-    println!("{} Hello, world!",WARNING);
 }
@@ -2,7 +2,7 @@
 const lottery = require("@solong/lottery.js");
 const solana  = require("@solana/web3.js");
 
-function main() {
+async function main() {
     //let url =  'http://api.mainnet-beta.solana.com';
     //let url =  'http://119.28.234.214:8899';
     let url =  'https://devnet.solana.com';
@@ -16,9 +16,15 @@ function main() {
     let billboard = new solana.PublicKey("GiTauptuEQThbuS9HbJVLmQinXx1WyS9xHzUemAs49pz");
     let pool = new solana.PublicKey("5TdmTJcSM6NVUXnE7o13kwk8diEXFRwTgZWx4x1sVURb");
     
+    let record = await lottery.SolongLottery.findPlayerAddress(
+        programID,
+        pool,
+        playerAccount.publicKey,
+      );
     let trxi = lottery.SolongLottery.createSignInstruction(
         playerAccount.publicKey,
         pool,
+        record,
         programID,
       );
   
@@ -125,6 +125,7 @@ class Content extends React.Component {
       this.playerAccount.publicKey,
       //this.billboardAccountKey,
       this.billboardAccount.publicKey,
+      this.poolAccount.publicKey,
       this.programID,
     );
 
@@ -151,14 +152,20 @@ class Content extends React.Component {
       let player = new Account();
       this.connection.requestAirdrop(player.publicKey, 10*1000000000).then(()=>{
         setTimeout(
-          ()=>{
+          async ()=>{
 // timeout
             console.log("player:", player.publicKey.toBase58())
+        let record = await SolongLottery.findPlayerAddress(
+          this.programID,
+          this.poolAccount.publicKey,
+          player.publicKey,
+        );
         let trxi = SolongLottery.createSignInstruction(
           //this.playerAccount.publicKey,
           //this.poolAccountKey,
           player.publicKey,
           this.poolAccount.publicKey,
+          record,
           this.programID,
         );
   
@@ -9,8 +9,21 @@ export function publicKey(property) {
     return BufferLayout.blob(32, property);
 }
 
-export const poolSpace =  8+32+2+32*10000
-export const awardSpace = 2+1000*(32+8+1+8);
+// pool account: the LotteryState header followed by MAX_PLAYER ticket entries
+export const poolHeaderSpace = 693;
+export const maxPlayer = 10000;
+// owner, cumulative tickets, picks, paid, refunded, referral share, fee
+export const ticketEntrySpace = 32+8+8+8+1+8+8;
+export const poolSpace = poolHeaderSpace+ticketEntrySpace*maxPlayer;
+
+// billboard account: award bills, then sponsor bills, then the owning pool
+export const maxAward = 1000;
+// account, award, status, timestamp, extensions, deadline
+export const awardBillSpace = 32+8+1+8+1+8;
+export const maxSponsor = 1000;
+// account, amount, round, timestamp
+export const sponsorBillSpace = 32+8+2+8;
+export const awardSpace = 2+maxAward*awardBillSpace+2+maxSponsor*sponsorBillSpace+32;
 
 /**
  * Layout for LotteryInitState 
@@ -18,4 +31,4 @@ export const awardSpace = 2+1000*(32+8+1+8);
 
 export const LotteryInitState = BufferLayout.struct([
 
-]);
\ No newline at end of file
+]);
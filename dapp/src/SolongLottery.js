@@ -6,9 +6,14 @@ import {
     PublicKey,
     SystemProgram,
     TransactionInstruction,
-    SYSVAR_CLOCK_PUBKEY } from "@solana/web3.js"
+    SYSVAR_CLOCK_PUBKEY,
+    SYSVAR_RENT_PUBKEY } from "@solana/web3.js"
 import {u64} from '@solana/spl-token'
 import {intFromBytes} from './utils'
+import {
+    poolHeaderSpace,
+    ticketEntrySpace,
+    awardBillSpace } from './Layout'
 import * as BufferLayout from 'buffer-layout';
 
 
@@ -16,6 +21,110 @@ import * as BufferLayout from 'buffer-layout';
  * SolongLottery
  */
 export class SolongLottery {
+    /**
+     * Address of the pool of `campaignId`
+     *
+     */
+    static async findPoolAddress(
+        programID,
+        campaignId,
+    ) {
+        const [address] = await PublicKey.findProgramAddress(
+            [Buffer.from("pool"), new u64(campaignId).toBuffer()],
+            programID,
+        );
+        return address;
+    }
+
+    /**
+     * Address of the record of `playerAccountKey` in the pool
+     *
+     */
+    static async findPlayerAddress(
+        programID,
+        poolAccountKey,
+        playerAccountKey,
+    ) {
+        const [address] = await PublicKey.findProgramAddress(
+            [Buffer.from("player"), poolAccountKey.toBuffer(), playerAccountKey.toBuffer()],
+            programID,
+        );
+        return address;
+    }
+
+    /**
+     * Construct a CreatePool instruction
+     *
+     */
+    static createCreatePoolInstruction(
+        payerAccountKey,
+        poolAccountKey,
+        programID,
+        campaignId,
+    ) {
+
+        const dataLayout = BufferLayout.struct([
+            BufferLayout.u8("i"),
+            BufferLayout.blob(8,"campaignId"),
+        ]);
+      
+        const data = Buffer.alloc(dataLayout.span);
+        dataLayout.encode(
+            {
+              i:25, // create pool instruct 
+              campaignId:new u64(campaignId).toBuffer(),
+            },
+            data,
+        );
+      
+        let keys = [
+            {pubkey: payerAccountKey, isSigner: true, isWritable: true},
+            {pubkey: poolAccountKey, isSigner: false, isWritable: true},
+            {pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false},
+            {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+        ];
+
+        const  trxi = new TransactionInstruction({
+            keys,
+            programId: programID,
+            data,
+        });
+        return trxi;
+    }
+
+    /**
+     * Construct an ExtendPool instruction, repeat until the pool reaches its full size
+     *
+     */
+    static createExtendPoolInstruction(
+        poolAccountKey,
+        programID,
+    ) {
+
+        const dataLayout = BufferLayout.struct([
+            BufferLayout.u8("i"),
+        ]);
+      
+        const data = Buffer.alloc(dataLayout.span);
+        dataLayout.encode(
+            {
+              i:26, // extend pool instruct 
+            },
+            data,
+        );
+      
+        let keys = [
+            {pubkey: poolAccountKey, isSigner: false, isWritable: true},
+        ];
+
+        const  trxi = new TransactionInstruction({
+            keys,
+            programId: programID,
+            data,
+        });
+        return trxi;
+    }
+
     /**
      * Construct an  Initialize instruction
      *
@@ -46,9 +155,10 @@ export class SolongLottery {
         );
       
         let keys = [
-            {pubkey: adminAccountKey, isSigner: false, isWritable: true},
+            {pubkey: adminAccountKey, isSigner: true, isWritable: false},
             {pubkey: poolAccountKey, isSigner: false, isWritable: true},
             {pubkey: billboardAccountKey, isSigner: false, isWritable: true},
+            {pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false},
         ];
 
         const  trxi = new TransactionInstruction({
@@ -66,6 +176,7 @@ export class SolongLottery {
     static createSignInstruction(
         playerAccountKey,
         poolAccountKey,
+        recordAccountKey,
         programID,
     ) {
 
@@ -84,6 +195,10 @@ export class SolongLottery {
         let keys = [
             {pubkey: playerAccountKey, isSigner: true, isWritable: true},
             {pubkey: poolAccountKey, isSigner: false, isWritable: true},
+            {pubkey: recordAccountKey, isSigner: false, isWritable: true},
+            {pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false},
+            {pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false},
+            {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
         ];
 
         const  trxi = new TransactionInstruction({
@@ -124,8 +239,9 @@ export class SolongLottery {
         );
       
         let keys = [
-            {pubkey: adminAccountKey, isSigner: true, isWritable: true},
+            {pubkey: adminAccountKey, isSigner: true, isWritable: false},
             {pubkey: poolAccountKey, isSigner: false, isWritable: true},
+            {pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false},
         ];
 
         const  trxi = new TransactionInstruction({
@@ -160,8 +276,8 @@ export class SolongLottery {
         );
       
         let keys = [
-            {pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: true},
-            {pubkey: adminAccountKey, isSigner: true, isWritable: true},
+            {pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false},
+            {pubkey: adminAccountKey, isSigner: true, isWritable: false},
             {pubkey: poolAccountKey, isSigner: false, isWritable: true},
             {pubkey: billboardAccountKey, isSigner: false, isWritable: true},
         ];
@@ -184,6 +300,7 @@ export class SolongLottery {
         adminAccountKey,
         playerAccountKey,
         billboardAccountKey,
+        poolAccountKey,
         programID,
     ) {
 
@@ -200,10 +317,136 @@ export class SolongLottery {
         );
       
         let keys = [
-            {pubkey: SystemProgram.programId, isSigner: false, isWritable: true},
-            {pubkey: adminAccountKey, isSigner: true, isWritable: true},
+            {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+            {pubkey: adminAccountKey, isSigner: true, isWritable: false},
             {pubkey: playerAccountKey, isSigner: false, isWritable: true},
             {pubkey: billboardAccountKey, isSigner: false, isWritable: true},
+            {pubkey: poolAccountKey, isSigner: false, isWritable: true},
+            {pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false},
+        ];
+
+        const  trxi = new TransactionInstruction({
+            keys,
+            programId: programID,
+            data,
+        });
+        return trxi;
+    }
+
+    /**
+     * Construct a fund instruction, sponsoring the current round
+     *
+     */
+    static createFundInstruction(
+        sponsorAccountKey,
+        poolAccountKey,
+        billboardAccountKey,
+        programID,
+        amount,
+    ) {
+
+        const dataLayout = BufferLayout.struct([
+            BufferLayout.u8("i"),
+            BufferLayout.blob(8,"amount"),
+        ]);
+      
+        const data = Buffer.alloc(dataLayout.span);
+        dataLayout.encode(
+            {
+              i:6, // fund instruct 
+              amount:new u64(amount).toBuffer(),
+            },
+            data,
+        );
+      
+        let keys = [
+            {pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false},
+            {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+            {pubkey: sponsorAccountKey, isSigner: true, isWritable: true},
+            {pubkey: poolAccountKey, isSigner: false, isWritable: true},
+            {pubkey: billboardAccountKey, isSigner: false, isWritable: true},
+        ];
+
+        const  trxi = new TransactionInstruction({
+            keys,
+            programId: programID,
+            data,
+        });
+        return trxi;
+    }
+
+    /**
+     * Construct a buy instruction, a ticket at the pool's price
+     *
+     */
+    static createBuyInstruction(
+        playerAccountKey,
+        poolAccountKey,
+        recordAccountKey,
+        programID,
+    ) {
+
+        const dataLayout = BufferLayout.struct([
+            BufferLayout.u8("i"),
+        ]);
+      
+        const data = Buffer.alloc(dataLayout.span);
+        dataLayout.encode(
+            {
+              i:10, // buy instruct 
+            },
+            data,
+        );
+      
+        let keys = [
+            {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+            {pubkey: playerAccountKey, isSigner: true, isWritable: true},
+            {pubkey: poolAccountKey, isSigner: false, isWritable: true},
+            {pubkey: recordAccountKey, isSigner: false, isWritable: true},
+            {pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false},
+            {pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false},
+        ];
+
+        const  trxi = new TransactionInstruction({
+            keys,
+            programId: programID,
+            data,
+        });
+        return trxi;
+    }
+
+    /**
+     * Construct a claim instruction, the winner takes the award of `round`
+     *
+     */
+    static createClaimInstruction(
+        winnerAccountKey,
+        poolAccountKey,
+        billboardAccountKey,
+        programID,
+        round,
+    ) {
+
+        const dataLayout = BufferLayout.struct([
+            BufferLayout.u8("i"),
+            BufferLayout.u16("round"),
+        ]);
+      
+        const data = Buffer.alloc(dataLayout.span);
+        dataLayout.encode(
+            {
+              i:46, // claim instruct 
+              round:round,
+            },
+            data,
+        );
+      
+        let keys = [
+            {pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false},
+            {pubkey: winnerAccountKey, isSigner: true, isWritable: true},
+            {pubkey: poolAccountKey, isSigner: false, isWritable: true},
+            {pubkey: billboardAccountKey, isSigner: false, isWritable: true},
+            {pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false},
         ];
 
         const  trxi = new TransactionInstruction({
@@ -233,50 +476,43 @@ export class SolongLottery {
             const pool_buf = result.value.data[0];
             const pool = Buffer.from(pool_buf, 'base64');
             //console.log("pool:", pool);
-            if (poolAccountKey.toBase58() == "Gi1Fa9NTw5MufrQqb7axjQHrZfpqDnvrPUPntmTNX9Xq") {
-                const award = intFromBytes(pool.slice(0,8));
-                const fund = intFromBytes(pool.slice(8,16));
-                const price = intFromBytes(pool.slice(16,24));
-                const playerCount = intFromBytes(pool.slice(56,58));
-                //console.log("Player count:", playerCount);
-                let players = new Map();
-                for(let i=0; i< playerCount; i++) {
-                    const offset = 58+i*35;
-                    const playerAccountKey =  new PublicKey(pool.slice(offset,offset+32)).toBase58(); 
-                    const playerLottery =  intFromBytes(pool.slice(offset+32,offset+32+2));
-                    players.set(playerAccountKey, playerLottery);
-                }
-
-                lottery = {
-                    award:award,
-                    fund:fund,
-                    price:price,
-                    players:players,
-                };
-                console.log("lottery:", lottery);
-
-                return lottery; 
-            } else {
-                const fund = intFromBytes(pool.slice(0,8));
-                const playerCount = intFromBytes(pool.slice(40,42));
-                //console.log("Player count:", playerCount);
-                let players = new Map();
-                for(let i=0; i< playerCount; i++) {
-                    const offset = 42+i*32;
-                    const playerAccountKey =  new PublicKey(pool.slice(offset,offset+32)).toBase58(); 
-                    players.set(playerAccountKey, 1);
-                }
-
-                lottery = {
-                    award:0,
-                    fund:fund,
-                    price:0,
-                    players:players,
-                };
-                console.log("lottery:", lottery);
-
-                return lottery; 
+            const fund = intFromBytes(pool.slice(0,8));
+            const billboard = new PublicKey(pool.slice(8,40)).toBase58();
+            const claimWindow = intFromBytes(pool.slice(50,58));
+            const price = intFromBytes(pool.slice(58,66));
+            const fees = intFromBytes(pool.slice(102,110));
+            const round = intFromBytes(pool.slice(146,150));
+            const paused = pool[162] == 1;
+            const campaignId = intFromBytes(pool.slice(452,460));
+            const cancelled = pool[634] == 1;
+            const playerCount = intFromBytes(pool.slice(691,693));
+            //console.log("Player count:", playerCount);
+            // entries hold cumulative ticket counts , a player may own several
+            let players = new Map();
+            let cumulative = 0;
+            for(let i=0; i< playerCount; i++) {
+                const offset = poolHeaderSpace+i*ticketEntrySpace;
+                const playerAccountKey =  new PublicKey(pool.slice(offset,offset+32)).toBase58(); 
+                const total = intFromBytes(pool.slice(offset+32,offset+40));
+                players.set(playerAccountKey, (players.get(playerAccountKey) || 0) + total - cumulative);
+                cumulative = total;
             }
+
+            lottery = {
+                fund:fund,
+                price:price,
+                fees:fees,
+                round:round,
+                billboard:billboard,
+                claimWindow:claimWindow,
+                campaignId:campaignId,
+                paused:paused,
+                cancelled:cancelled,
+                players:players,
+            };
+            console.log("lottery:", lottery);
+
+            return lottery; 
         } else {
             return null;
         }
@@ -305,16 +541,20 @@ export class SolongLottery {
             const awardCount = intFromBytes(pool.slice(0,2));
             //console.log("awardCount count:", awardCount);
             for(let i=0; i< awardCount; i++) {
-                const index = 2+(32+8+1+8)*i;
+                const index = 2+awardBillSpace*i;
                 const key =  new PublicKey(pool.slice(index,index+32)); 
                 const award =  intFromBytes(pool.slice(index+32,index+40));
-                const reward =  pool.slice(index+40,index+41)[0];
+                const status =  pool.slice(index+40,index+41)[0];
                 const timestamp =  intFromBytes(pool.slice(index+41,index+49));
+                const extensions =  pool.slice(index+49,index+50)[0];
+                const deadline =  intFromBytes(pool.slice(index+50,index+58));
                 const record = {
                     account:key.toBase58(),
                     award: award,
-                    reward:reward,
+                    status:status,
                     timestamp:timestamp,
+                    extensions:extensions,
+                    deadline:deadline,
                 };
                 billboard.push(record)
             }
@@ -0,0 +1,376 @@
+mod store;
+
+use clap::{
+    crate_description,
+    crate_name,
+    crate_version,
+    value_t_or_exit,
+    App,
+    Arg,
+};
+use solana_clap_utils::input_validators::{
+    is_parsable,
+    is_url,
+    is_valid_pubkey,
+};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::Signature,
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta,
+    UiTransactionEncoding,
+};
+use solong_lottery::{
+    event::LotteryEvent,
+    instruction::LotteryInstruction,
+};
+use std::{str::FromStr, thread, time::Duration};
+use store::{Entry, Store, TxRecord};
+
+type Error = Box<dyn std::error::Error>;
+
+/// max signatures the RPC returns for one request
+const SIGNATURE_PAGE: usize = 1000;
+
+/// Returns the signatures of `program` after `until`, oldest first.
+fn new_signatures(
+    client: &RpcClient,
+    program: &Pubkey,
+    until: Option<Signature>,
+) -> Result<Vec<(Signature, u64, bool)>, Error> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = client.get_signatures_for_address_with_config(
+            program,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some(SIGNATURE_PAGE),
+                commitment: Some(CommitmentConfig::finalized()),
+            },
+        )?;
+        let last = page.len() < SIGNATURE_PAGE;
+        for status in page {
+            let signature = Signature::from_str(&status.signature)?;
+            before = Some(signature);
+            signatures.push((signature, status.slot, status.err.is_none()));
+        }
+        if last {
+            break;
+        }
+    }
+    signatures.reverse();
+    Ok(signatures)
+}
+
+/// Position of the pool among the accounts of `instruction`.
+fn pool_position(instruction: &LotteryInstruction) -> usize {
+    match instruction {
+        LotteryInstruction::ExtendPool => 0,
+        LotteryInstruction::Roll
+        | LotteryInstruction::Buy
+        | LotteryInstruction::BuyLotto { .. }
+        | LotteryInstruction::SettleLotto { .. }
+        | LotteryInstruction::BuyScratch { .. }
+        | LotteryInstruction::RevealScratch
+        | LotteryInstruction::CancelRound
+        | LotteryInstruction::Expire { .. }
+        | LotteryInstruction::Claim { .. } => 2,
+        LotteryInstruction::Fund { .. } => 3,
+        LotteryInstruction::Reward => 4,
+        _ => 1,
+    }
+}
+
+/// Decodes the lottery instructions and events of a transaction into rows.
+fn decode_transaction(
+    program: &Pubkey,
+    signature: &Signature,
+    tx: EncodedConfirmedTransactionWithStatusMeta,
+) -> TxRecord {
+    let mut record = TxRecord {
+        signature: signature.to_string(),
+        slot: tx.slot,
+        block_time: tx.block_time,
+        entries: Vec::new(),
+    };
+    let meta = match tx.transaction.meta {
+        Some(meta) if meta.err.is_none() => meta,
+        _ => return record,
+    };
+    let message = match tx.transaction.transaction.decode() {
+        Some(transaction) => transaction.message,
+        None => return record,
+    };
+    let keys = message.static_account_keys();
+    let instructions: Vec<Option<(LotteryInstruction, String)>> = message
+        .instructions()
+        .iter()
+        .map(|ix| {
+            if keys.get(ix.program_id_index as usize) != Some(program) {
+                return None;
+            }
+            let instruction = LotteryInstruction::unpack(&ix.data).ok()?;
+            let pool = ix
+                .accounts
+                .get(pool_position(&instruction))
+                .and_then(|&index| keys.get(index as usize))?;
+            Some((instruction, pool.to_string()))
+        })
+        .collect();
+
+    // walk the logs, keeping track of the running program and top level instruction
+    let logs: Option<Vec<String>> = meta.log_messages.into();
+    let invoke_prefix = format!("Program {} invoke", program);
+    let mut stack: Vec<bool> = Vec::new();
+    let mut ix_index: i64 = -1;
+    for line in logs.unwrap_or_default() {
+        if line.starts_with("Program ") && line.contains(" invoke [") {
+            if line.ends_with(" invoke [1]") {
+                ix_index += 1;
+            }
+            stack.push(line.starts_with(&invoke_prefix));
+            continue;
+        }
+        if line.starts_with("Program ") && (line.contains(" success") || line.contains(" failed")) {
+            stack.pop();
+            continue;
+        }
+        if stack.last() != Some(&true) || ix_index < 0 {
+            continue;
+        }
+        let event = match LotteryEvent::decode(&line) {
+            Some(event) => event,
+            None => continue,
+        };
+        // rows belong to the pool of their instruction
+        let (instruction, pool) = match instructions.get(ix_index as usize).cloned().flatten() {
+            Some(instruction) => instruction,
+            None => continue,
+        };
+        let ix_index = ix_index as u32;
+        let entry = match (instruction, event) {
+            (_, LotteryEvent::SignedIn { player, tickets, .. }) => Entry::Ticket {
+                ix_index,
+                pool,
+                player: player.to_string(),
+                kind: "signin",
+                count: tickets,
+                price: 0,
+            },
+            (_, LotteryEvent::TicketBought { player, price }) => Entry::Ticket {
+                ix_index,
+                pool,
+                player: player.to_string(),
+                kind: "buy",
+                count: 1,
+                price,
+            },
+            (LotteryInstruction::Roll, LotteryEvent::Rolled { winner, award, timestamp }) => Entry::Round {
+                ix_index,
+                pool,
+                winner: winner.to_string(),
+                award,
+                timestamp,
+            },
+            (_, LotteryEvent::RolledOver { fund, timestamp }) => Entry::Rollover {
+                ix_index,
+                pool,
+                fund,
+                timestamp,
+            },
+            (_, LotteryEvent::RoundCancelled { .. }) => Entry::Cancel {
+                ix_index,
+                pool,
+            },
+            (_, LotteryEvent::Rewarded { player, award }) => Entry::Payout {
+                ix_index,
+                pool,
+                player: player.to_string(),
+                award,
+            },
+            (_, LotteryEvent::Expired { round, player, award }) => Entry::Expiry {
+                ix_index,
+                pool,
+                round,
+                player: player.to_string(),
+                award,
+            },
+            (_, LotteryEvent::FeeCollected { fee, fees }) => Entry::Fee {
+                ix_index,
+                pool,
+                fee,
+                fees,
+            },
+            (_, LotteryEvent::Refunded { player, amount }) => Entry::Refund {
+                ix_index,
+                pool,
+                player: player.to_string(),
+                amount,
+            },
+            (_, LotteryEvent::LottoDrawn { numbers }) => Entry::LottoDraw {
+                ix_index,
+                pool,
+                numbers,
+            },
+            (_, LotteryEvent::LottoSettled { winners, fund }) => Entry::LottoSettle {
+                ix_index,
+                pool,
+                winners,
+                fund,
+            },
+            (_, LotteryEvent::Referred { player, referrer, tickets }) => Entry::Referral {
+                ix_index,
+                pool,
+                player: player.to_string(),
+                referrer: referrer.to_string(),
+                tickets,
+            },
+            (_, LotteryEvent::ScratchBought { player, cards, .. }) => Entry::Scratch {
+                ix_index,
+                pool,
+                player: player.to_string(),
+                event: "bought",
+                cards: Some(cards),
+                amount: None,
+            },
+            (_, LotteryEvent::ScratchRevealed { player, cards, won }) => Entry::Scratch {
+                ix_index,
+                pool,
+                player: player.to_string(),
+                event: "revealed",
+                cards: Some(cards),
+                amount: Some(won),
+            },
+            (_, LotteryEvent::ScratchClaimed { player, amount }) => Entry::Scratch {
+                ix_index,
+                pool,
+                player: player.to_string(),
+                event: "claimed",
+                cards: None,
+                amount: Some(amount),
+            },
+            (_, LotteryEvent::Funded { sponsor, amount }) => Entry::Sponsor {
+                ix_index,
+                pool,
+                sponsor: sponsor.to_string(),
+                amount,
+            },
+            _ => continue,
+        };
+        record.entries.push(entry);
+    }
+    record
+}
+
+/// Indexes every transaction since the cursor, returns how many were processed.
+fn sync(client: &RpcClient, program: &Pubkey, store: &mut Store) -> Result<usize, Error> {
+    let until = match store.cursor()? {
+        Some((_, signature)) => Some(Signature::from_str(&signature)?),
+        None => None,
+    };
+    let signatures = new_signatures(client, program, until)?;
+    for (signature, slot, ok) in &signatures {
+        let record = if *ok {
+            let tx = client.get_transaction_with_config(
+                signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(CommitmentConfig::finalized()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )?;
+            decode_transaction(program, signature, tx)
+        } else {
+            // failed transactions change nothing, only move the cursor
+            TxRecord {
+                signature: signature.to_string(),
+                slot: *slot,
+                ..TxRecord::default()
+            }
+        };
+        store.apply(&record)?;
+        println!("slot {} {} : {} rows", record.slot, record.signature, record.entries.len());
+    }
+    Ok(signatures.len())
+}
+
+fn main() {
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .arg(
+            Arg::with_name("json_rpc_url")
+                .long("url")
+                .value_name("URL")
+                .takes_value(true)
+                .default_value("http://localhost:8899")
+                .validator(is_url)
+                .help("JSON RPC URL for the cluster"),
+        )
+        .arg(
+            Arg::with_name("program")
+                .long("program")
+                .value_name("PROGRAM_ID")
+                .takes_value(true)
+                .required(true)
+                .validator(is_valid_pubkey)
+                .help("Address of the solong-lottery program"),
+        )
+        .arg(
+            Arg::with_name("db")
+                .long("db")
+                .value_name("PATH")
+                .takes_value(true)
+                .default_value("lottery.sqlite")
+                .help("SQLite database to write to"),
+        )
+        .arg(
+            Arg::with_name("interval")
+                .long("interval")
+                .value_name("SECONDS")
+                .takes_value(true)
+                .default_value("10")
+                .validator(is_parsable::<u64>)
+                .help("Seconds between two polls"),
+        )
+        .arg(
+            Arg::with_name("once")
+                .long("once")
+                .takes_value(false)
+                .help("Exit after catching up instead of following"),
+        )
+        .get_matches();
+
+    let url = value_t_or_exit!(matches, "json_rpc_url", String);
+    let program = value_t_or_exit!(matches, "program", Pubkey);
+    let db = value_t_or_exit!(matches, "db", String);
+    let interval = value_t_or_exit!(matches, "interval", u64);
+    let once = matches.is_present("once");
+
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::finalized());
+    let mut store = Store::open(&db).unwrap_or_else(|err| {
+        eprintln!("open {} failed: {}", db, err);
+        std::process::exit(1);
+    });
+
+    loop {
+        if let Err(err) = sync(&client, &program, &mut store) {
+            eprintln!("sync failed: {}", err);
+            if once {
+                std::process::exit(1);
+            }
+        }
+        if once {
+            break;
+        }
+        thread::sleep(Duration::from_secs(interval));
+    }
+}
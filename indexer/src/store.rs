@@ -0,0 +1,646 @@
+//! SQLite storage of the lottery history
+
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cursor (
+    id          INTEGER PRIMARY KEY CHECK (id = 0),
+    slot        INTEGER NOT NULL,
+    signature   TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS rounds (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    pool        TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    ix_index    INTEGER NOT NULL,
+    slot        INTEGER NOT NULL,
+    outcome     TEXT NOT NULL,
+    winner      TEXT,
+    award       INTEGER NOT NULL,
+    timestamp   INTEGER,
+    UNIQUE (pool, signature, ix_index)
+);
+CREATE TABLE IF NOT EXISTS tickets (
+    pool        TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    ix_index    INTEGER NOT NULL,
+    slot        INTEGER NOT NULL,
+    block_time  INTEGER,
+    player      TEXT NOT NULL,
+    kind        TEXT NOT NULL,
+    count       INTEGER NOT NULL,
+    price       INTEGER NOT NULL,
+    UNIQUE (pool, signature, ix_index)
+);
+CREATE TABLE IF NOT EXISTS payouts (
+    pool        TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    ix_index    INTEGER NOT NULL,
+    seq         INTEGER NOT NULL,
+    slot        INTEGER NOT NULL,
+    block_time  INTEGER,
+    player      TEXT NOT NULL,
+    award       INTEGER NOT NULL,
+    UNIQUE (pool, signature, ix_index, seq)
+);
+CREATE TABLE IF NOT EXISTS expirations (
+    pool        TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    ix_index    INTEGER NOT NULL,
+    slot        INTEGER NOT NULL,
+    block_time  INTEGER,
+    round       INTEGER NOT NULL,
+    player      TEXT NOT NULL,
+    award       INTEGER NOT NULL,
+    UNIQUE (pool, signature, ix_index)
+);
+CREATE TABLE IF NOT EXISTS fees (
+    pool        TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    ix_index    INTEGER NOT NULL,
+    seq         INTEGER NOT NULL,
+    slot        INTEGER NOT NULL,
+    block_time  INTEGER,
+    fee         INTEGER NOT NULL,
+    fees        INTEGER NOT NULL,
+    UNIQUE (pool, signature, ix_index, seq)
+);
+CREATE TABLE IF NOT EXISTS refunds (
+    pool        TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    ix_index    INTEGER NOT NULL,
+    slot        INTEGER NOT NULL,
+    block_time  INTEGER,
+    player      TEXT NOT NULL,
+    amount      INTEGER NOT NULL,
+    UNIQUE (pool, signature, ix_index)
+);
+CREATE TABLE IF NOT EXISTS lotto (
+    pool        TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    ix_index    INTEGER NOT NULL,
+    slot        INTEGER NOT NULL,
+    block_time  INTEGER,
+    event       TEXT NOT NULL,
+    numbers     INTEGER,
+    winners     INTEGER,
+    fund        INTEGER,
+    UNIQUE (pool, signature, ix_index, event)
+);
+CREATE TABLE IF NOT EXISTS referrals (
+    pool        TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    ix_index    INTEGER NOT NULL,
+    slot        INTEGER NOT NULL,
+    block_time  INTEGER,
+    player      TEXT NOT NULL,
+    referrer    TEXT NOT NULL,
+    tickets     INTEGER NOT NULL,
+    UNIQUE (pool, signature, ix_index)
+);
+CREATE TABLE IF NOT EXISTS scratches (
+    pool        TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    ix_index    INTEGER NOT NULL,
+    slot        INTEGER NOT NULL,
+    block_time  INTEGER,
+    player      TEXT NOT NULL,
+    event       TEXT NOT NULL,
+    cards       INTEGER,
+    amount      INTEGER,
+    UNIQUE (pool, signature, ix_index, event)
+);
+CREATE TABLE IF NOT EXISTS sponsors (
+    pool        TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    ix_index    INTEGER NOT NULL,
+    slot        INTEGER NOT NULL,
+    block_time  INTEGER,
+    sponsor     TEXT NOT NULL,
+    amount      INTEGER NOT NULL,
+    UNIQUE (pool, signature, ix_index)
+);
+CREATE INDEX IF NOT EXISTS rounds_pool ON rounds (pool);
+CREATE INDEX IF NOT EXISTS tickets_player ON tickets (player);
+CREATE INDEX IF NOT EXISTS payouts_player ON payouts (player);
+CREATE INDEX IF NOT EXISTS refunds_player ON refunds (player);
+CREATE INDEX IF NOT EXISTS scratches_player ON scratches (player);
+";
+
+/// One decoded row of a transaction
+#[derive(Clone, Debug, PartialEq)]
+pub enum Entry {
+    /// a ticket got by sign in or purchase
+    Ticket {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// player's account
+        player: String,
+        /// "signin" or "buy"
+        kind: &'static str,
+        /// tickets got , more than one with a sign in streak bonus
+        count: u16,
+        /// price paid , unit lamports
+        price: u64,
+    },
+    /// a finished round
+    Round {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// winner's account
+        winner: String,
+        /// award for the winner
+        award: u64,
+        /// timestamp of the roll
+        timestamp: i64,
+    },
+    /// a round nobody played, its fund went to the next round
+    Rollover {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// fund of the next round
+        fund: u64,
+        /// timestamp of the roll
+        timestamp: i64,
+    },
+    /// a round called off , its tickets refundable
+    Cancel {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+    },
+    /// an award sent to a winner
+    Payout {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// winner's account
+        player: String,
+        /// award sent , unit lamports
+        award: u64,
+    },
+    /// an unclaimed award that expired back to the pool
+    Expiry {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// round of the award , index in billboard
+        round: u16,
+        /// winner's account
+        player: String,
+        /// award returned , unit lamports
+        award: u64,
+    },
+    /// a protocol fee collected into the pool
+    Fee {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// fee collected , unit lamports
+        fee: u64,
+        /// fees in the pool not withdrawn yet , unit lamports
+        fees: u64,
+    },
+    /// a ticket of a cancelled round paid back
+    Refund {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// player's account
+        player: String,
+        /// amount paid back
+        amount: u64,
+    },
+    /// a lotto roll drew its winning numbers
+    LottoDraw {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// numbers drawn , bit `n-1` for `n`
+        numbers: u64,
+    },
+    /// a lotto roll settled , its winners awarded
+    LottoSettle {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// winning tickets
+        winners: u16,
+        /// fund of the next round
+        fund: u64,
+    },
+    /// a first sign in referred by another player
+    Referral {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// player's account
+        player: String,
+        /// referrer's account
+        referrer: String,
+        /// bonus tickets the referrer earned
+        tickets: u16,
+    },
+    /// scratch cards bought , revealed or cashed out
+    Scratch {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// player's account
+        player: String,
+        /// "bought" , "revealed" or "claimed"
+        event: &'static str,
+        /// cards bought or revealed
+        cards: Option<u16>,
+        /// winnings credited or withdrawn
+        amount: Option<u64>,
+    },
+    /// a sponsorship of the current round
+    Sponsor {
+        /// index of the instruction in the transaction
+        ix_index: u32,
+        /// pool of the instruction
+        pool: String,
+        /// sponsor's account
+        sponsor: String,
+        /// lamports funded
+        amount: u64,
+    },
+}
+
+/// Everything the indexer keeps of one transaction
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TxRecord {
+    /// transaction signature
+    pub signature: String,
+    /// slot of the transaction
+    pub slot: u64,
+    /// block time of the transaction
+    pub block_time: Option<i64>,
+    /// decoded rows, empty for failed or unrelated transactions
+    pub entries: Vec<Entry>,
+}
+
+/// SQLite store
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Opens (and creates if needed) the database at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Opens a throwaway in-memory database.
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Store { conn })
+    }
+
+    /// Returns the last processed slot and signature.
+    pub fn cursor(&self) -> Result<Option<(u64, String)>> {
+        self.conn
+            .query_row("SELECT slot, signature FROM cursor WHERE id = 0", params![], |row| {
+                Ok((row.get::<_, i64>(0)? as u64, row.get(1)?))
+            })
+            .optional()
+    }
+
+    /// Stores a transaction and moves the cursor to it, atomically.
+    ///
+    /// Applying the same record twice is a no-op.
+    pub fn apply(&mut self, record: &TxRecord) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for (seq, entry) in record.entries.iter().enumerate() {
+            match entry {
+                Entry::Ticket { ix_index, pool, player, kind, count, price } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO tickets
+                         (pool, signature, ix_index, slot, block_time, player, kind, count, price)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            record.block_time,
+                            player,
+                            kind,
+                            count,
+                            *price as i64
+                        ],
+                    )?;
+                }
+                Entry::Round { ix_index, pool, winner, award, timestamp } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO rounds
+                         (pool, signature, ix_index, slot, outcome, winner, award, timestamp)
+                         VALUES (?1, ?2, ?3, ?4, 'drawn', ?5, ?6, ?7)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            winner,
+                            *award as i64,
+                            timestamp
+                        ],
+                    )?;
+                }
+                Entry::Rollover { ix_index, pool, fund, timestamp } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO rounds
+                         (pool, signature, ix_index, slot, outcome, winner, award, timestamp)
+                         VALUES (?1, ?2, ?3, ?4, 'rolled_over', NULL, ?5, ?6)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            *fund as i64,
+                            timestamp
+                        ],
+                    )?;
+                }
+                Entry::Cancel { ix_index, pool } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO rounds
+                         (pool, signature, ix_index, slot, outcome, winner, award, timestamp)
+                         VALUES (?1, ?2, ?3, ?4, 'cancelled', NULL, 0, ?5)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            record.block_time
+                        ],
+                    )?;
+                }
+                Entry::Payout { ix_index, pool, player, award } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO payouts
+                         (pool, signature, ix_index, seq, slot, block_time, player, award)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            seq as i64,
+                            record.slot as i64,
+                            record.block_time,
+                            player,
+                            *award as i64
+                        ],
+                    )?;
+                }
+                Entry::Expiry { ix_index, pool, round, player, award } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO expirations
+                         (pool, signature, ix_index, slot, block_time, round, player, award)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            record.block_time,
+                            round,
+                            player,
+                            *award as i64
+                        ],
+                    )?;
+                }
+                Entry::Fee { ix_index, pool, fee, fees } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO fees
+                         (pool, signature, ix_index, seq, slot, block_time, fee, fees)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            seq as i64,
+                            record.slot as i64,
+                            record.block_time,
+                            *fee as i64,
+                            *fees as i64
+                        ],
+                    )?;
+                }
+                Entry::Refund { ix_index, pool, player, amount } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO refunds
+                         (pool, signature, ix_index, slot, block_time, player, amount)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            record.block_time,
+                            player,
+                            *amount as i64
+                        ],
+                    )?;
+                }
+                Entry::LottoDraw { ix_index, pool, numbers } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO lotto
+                         (pool, signature, ix_index, slot, block_time, event, numbers, winners, fund)
+                         VALUES (?1, ?2, ?3, ?4, ?5, 'drawn', ?6, NULL, NULL)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            record.block_time,
+                            *numbers as i64
+                        ],
+                    )?;
+                }
+                Entry::LottoSettle { ix_index, pool, winners, fund } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO lotto
+                         (pool, signature, ix_index, slot, block_time, event, numbers, winners, fund)
+                         VALUES (?1, ?2, ?3, ?4, ?5, 'settled', NULL, ?6, ?7)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            record.block_time,
+                            winners,
+                            *fund as i64
+                        ],
+                    )?;
+                }
+                Entry::Referral { ix_index, pool, player, referrer, tickets } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO referrals
+                         (pool, signature, ix_index, slot, block_time, player, referrer, tickets)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            record.block_time,
+                            player,
+                            referrer,
+                            tickets
+                        ],
+                    )?;
+                }
+                Entry::Scratch { ix_index, pool, player, event, cards, amount } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO scratches
+                         (pool, signature, ix_index, slot, block_time, player, event, cards, amount)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            record.block_time,
+                            player,
+                            event,
+                            cards,
+                            amount.map(|amount| amount as i64)
+                        ],
+                    )?;
+                }
+                Entry::Sponsor { ix_index, pool, sponsor, amount } => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO sponsors
+                         (pool, signature, ix_index, slot, block_time, sponsor, amount)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![
+                            pool,
+                            record.signature,
+                            ix_index,
+                            record.slot as i64,
+                            record.block_time,
+                            sponsor,
+                            *amount as i64
+                        ],
+                    )?;
+                }
+            }
+        }
+        tx.execute(
+            "INSERT INTO cursor (id, slot, signature) VALUES (0, ?1, ?2)
+             ON CONFLICT (id) DO UPDATE SET slot = excluded.slot, signature = excluded.signature",
+            params![record.slot as i64, record.signature],
+        )?;
+        tx.commit()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn count(store: &Store, table: &str) -> i64 {
+        store
+            .conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), params![], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_store_apply_idempotent() {
+        let mut store = Store::open_in_memory().unwrap();
+        assert_eq!(store.cursor().unwrap(), None);
+
+        let pool = "pool".to_string();
+        let record = TxRecord {
+            signature: "sig1".to_string(),
+            slot: 10,
+            block_time: Some(1608273769),
+            entries: vec![
+                Entry::Ticket { ix_index: 0, pool: pool.clone(), player: "alice".to_string(), kind: "signin", count: 2, price: 0 },
+                Entry::Round { ix_index: 1, pool: pool.clone(), winner: "alice".to_string(), award: 9527, timestamp: 1608273769 },
+                Entry::Payout { ix_index: 2, pool: pool.clone(), player: "alice".to_string(), award: 9527 },
+                Entry::Rollover { ix_index: 3, pool: pool.clone(), fund: 9527, timestamp: 1608273770 },
+                Entry::Expiry { ix_index: 4, pool: pool.clone(), round: 0, player: "alice".to_string(), award: 9527 },
+                Entry::Cancel { ix_index: 5, pool: pool.clone() },
+                Entry::Fee { ix_index: 6, pool: pool.clone(), fee: 20, fees: 20 },
+                Entry::Fee { ix_index: 6, pool: pool.clone(), fee: 30, fees: 50 },
+                Entry::Refund { ix_index: 7, pool: pool.clone(), player: "alice".to_string(), amount: 100 },
+                Entry::LottoDraw { ix_index: 8, pool: pool.clone(), numbers: 0b111 },
+                Entry::LottoSettle { ix_index: 8, pool: pool.clone(), winners: 1, fund: 0 },
+                Entry::Referral { ix_index: 9, pool: pool.clone(), player: "bob".to_string(), referrer: "alice".to_string(), tickets: 3 },
+                Entry::Scratch { ix_index: 10, pool: pool.clone(), player: "bob".to_string(), event: "bought", cards: Some(2), amount: None },
+                Entry::Scratch { ix_index: 11, pool: pool.clone(), player: "bob".to_string(), event: "claimed", cards: None, amount: Some(500) },
+                Entry::Sponsor { ix_index: 12, pool: pool.clone(), sponsor: "carol".to_string(), amount: 1000 },
+            ],
+        };
+        store.apply(&record).unwrap();
+        store.apply(&record).unwrap();
+        assert_eq!(count(&store, "tickets"), 1);
+        assert_eq!(count(&store, "rounds"), 3);
+        assert_eq!(count(&store, "payouts"), 1);
+        assert_eq!(count(&store, "expirations"), 1);
+        assert_eq!(count(&store, "fees"), 2);
+        assert_eq!(count(&store, "refunds"), 1);
+        assert_eq!(count(&store, "lotto"), 2);
+        assert_eq!(count(&store, "referrals"), 1);
+        assert_eq!(count(&store, "scratches"), 2);
+        assert_eq!(count(&store, "sponsors"), 1);
+        assert_eq!(store.cursor().unwrap(), Some((10, "sig1".to_string())));
+
+        let record = TxRecord {
+            signature: "sig2".to_string(),
+            slot: 11,
+            ..TxRecord::default()
+        };
+        store.apply(&record).unwrap();
+        assert_eq!(store.cursor().unwrap(), Some((11, "sig2".to_string())));
+    }
+
+    #[test]
+    fn test_store_rows_per_pool() {
+        let mut store = Store::open_in_memory().unwrap();
+        let entries = ["pool1", "pool2"]
+            .iter()
+            .map(|pool| Entry::Ticket {
+                ix_index: 0,
+                pool: pool.to_string(),
+                player: "alice".to_string(),
+                kind: "buy",
+                count: 1,
+                price: 100,
+            })
+            .collect();
+        let record = TxRecord {
+            signature: "sig1".to_string(),
+            slot: 10,
+            block_time: None,
+            entries,
+        };
+        store.apply(&record).unwrap();
+        assert_eq!(count(&store, "tickets"), 2);
+    }
+}
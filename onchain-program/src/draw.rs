@@ -0,0 +1,292 @@
+//! Draw helpers shared by the program and off-chain verifiers
+
+use crate::state::{ScratchTier, SCRATCH_SCALE};
+use solana_program::{
+    clock::{Slot, UnixTimestamp},
+    hash::hashv,
+    pubkey::Pubkey,
+};
+use std::convert::TryInto;
+
+/// Winning ticket among `total` tickets for a roll at `timestamp`.
+///
+/// This is the exact function `Roll` uses, so anyone can recompute a draw from
+/// the roll's timestamp and the tickets sold before it.
+pub fn winning_ticket(timestamp: UnixTimestamp, total: u64) -> u64 {
+    (timestamp as u64) % total
+}
+
+/// Winning tickets of `count` prizes among `total` tickets for a roll at `timestamp`.
+///
+/// The first is the [winning_ticket](fn.winning_ticket.html), the others are
+/// spread evenly after it around the tickets, so no ticket wins twice while
+/// there are at least as many tickets as prizes.
+pub fn raffle_tickets(timestamp: UnixTimestamp, total: u64, count: usize) -> Vec<u64> {
+    let first = winning_ticket(timestamp, total);
+    (0..count as u64)
+        .map(|i| ((first as u128 + i as u128 * total as u128 / count as u128) % total as u128) as u64)
+        .collect()
+}
+
+/// Winning numbers of a lotto drawn from `slot_hash`: `picks` distinct numbers
+/// from 1 to `range`, bit `n-1` set for `n`.
+///
+/// A partial Fisher-Yates shuffle of 1..=range driven by splitmix64 seeded
+/// with the hash, so every set is equally likely and anyone can recompute it.
+/// The slot comes after sales close, so nobody knows the hash while they can
+/// still pick numbers.
+pub fn lotto_numbers(slot_hash: &[u8; 32], picks: u8, range: u8) -> u64 {
+    let mut numbers: Vec<u8> = (1..=range).collect();
+    let mut state = u64::from_le_bytes(slot_hash[..8].try_into().unwrap());
+    let mut drawn = 0u64;
+    for i in 0..picks.min(range) as usize {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        let j = i + (z % (numbers.len() - i) as u64) as usize;
+        numbers.swap(i, j);
+        drawn |= 1 << (numbers[i] - 1);
+    }
+    drawn
+}
+
+/// Numbers set in a lotto bitmask, in increasing order.
+pub fn numbers_of(mask: u64) -> Vec<u8> {
+    (0..64u8).filter(|n| mask >> n & 1 == 1).map(|n| n+1).collect()
+}
+
+/// Hash of `slot` in the data of the SlotHashes sysvar, none once it is no
+/// longer kept.
+///
+/// The sysvar is a length prefixed list of (slot, hash) pairs, newest first,
+/// read in place since deserializing all of it costs too much compute.
+pub fn slot_hash(data: &[u8], slot: Slot) -> Option<[u8; 32]> {
+    let len = u64::from_le_bytes(data.get(..8)?.try_into().ok()?) as usize;
+    data.get(8..)?
+        .chunks_exact(40)
+        .take(len)
+        .find(|entry| entry[..8] == slot.to_le_bytes())
+        .map(|entry| entry[8..].try_into().unwrap())
+}
+
+/// Random value of the scratch card `card` bought by `player`, from the hash
+/// of the slot the card was bought in.
+///
+/// The hash is unknown until the slot is over, so the player cannot retry a
+/// purchase until it wins.
+pub fn scratch_random(slot_hash: &[u8; 32], player: &Pubkey, card: u16) -> u64 {
+    let hash = hashv(&[slot_hash, player.as_ref(), &card.to_le_bytes()]);
+    u64::from_le_bytes(hash.to_bytes()[..8].try_into().unwrap())
+}
+
+/// Tier of `tiers` won by a scratch card drawing `random`, none for a loss.
+///
+/// Each tier takes its [chance](../state/struct.ScratchTier.html#method.chance)
+/// out of [SCRATCH_SCALE] outcomes, one after the other.
+pub fn scratch_tier(random: u64, tiers: &[ScratchTier]) -> Option<usize> {
+    let mut outcome = random % SCRATCH_SCALE;
+    for (i, tier) in tiers.iter().enumerate() {
+        let chance = tier.chance();
+        if outcome < chance {
+            return Some(i);
+        }
+        outcome -= chance;
+    }
+    None
+}
+
+/// Index of the entry holding `ticket`, among `len` entries.
+///
+/// `cumulative(i)` is the count of tickets held by entries `0..=i`, so every
+/// entry owns the tickets from the previous cumulative count up to its own
+/// and wins with a chance proportional to its weight. Binary search keeps the
+/// draw cheap with thousands of entries.
+pub fn find_entry<F: Fn(usize) -> u64>(len: usize, ticket: u64, cumulative: F) -> usize {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if cumulative(mid) > ticket {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    low
+}
+
+/// Index of the winning entry for a roll at `timestamp`, entries given by
+/// their cumulative ticket counts.
+pub fn winner_index(timestamp: UnixTimestamp, cumulative: &[u64]) -> usize {
+    let total = cumulative.last().copied().unwrap_or(0);
+    let ticket = winning_ticket(timestamp, total);
+    find_entry(cumulative.len(), ticket, |i| cumulative[i])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// xorshift64, deterministic timestamps for the statistical tests
+    fn next(seed: &mut u64) -> UnixTimestamp {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        (*seed >> 1) as UnixTimestamp
+    }
+
+    fn cumulative_of(weights: &[u64]) -> Vec<u64> {
+        weights
+            .iter()
+            .scan(0, |sum, weight| {
+                *sum += weight;
+                Some(*sum)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_winning_ticket() {
+        assert_eq!(winning_ticket(0, 1), 0);
+        assert_eq!(winning_ticket(1608273769, 1), 0);
+        assert_eq!(winning_ticket(1608273769, 10), 9);
+        assert_eq!(winning_ticket(1608273769, 10000), 3769);
+    }
+
+    #[test]
+    fn test_raffle_tickets() {
+        assert_eq!(raffle_tickets(1608273769, 10, 1), vec![9]);
+        assert_eq!(raffle_tickets(1608273769, 10, 3), vec![9, 2, 5]);
+        assert_eq!(raffle_tickets(1608273769, 2, 3), vec![1, 1, 0]);
+        assert_eq!(raffle_tickets(1608273769, u64::MAX, 2)[1], 1608273769 + u64::MAX / 2);
+
+        let tickets = raffle_tickets(1608273769, 10000, 10);
+        for (i, ticket) in tickets.iter().enumerate() {
+            assert!(!tickets[..i].contains(ticket));
+        }
+    }
+
+    #[test]
+    fn test_lotto_numbers() {
+        let drawn = lotto_numbers(&[7; 32], 6, 49);
+        assert_eq!(drawn.count_ones(), 6);
+        assert_eq!(drawn >> 49, 0);
+        assert_eq!(lotto_numbers(&[7; 32], 6, 49), drawn);
+        assert_ne!(lotto_numbers(&[8; 32], 6, 49), drawn);
+        assert_eq!(lotto_numbers(&[7; 32], 64, 64), u64::MAX);
+        assert_eq!(numbers_of(drawn).len(), 6);
+        assert_eq!(numbers_of(1 | 1 << 2 | 1 << 63), vec![1, 3, 64]);
+
+        // every number is drawn about as often
+        let mut seed = 9527u64;
+        let mut hits = [0u64; 10];
+        let draws = 100_000;
+        for _ in 0..draws {
+            let mut hash = [0u8; 32];
+            hash[..8].copy_from_slice(&next(&mut seed).to_le_bytes());
+            let drawn = lotto_numbers(&hash, 3, 10);
+            for (n, hit) in hits.iter_mut().enumerate() {
+                *hit += drawn >> n & 1;
+            }
+        }
+        for hit in hits.iter() {
+            let expected = draws as f64 * 3.0 / 10.0;
+            assert!((*hit as f64 - expected).abs() / expected < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_find_entry() {
+        let cumulative = cumulative_of(&[1, 3, 1]);
+        let owners: Vec<usize> = (0..5).map(|ticket| find_entry(3, ticket, |i| cumulative[i])).collect();
+        assert_eq!(owners, vec![0, 1, 1, 1, 2]);
+
+        // equal weights draw like the plain index
+        let cumulative = cumulative_of(&[1; 10]);
+        assert_eq!(winner_index(1608273769, &cumulative), 9);
+    }
+
+    #[test]
+    fn test_find_entry_matches_linear_scan() {
+        let mut seed = 9527u64;
+        let weights: Vec<u64> = (0..10000).map(|_| 1 + next(&mut seed) as u64 % 5).collect();
+        let cumulative = cumulative_of(&weights);
+        let total = *cumulative.last().unwrap();
+        for _ in 0..1000 {
+            let ticket = winning_ticket(next(&mut seed), total);
+            let expected = cumulative.iter().position(|sum| *sum > ticket).unwrap();
+            assert_eq!(find_entry(cumulative.len(), ticket, |i| cumulative[i]), expected);
+        }
+    }
+
+    #[test]
+    fn test_winner_index_distribution() {
+        let weights = [1u64, 2, 3, 4, 10];
+        let cumulative = cumulative_of(&weights);
+        let total: u64 = weights.iter().sum();
+        let draws = 200_000;
+
+        let mut seed = 1608273769u64;
+        let mut wins = [0u64; 5];
+        for _ in 0..draws {
+            wins[winner_index(next(&mut seed), &cumulative)] += 1;
+        }
+        for (weight, won) in weights.iter().zip(wins.iter()) {
+            let expected = draws as f64 * *weight as f64 / total as f64;
+            let deviation = (*won as f64 - expected).abs() / expected;
+            assert!(deviation < 0.05, "weight {} won {} times, expected {}", weight, won, expected);
+        }
+    }
+
+    #[test]
+    fn test_slot_hash() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.extend_from_slice(&9528u64.to_le_bytes());
+        data.extend_from_slice(&[1u8; 32]);
+        data.extend_from_slice(&9527u64.to_le_bytes());
+        data.extend_from_slice(&[2u8; 32]);
+        assert_eq!(slot_hash(&data, 9528), Some([1u8; 32]));
+        assert_eq!(slot_hash(&data, 9527), Some([2u8; 32]));
+        assert_eq!(slot_hash(&data, 9526), None);
+        assert_eq!(slot_hash(&data[..48], 9527), None);
+        assert_eq!(slot_hash(&[], 9527), None);
+    }
+
+    #[test]
+    fn test_scratch_tier() {
+        let tiers = [
+            ScratchTier{odds: 10, multiplier: 2},
+            ScratchTier{odds: 1000, multiplier: 100},
+            ScratchTier::default(),
+            ScratchTier::default(),
+        ];
+        assert_eq!(scratch_tier(0, &tiers), Some(0));
+        assert_eq!(scratch_tier(99_999, &tiers), Some(0));
+        assert_eq!(scratch_tier(100_000, &tiers), Some(1));
+        assert_eq!(scratch_tier(100_999, &tiers), Some(1));
+        assert_eq!(scratch_tier(101_000, &tiers), None);
+        assert_eq!(scratch_tier(SCRATCH_SCALE, &tiers), Some(0));
+
+        let player = Pubkey::new_from_array([3u8; 32]);
+        let random = scratch_random(&[1u8; 32], &player, 0);
+        assert_eq!(scratch_random(&[1u8; 32], &player, 0), random);
+        assert_ne!(scratch_random(&[1u8; 32], &player, 1), random);
+        assert_ne!(scratch_random(&[2u8; 32], &player, 0), random);
+
+        // cards win about as often as the table says
+        let mut wins = [0u64; 2];
+        for seed in 0..200u8 {
+            let hash = [seed; 32];
+            for card in 0..1000u16 {
+                if let Some(tier) = scratch_tier(scratch_random(&hash, &player, card), &tiers) {
+                    wins[tier] += 1;
+                }
+            }
+        }
+        let expected = 200_000.0 / 10.0;
+        assert!((wins[0] as f64 - expected).abs() / expected < 0.05);
+        assert!(wins[1] > 100 && wins[1] < 300);
+    }
+}
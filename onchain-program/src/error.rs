@@ -53,6 +53,122 @@ pub enum LotteryError {
     #[error("Too many players")]
     TooManyPlayers, 
 
+    /// too many sponsors
+    #[error("Too many sponsors")]
+    TooManySponsors, 
+
+    /// arithmetic overflow
+    #[error("Arithmetic overflow")]
+    Overflow, 
+
+    /// billboard is full
+    #[error("Billboard is full")]
+    BillboardFull, 
+
+    /// award can not expire
+    #[error("Award can not expire")]
+    NotExpired, 
+
+    /// player account too new to sign in
+    #[error("Player account too new")]
+    AccountTooNew, 
+
+    /// player already signed in today
+    #[error("Already signed in today")]
+    AlreadySignedInToday, 
+
+    /// player bought the max tickets of the round
+    #[error("Ticket cap per round reached")]
+    TicketCapReached, 
+
+    /// player spent the max lamports of the day
+    #[error("Daily spend cap reached")]
+    SpendCapReached, 
+
+    /// pool is paused
+    #[error("Pool is paused")]
+    Paused, 
+
+    /// pending GM change is not effective yet
+    #[error("Pending change not effective yet")]
+    NotEffectiveYet, 
+
+    /// prize can not decrease after tickets are sold
+    #[error("Prize can not decrease after tickets are sold")]
+    PrizeDecrease, 
+
+    /// the pool's schedule does not allow a roll yet
+    #[error("Roll too early")]
+    RollTooEarly, 
+
+    /// token account does not match the pool
+    #[error("Invalid token account")]
+    InvalidTokenAccount, 
+
+    /// raffle prize is not a token of supply 1
+    #[error("Prize is not an NFT")]
+    NotNft, 
+
+    /// raffle escrows as many prizes as it can
+    #[error("Too many prizes")]
+    TooManyPrizes, 
+
+    /// lotto ticket numbers do not fit the pool
+    #[error("Invalid lotto numbers")]
+    InvalidPicks, 
+
+    /// the last lotto roll is still being settled
+    #[error("Settlement pending")]
+    SettlementPending, 
+
+    /// the fund cannot back what the scratch cards could win
+    #[error("Liability exceeded")]
+    LiabilityExceeded, 
+
+    /// scratch cards bought earlier are not revealed yet
+    #[error("Cards pending")]
+    CardsPending, 
+
+    /// scratch cards and lotto numbers are revealed after the slot they were bought or closed in
+    #[error("Reveal too early")]
+    RevealTooEarly, 
+
+    /// the round was cancelled , its tickets are refunded
+    #[error("Round cancelled")]
+    RoundCancelled, 
+
+    /// the ticket was not paid for or is refunded already
+    #[error("Not refundable")]
+    NotRefundable, 
+
+    /// a player can not refer themselves
+    #[error("Self referral")]
+    SelfReferral, 
+
+    /// the referrer has not played yet , joined after the player or the player was already referred
+    #[error("Invalid referrer")]
+    InvalidReferrer, 
+
+    /// the prize is more than the lamports deposited for it
+    #[error("Fund not backed by deposits")]
+    FundNotBacked, 
+
+    /// the pool was initialized already
+    #[error("Already initialized")]
+    AlreadyInitialized, 
+
+    /// a sponsorship has to be worth at least one ticket
+    #[error("Sponsorship too small")]
+    SponsorTooSmall, 
+
+    /// award is past its claim deadline
+    #[error("Award has expired")]
+    AwardExpired, 
+
+    /// billboard was initialized for another pool
+    #[error("Billboard belongs to another pool")]
+    BillboardInUse, 
+
     /// TestError 
     #[error("TestError")]
     TestError,
@@ -85,6 +201,35 @@ impl PrintProgramError for LotteryError {
             LotteryError::LowBalance=> msg!("Low balance for 1 SOL"),
             LotteryError::InvalidAccountForReward=> msg!("Invalid account for reward"),
             LotteryError::TooManyPlayers=> msg!("Too many players"),
+            LotteryError::TooManySponsors=> msg!("Too many sponsors"),
+            LotteryError::Overflow=> msg!("Arithmetic overflow"),
+            LotteryError::BillboardFull=> msg!("Billboard is full"),
+            LotteryError::NotExpired=> msg!("Award can not expire"),
+            LotteryError::AccountTooNew=> msg!("Player account too new"),
+            LotteryError::AlreadySignedInToday=> msg!("Already signed in today"),
+            LotteryError::TicketCapReached=> msg!("Ticket cap per round reached"),
+            LotteryError::SpendCapReached=> msg!("Daily spend cap reached"),
+            LotteryError::Paused=> msg!("Pool is paused"),
+            LotteryError::NotEffectiveYet=> msg!("Pending change not effective yet"),
+            LotteryError::PrizeDecrease=> msg!("Prize can not decrease after tickets are sold"),
+            LotteryError::RollTooEarly=> msg!("Roll too early"),
+            LotteryError::InvalidTokenAccount=> msg!("Invalid token account"),
+            LotteryError::NotNft=> msg!("Prize is not an NFT"),
+            LotteryError::TooManyPrizes=> msg!("Too many prizes"),
+            LotteryError::InvalidPicks=> msg!("Invalid lotto numbers"),
+            LotteryError::SettlementPending=> msg!("Settlement pending"),
+            LotteryError::LiabilityExceeded=> msg!("Liability exceeded"),
+            LotteryError::CardsPending=> msg!("Cards pending"),
+            LotteryError::RevealTooEarly=> msg!("Reveal too early"),
+            LotteryError::RoundCancelled=> msg!("Round cancelled"),
+            LotteryError::NotRefundable=> msg!("Not refundable"),
+            LotteryError::SelfReferral=> msg!("Self referral"),
+            LotteryError::InvalidReferrer=> msg!("Invalid referrer"),
+            LotteryError::FundNotBacked=> msg!("Fund not backed by deposits"),
+            LotteryError::AlreadyInitialized=> msg!("Already initialized"),
+            LotteryError::SponsorTooSmall=> msg!("Sponsorship too small"),
+            LotteryError::AwardExpired=> msg!("Award has expired"),
+            LotteryError::BillboardInUse=> msg!("Billboard belongs to another pool"),
             LotteryError::TestError => msg!("TestError"),
         }
     }
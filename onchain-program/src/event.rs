@@ -0,0 +1,1377 @@
+//! Event types
+//!
+//! Every state change of the program emits one event as a program log line:
+//! [EVENT_PREFIX](constant.EVENT_PREFIX.html) followed by the base64 of the
+//! packed [LotteryEvent](enum.LotteryEvent.html).
+
+use crate::{
+    error::LotteryError,
+    state::Role,
+};
+use num_enum::TryFromPrimitive;
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    clock::UnixTimestamp,
+};
+use std::mem::size_of;
+use std::convert::TryInto;
+
+/// prefix of every event log line
+pub const EVENT_PREFIX: &str = "SOLONG_LOTTERY_EVENT:";
+
+/// Events emitted by the solong-lottery program.
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum LotteryEvent {
+    /// a player signed in and got a ticket
+    SignedIn {
+        /// player's account
+        player: Pubkey,
+        /// consecutive days signed in , including today
+        streak: u32,
+        /// tickets got , one plus the streak bonus
+        tickets: u16,
+    },
+
+    /// a player bought a ticket
+    TicketBought {
+        /// player's account
+        player: Pubkey,
+        /// price paid , unit lamports
+        price: u64,
+    },
+
+    /// a winner was drawn
+    Rolled {
+        /// winner's account
+        winner: Pubkey,
+        /// award for the winner
+        award: u64,
+        /// timestamp of the roll
+        timestamp: UnixTimestamp,
+    },
+
+    /// an award was sent to the winner
+    Rewarded {
+        /// winner's account
+        player: Pubkey,
+        /// award sent , unit lamports
+        award: u64,
+    },
+
+    /// the fund of the pool changed
+    FundChanged {
+        /// new fund of the pool
+        fund: u64,
+    },
+
+    /// a sponsor funded the current round
+    Funded {
+        /// sponsor's account
+        sponsor: Pubkey,
+        /// lamports funded
+        amount: u64,
+    },
+
+    /// nobody played, the fund went to the next round
+    RolledOver {
+        /// fund of the next round
+        fund: u64,
+        /// timestamp of the roll
+        timestamp: UnixTimestamp,
+    },
+
+    /// an unclaimed award expired and went back to the pool
+    Expired {
+        /// round of the award , index in billboard
+        round: u16,
+        /// winner's account
+        player: Pubkey,
+        /// award returned , unit lamports
+        award: u64,
+    },
+
+    /// a protocol fee was collected into the pool
+    FeeCollected {
+        /// fee collected , unit lamports
+        fee: u64,
+        /// fees in the pool not withdrawn yet , unit lamports
+        fees: u64,
+    },
+
+    /// collected fees were sent to the treasury
+    FeesWithdrawn {
+        /// treasury's account
+        treasury: Pubkey,
+        /// lamports withdrawn
+        amount: u64,
+    },
+
+    /// the pool was paused or unpaused
+    PauseChanged {
+        /// whether the pool is paused now
+        paused: bool,
+    },
+
+    /// a GM change was queued
+    GMQueued {
+        /// fund after the change
+        fund: u64,
+        /// price after the change
+        price: u64,
+        /// timestamp the change can be applied at
+        effective: UnixTimestamp,
+    },
+
+    /// the pending GM change was applied
+    GMApplied {
+        /// fund of the pool now
+        fund: u64,
+        /// price of the pool now
+        price: u64,
+    },
+
+    /// the pending GM change was cancelled
+    GMCancelled,
+
+    /// the admin set was replaced
+    AdminsChanged {
+        /// admin signers needed for privileged instructions
+        threshold: u8,
+        /// count of admin signers
+        signers: u8,
+    },
+
+    /// a role was assigned or revoked
+    RoleChanged {
+        /// role assigned
+        role: Role,
+        /// key holding the role , default key when revoked
+        key: Pubkey,
+    },
+
+    /// the pool account of a campaign was created
+    PoolCreated {
+        /// campaign the pool address is derived from
+        campaign_id: u64,
+    },
+
+    /// the pool was denominated in an SPL mint
+    MintSet {
+        /// mint of the pool
+        mint: Pubkey,
+        /// token account holding the pool's tokens
+        vault: Pubkey,
+    },
+
+    /// the next roll of the pool was turned into an NFT raffle
+    RaffleCreated {
+        /// key depositing the prizes
+        sponsor: Pubkey,
+    },
+
+    /// an NFT was escrowed for the raffle
+    PrizeDeposited {
+        /// mint of the NFT
+        mint: Pubkey,
+    },
+
+    /// an NFT was drawn to a winner
+    PrizeDrawn {
+        /// round drawn, index of its award bill in billboard
+        round: u16,
+        /// winner's account
+        winner: Pubkey,
+        /// mint of the NFT
+        mint: Pubkey,
+    },
+
+    /// a winner claimed an NFT
+    PrizeClaimed {
+        /// winner's account
+        winner: Pubkey,
+        /// mint of the NFT
+        mint: Pubkey,
+    },
+
+    /// the raffle was called off before the draw
+    RaffleCancelled,
+
+    /// the sponsor took an NFT back from a cancelled raffle
+    PrizeWithdrawn {
+        /// sponsor's account
+        sponsor: Pubkey,
+        /// mint of the NFT
+        mint: Pubkey,
+    },
+
+    /// a lotto roll drew its winning numbers
+    LottoDrawn {
+        /// numbers drawn , bit `n-1` for `n`
+        numbers: u64,
+    },
+
+    /// a lotto roll was settled , its winners awarded
+    LottoSettled {
+        /// winning tickets
+        winners: u16,
+        /// fund of the next round
+        fund: u64,
+    },
+
+    /// a player bought scratch cards
+    ScratchBought {
+        /// player's account
+        player: Pubkey,
+        /// cards bought
+        cards: u16,
+        /// slot whose hash reveals the cards
+        slot: u64,
+    },
+
+    /// the scratch cards of a player were revealed
+    ScratchRevealed {
+        /// player's account
+        player: Pubkey,
+        /// cards revealed
+        cards: u16,
+        /// winnings credited to the player , after the prize fee
+        won: u64,
+    },
+
+    /// a player withdrew their instant winnings
+    ScratchClaimed {
+        /// player's account
+        player: Pubkey,
+        /// amount withdrawn
+        amount: u64,
+    },
+
+    /// the current round was called off , its tickets are refunded
+    RoundCancelled {
+        /// round cancelled
+        round: u32,
+    },
+
+    /// a ticket of a cancelled round was paid back
+    Refunded {
+        /// player's account
+        player: Pubkey,
+        /// amount paid back
+        amount: u64,
+    },
+
+    /// the current round fell short of the minimum and was extended
+    RoundExtended {
+        /// round extended
+        round: u32,
+        /// timestamp the round can roll at
+        until: UnixTimestamp,
+    },
+
+    /// a player signed in for the first time , referred by another
+    Referred {
+        /// player's account
+        player: Pubkey,
+        /// referrer's account
+        referrer: Pubkey,
+        /// bonus tickets the referrer earned
+        tickets: u16,
+    },
+
+    /// a referrer earned a share of a referred player's purchase
+    ReferralPaid {
+        /// referrer's account
+        referrer: Pubkey,
+        /// amount credited to the referrer's balance
+        amount: u64,
+    },
+}
+
+impl LotteryEvent {
+    /// Unpacks a byte buffer into a [LotteryEvent](enum.LotteryEvent.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        use LotteryError::InvalidInstruction;
+
+        let (&tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
+        Ok(match tag {
+            1 => {
+                let (player, rest) = Self::unpack_pubkey(rest)?;
+                let (streak, rest) = Self::unpack_u32(rest)?;
+                let (tickets, _) = Self::unpack_u16(rest)?;
+                Self::SignedIn{
+                    player,
+                    streak,
+                    tickets,
+                }
+            }
+            2 => {
+                let (player, rest) = Self::unpack_pubkey(rest)?;
+                let (price, _) = Self::unpack_u64(rest)?;
+                Self::TicketBought{
+                    player,
+                    price,
+                }
+            }
+            3 => {
+                let (winner, rest) = Self::unpack_pubkey(rest)?;
+                let (award, rest) = Self::unpack_u64(rest)?;
+                let (timestamp, _) = Self::unpack_u64(rest)?;
+                Self::Rolled{
+                    winner,
+                    award,
+                    timestamp: timestamp as UnixTimestamp,
+                }
+            }
+            4 => {
+                let (player, rest) = Self::unpack_pubkey(rest)?;
+                let (award, _) = Self::unpack_u64(rest)?;
+                Self::Rewarded{
+                    player,
+                    award,
+                }
+            }
+            5 => {
+                let (fund, _) = Self::unpack_u64(rest)?;
+                Self::FundChanged{
+                    fund,
+                }
+            }
+            6 => {
+                let (sponsor, rest) = Self::unpack_pubkey(rest)?;
+                let (amount, _) = Self::unpack_u64(rest)?;
+                Self::Funded{
+                    sponsor,
+                    amount,
+                }
+            }
+            7 => {
+                let (fund, rest) = Self::unpack_u64(rest)?;
+                let (timestamp, _) = Self::unpack_u64(rest)?;
+                Self::RolledOver{
+                    fund,
+                    timestamp: timestamp as UnixTimestamp,
+                }
+            }
+            8 => {
+                let (round, rest) = Self::unpack_u16(rest)?;
+                let (player, rest) = Self::unpack_pubkey(rest)?;
+                let (award, _) = Self::unpack_u64(rest)?;
+                Self::Expired{
+                    round,
+                    player,
+                    award,
+                }
+            }
+            9 => {
+                let (fee, rest) = Self::unpack_u64(rest)?;
+                let (fees, _) = Self::unpack_u64(rest)?;
+                Self::FeeCollected{
+                    fee,
+                    fees,
+                }
+            }
+            10 => {
+                let (treasury, rest) = Self::unpack_pubkey(rest)?;
+                let (amount, _) = Self::unpack_u64(rest)?;
+                Self::FeesWithdrawn{
+                    treasury,
+                    amount,
+                }
+            }
+            11 => {
+                let paused = match rest.first() {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(InvalidInstruction.into()),
+                };
+                Self::PauseChanged{
+                    paused,
+                }
+            }
+            12 => {
+                let (fund, rest) = Self::unpack_u64(rest)?;
+                let (price, rest) = Self::unpack_u64(rest)?;
+                let (effective, _) = Self::unpack_u64(rest)?;
+                Self::GMQueued{
+                    fund,
+                    price,
+                    effective: effective as UnixTimestamp,
+                }
+            }
+            13 => {
+                let (fund, rest) = Self::unpack_u64(rest)?;
+                let (price, _) = Self::unpack_u64(rest)?;
+                Self::GMApplied{
+                    fund,
+                    price,
+                }
+            }
+            14 => Self::GMCancelled,
+            15 => {
+                let (&threshold, rest) = rest.split_first().ok_or(LotteryError::InvalidInstruction)?;
+                let (&signers, _) = rest.split_first().ok_or(LotteryError::InvalidInstruction)?;
+                Self::AdminsChanged{
+                    threshold,
+                    signers,
+                }
+            }
+            16 => {
+                let (&role, rest) = rest.split_first().ok_or(LotteryError::InvalidInstruction)?;
+                let role = Role::try_from_primitive(role)
+                    .map_err(|_| LotteryError::InvalidInstruction)?;
+                let (key, _) = Self::unpack_pubkey(rest)?;
+                Self::RoleChanged{
+                    role,
+                    key,
+                }
+            }
+            17 => {
+                let (campaign_id, _) = Self::unpack_u64(rest)?;
+                Self::PoolCreated{
+                    campaign_id,
+                }
+            }
+            18 => {
+                let (mint, rest) = Self::unpack_pubkey(rest)?;
+                let (vault, _) = Self::unpack_pubkey(rest)?;
+                Self::MintSet{
+                    mint,
+                    vault,
+                }
+            }
+            19 => {
+                let (sponsor, _) = Self::unpack_pubkey(rest)?;
+                Self::RaffleCreated{
+                    sponsor,
+                }
+            }
+            20 => {
+                let (mint, _) = Self::unpack_pubkey(rest)?;
+                Self::PrizeDeposited{
+                    mint,
+                }
+            }
+            21 => {
+                let (round, rest) = Self::unpack_u16(rest)?;
+                let (winner, rest) = Self::unpack_pubkey(rest)?;
+                let (mint, _) = Self::unpack_pubkey(rest)?;
+                Self::PrizeDrawn{
+                    round,
+                    winner,
+                    mint,
+                }
+            }
+            22 => {
+                let (winner, rest) = Self::unpack_pubkey(rest)?;
+                let (mint, _) = Self::unpack_pubkey(rest)?;
+                Self::PrizeClaimed{
+                    winner,
+                    mint,
+                }
+            }
+            23 => Self::RaffleCancelled,
+            24 => {
+                let (sponsor, rest) = Self::unpack_pubkey(rest)?;
+                let (mint, _) = Self::unpack_pubkey(rest)?;
+                Self::PrizeWithdrawn{
+                    sponsor,
+                    mint,
+                }
+            }
+            25 => {
+                let (numbers, _) = Self::unpack_u64(rest)?;
+                Self::LottoDrawn{
+                    numbers,
+                }
+            }
+            26 => {
+                let (winners, rest) = Self::unpack_u16(rest)?;
+                let (fund, _) = Self::unpack_u64(rest)?;
+                Self::LottoSettled{
+                    winners,
+                    fund,
+                }
+            }
+            27 => {
+                let (player, rest) = Self::unpack_pubkey(rest)?;
+                let (cards, rest) = Self::unpack_u16(rest)?;
+                let (slot, _) = Self::unpack_u64(rest)?;
+                Self::ScratchBought{
+                    player,
+                    cards,
+                    slot,
+                }
+            }
+            28 => {
+                let (player, rest) = Self::unpack_pubkey(rest)?;
+                let (cards, rest) = Self::unpack_u16(rest)?;
+                let (won, _) = Self::unpack_u64(rest)?;
+                Self::ScratchRevealed{
+                    player,
+                    cards,
+                    won,
+                }
+            }
+            29 => {
+                let (player, rest) = Self::unpack_pubkey(rest)?;
+                let (amount, _) = Self::unpack_u64(rest)?;
+                Self::ScratchClaimed{
+                    player,
+                    amount,
+                }
+            }
+            30 => {
+                let (round, _) = Self::unpack_u32(rest)?;
+                Self::RoundCancelled{
+                    round,
+                }
+            }
+            31 => {
+                let (player, rest) = Self::unpack_pubkey(rest)?;
+                let (amount, _) = Self::unpack_u64(rest)?;
+                Self::Refunded{
+                    player,
+                    amount,
+                }
+            }
+            32 => {
+                let (round, rest) = Self::unpack_u32(rest)?;
+                let (until, _) = Self::unpack_u64(rest)?;
+                Self::RoundExtended{
+                    round,
+                    until: until as UnixTimestamp,
+                }
+            }
+            33 => {
+                let (player, rest) = Self::unpack_pubkey(rest)?;
+                let (referrer, rest) = Self::unpack_pubkey(rest)?;
+                let (tickets, _) = Self::unpack_u16(rest)?;
+                Self::Referred{
+                    player,
+                    referrer,
+                    tickets,
+                }
+            }
+            34 => {
+                let (referrer, rest) = Self::unpack_pubkey(rest)?;
+                let (amount, _) = Self::unpack_u64(rest)?;
+                Self::ReferralPaid{
+                    referrer,
+                    amount,
+                }
+            }
+            _ => return Err(LotteryError::InvalidInstruction.into()),
+        })
+    }
+
+    /// Packs a [LotteryEvent](enum.LotteryEvent.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            Self::SignedIn {
+                player,
+                streak,
+                tickets,
+            } => {
+                buf.push(1);
+                buf.extend_from_slice(player.as_ref());
+                buf.extend_from_slice(&streak.to_le_bytes());
+                buf.extend_from_slice(&tickets.to_le_bytes());
+            }
+
+            Self::TicketBought {
+                player,
+                price,
+            } => {
+                buf.push(2);
+                buf.extend_from_slice(player.as_ref());
+                buf.extend_from_slice(&price.to_le_bytes());
+            }
+
+            Self::Rolled {
+                winner,
+                award,
+                timestamp,
+            } => {
+                buf.push(3);
+                buf.extend_from_slice(winner.as_ref());
+                buf.extend_from_slice(&award.to_le_bytes());
+                buf.extend_from_slice(&timestamp.to_le_bytes());
+            }
+
+            Self::Rewarded {
+                player,
+                award,
+            } => {
+                buf.push(4);
+                buf.extend_from_slice(player.as_ref());
+                buf.extend_from_slice(&award.to_le_bytes());
+            }
+
+            Self::FundChanged {
+                fund,
+            } => {
+                buf.push(5);
+                buf.extend_from_slice(&fund.to_le_bytes());
+            }
+
+            Self::Funded {
+                sponsor,
+                amount,
+            } => {
+                buf.push(6);
+                buf.extend_from_slice(sponsor.as_ref());
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+
+            Self::RolledOver {
+                fund,
+                timestamp,
+            } => {
+                buf.push(7);
+                buf.extend_from_slice(&fund.to_le_bytes());
+                buf.extend_from_slice(&timestamp.to_le_bytes());
+            }
+
+            Self::Expired {
+                round,
+                player,
+                award,
+            } => {
+                buf.push(8);
+                buf.extend_from_slice(&round.to_le_bytes());
+                buf.extend_from_slice(player.as_ref());
+                buf.extend_from_slice(&award.to_le_bytes());
+            }
+
+            Self::FeeCollected {
+                fee,
+                fees,
+            } => {
+                buf.push(9);
+                buf.extend_from_slice(&fee.to_le_bytes());
+                buf.extend_from_slice(&fees.to_le_bytes());
+            }
+
+            Self::FeesWithdrawn {
+                treasury,
+                amount,
+            } => {
+                buf.push(10);
+                buf.extend_from_slice(treasury.as_ref());
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+
+            Self::PauseChanged {
+                paused,
+            } => {
+                buf.push(11);
+                buf.push(*paused as u8);
+            }
+
+            Self::GMQueued {
+                fund,
+                price,
+                effective,
+            } => {
+                buf.push(12);
+                buf.extend_from_slice(&fund.to_le_bytes());
+                buf.extend_from_slice(&price.to_le_bytes());
+                buf.extend_from_slice(&effective.to_le_bytes());
+            }
+
+            Self::GMApplied {
+                fund,
+                price,
+            } => {
+                buf.push(13);
+                buf.extend_from_slice(&fund.to_le_bytes());
+                buf.extend_from_slice(&price.to_le_bytes());
+            }
+
+            Self::GMCancelled => {
+                buf.push(14);
+            }
+
+            Self::AdminsChanged {
+                threshold,
+                signers,
+            } => {
+                buf.push(15);
+                buf.push(*threshold);
+                buf.push(*signers);
+            }
+
+            Self::RoleChanged {
+                role,
+                key,
+            } => {
+                buf.push(16);
+                buf.push(*role as u8);
+                buf.extend_from_slice(key.as_ref());
+            }
+
+            Self::PoolCreated {
+                campaign_id,
+            } => {
+                buf.push(17);
+                buf.extend_from_slice(&campaign_id.to_le_bytes());
+            }
+
+            Self::MintSet {
+                mint,
+                vault,
+            } => {
+                buf.push(18);
+                buf.extend_from_slice(mint.as_ref());
+                buf.extend_from_slice(vault.as_ref());
+            }
+
+            Self::RaffleCreated {
+                sponsor,
+            } => {
+                buf.push(19);
+                buf.extend_from_slice(sponsor.as_ref());
+            }
+
+            Self::PrizeDeposited {
+                mint,
+            } => {
+                buf.push(20);
+                buf.extend_from_slice(mint.as_ref());
+            }
+
+            Self::PrizeDrawn {
+                round,
+                winner,
+                mint,
+            } => {
+                buf.push(21);
+                buf.extend_from_slice(&round.to_le_bytes());
+                buf.extend_from_slice(winner.as_ref());
+                buf.extend_from_slice(mint.as_ref());
+            }
+
+            Self::PrizeClaimed {
+                winner,
+                mint,
+            } => {
+                buf.push(22);
+                buf.extend_from_slice(winner.as_ref());
+                buf.extend_from_slice(mint.as_ref());
+            }
+
+            Self::RaffleCancelled => {
+                buf.push(23);
+            }
+
+            Self::PrizeWithdrawn {
+                sponsor,
+                mint,
+            } => {
+                buf.push(24);
+                buf.extend_from_slice(sponsor.as_ref());
+                buf.extend_from_slice(mint.as_ref());
+            }
+
+            Self::LottoDrawn {
+                numbers,
+            } => {
+                buf.push(25);
+                buf.extend_from_slice(&numbers.to_le_bytes());
+            }
+
+            Self::LottoSettled {
+                winners,
+                fund,
+            } => {
+                buf.push(26);
+                buf.extend_from_slice(&winners.to_le_bytes());
+                buf.extend_from_slice(&fund.to_le_bytes());
+            }
+
+            Self::ScratchBought {
+                player,
+                cards,
+                slot,
+            } => {
+                buf.push(27);
+                buf.extend_from_slice(player.as_ref());
+                buf.extend_from_slice(&cards.to_le_bytes());
+                buf.extend_from_slice(&slot.to_le_bytes());
+            }
+
+            Self::ScratchRevealed {
+                player,
+                cards,
+                won,
+            } => {
+                buf.push(28);
+                buf.extend_from_slice(player.as_ref());
+                buf.extend_from_slice(&cards.to_le_bytes());
+                buf.extend_from_slice(&won.to_le_bytes());
+            }
+
+            Self::ScratchClaimed {
+                player,
+                amount,
+            } => {
+                buf.push(29);
+                buf.extend_from_slice(player.as_ref());
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+
+            Self::RoundCancelled {
+                round,
+            } => {
+                buf.push(30);
+                buf.extend_from_slice(&round.to_le_bytes());
+            }
+
+            Self::Refunded {
+                player,
+                amount,
+            } => {
+                buf.push(31);
+                buf.extend_from_slice(player.as_ref());
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+
+            Self::RoundExtended {
+                round,
+                until,
+            } => {
+                buf.push(32);
+                buf.extend_from_slice(&round.to_le_bytes());
+                buf.extend_from_slice(&until.to_le_bytes());
+            }
+
+            Self::Referred {
+                player,
+                referrer,
+                tickets,
+            } => {
+                buf.push(33);
+                buf.extend_from_slice(player.as_ref());
+                buf.extend_from_slice(referrer.as_ref());
+                buf.extend_from_slice(&tickets.to_le_bytes());
+            }
+
+            Self::ReferralPaid {
+                referrer,
+                amount,
+            } => {
+                buf.push(34);
+                buf.extend_from_slice(referrer.as_ref());
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+        };
+        buf
+    }
+
+    /// Encodes the event into the log line emitted by the program.
+    pub fn encode(&self) -> String {
+        format!("{}{}", EVENT_PREFIX, base64::encode(self.pack()))
+    }
+
+    /// Decodes an event from a program log line.
+    ///
+    /// Accepts the raw line as returned by RPC (e.g. with the `Program log: `
+    /// prefix); returns `None` if the line is not a lottery event.
+    pub fn decode(log: &str) -> Option<Self> {
+        let start = log.find(EVENT_PREFIX)? + EVENT_PREFIX.len();
+        let data = base64::decode(log[start..].trim()).ok()?;
+        Self::unpack(&data).ok()
+    }
+
+    fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+        if input.len() >= 32 {
+            let (key, rest) = input.split_at(32);
+            let key = key
+                .try_into()
+                .map(Pubkey::new_from_array)
+                .map_err(|_| LotteryError::InvalidInstruction)?;
+            Ok((key, rest))
+        } else {
+            Err(LotteryError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_u32(input: &[u8]) -> Result<(u32, &[u8]), ProgramError> {
+        if input.len() >= 4 {
+            let (amount, rest) = input.split_at(4);
+            let amount = amount
+                .get(..4)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u32::from_le_bytes)
+                .ok_or(LotteryError::InvalidInstruction)?;
+            Ok((amount, rest))
+        } else {
+            Err(LotteryError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+        if input.len() >= 2 {
+            let (amount, rest) = input.split_at(2);
+            let amount = amount
+                .get(..2)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u16::from_le_bytes)
+                .ok_or(LotteryError::InvalidInstruction)?;
+            Ok((amount, rest))
+        } else {
+            Err(LotteryError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+        if input.len() >= 8 {
+            let (amount, rest) = input.split_at(8);
+            let amount = amount
+                .get(..8)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(LotteryError::InvalidInstruction)?;
+            Ok((amount, rest))
+        } else {
+            Err(LotteryError::InvalidInstruction.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_event_signed_in() {
+        let check = LotteryEvent::SignedIn{
+            player: Pubkey::new_from_array([1u8;32]),
+            streak: 7u32,
+            tickets: 2u16,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&[1u8;32]);
+        expect.extend_from_slice(&[7, 0, 0, 0]);
+        expect.extend_from_slice(&[2, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_event_ticket_bought() {
+        let check = LotteryEvent::TicketBought{
+            player: Pubkey::new_from_array([2u8;32]),
+            price: 1_000_000_000u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[2]);
+        expect.extend_from_slice(&[2u8;32]);
+        expect.extend_from_slice(&[0, 202, 154, 59, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_event_rolled() {
+        let check = LotteryEvent::Rolled{
+            winner: Pubkey::new_from_array([3u8;32]),
+            award: 10_000_000_000u64,
+            timestamp: 1608273769,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[3]);
+        expect.extend_from_slice(&[3u8;32]);
+        expect.extend_from_slice(&[0, 228, 11, 84, 2, 0, 0, 0]);
+        expect.extend_from_slice(&[105, 79, 220, 95, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_event_rewarded() {
+        let check = LotteryEvent::Rewarded{
+            player: Pubkey::new_from_array([4u8;32]),
+            award: 9527u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[4]);
+        expect.extend_from_slice(&[4u8;32]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_event_fund_changed() {
+        let check = LotteryEvent::FundChanged{
+            fund: 9527u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[5]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_event_funded() {
+        let check = LotteryEvent::Funded{
+            sponsor: Pubkey::new_from_array([6u8;32]),
+            amount: 9527u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[6]);
+        expect.extend_from_slice(&[6u8;32]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_event_rolled_over() {
+        let check = LotteryEvent::RolledOver{
+            fund: 9527u64,
+            timestamp: 1608273769,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[7]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[105, 79, 220, 95, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_event_expired() {
+        let check = LotteryEvent::Expired{
+            round: 9527u16,
+            player: Pubkey::new_from_array([8u8;32]),
+            award: 9527u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[8]);
+        expect.extend_from_slice(&[55, 37]);
+        expect.extend_from_slice(&[8u8;32]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_event_fee_collected() {
+        let check = LotteryEvent::FeeCollected{
+            fee: 9527u64,
+            fees: 10_000u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[9]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[16, 39, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_event_fees_withdrawn() {
+        let check = LotteryEvent::FeesWithdrawn{
+            treasury: Pubkey::new_from_array([8u8;32]),
+            amount: 9527u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[10]);
+        expect.extend_from_slice(&[8u8;32]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_event_pause_changed() {
+        let check = LotteryEvent::PauseChanged{
+            paused: true,
+        };
+        let packed = check.pack();
+        let expect = vec![11u8, 1];
+        assert_eq!(packed, expect);
+        let unpacked = LotteryEvent::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(LotteryEvent::unpack(&[11u8, 2]).is_err());
+    }
+
+    #[test]
+    fn test_event_gm() {
+        let check = LotteryEvent::GMQueued{
+            fund: 9527u64,
+            price: 10_000u64,
+            effective: 1608273769,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[12]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[16, 39, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[105, 79, 220, 95, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        assert_eq!(LotteryEvent::unpack(&expect).unwrap(), check);
+
+        let check = LotteryEvent::GMApplied{
+            fund: 9527u64,
+            price: 10_000u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[13]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[16, 39, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        assert_eq!(LotteryEvent::unpack(&expect).unwrap(), check);
+
+        let check = LotteryEvent::GMCancelled;
+        assert_eq!(check.pack(), vec![14u8]);
+        assert_eq!(LotteryEvent::unpack(&[14u8]).unwrap(), check);
+    }
+
+    #[test]
+    fn test_event_admins_changed() {
+        let check = LotteryEvent::AdminsChanged{
+            threshold: 2,
+            signers: 3,
+        };
+        assert_eq!(check.pack(), vec![15u8, 2, 3]);
+        assert_eq!(LotteryEvent::unpack(&[15u8, 2, 3]).unwrap(), check);
+        assert!(LotteryEvent::unpack(&[15u8, 2]).is_err());
+    }
+
+    #[test]
+    fn test_event_role_changed() {
+        let check = LotteryEvent::RoleChanged{
+            role: Role::Pauser,
+            key: Pubkey::new_from_array([10u8;32]),
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[16, 3]);
+        expect.extend_from_slice(&[10;32]);
+        assert_eq!(packed, expect);
+        assert_eq!(LotteryEvent::unpack(&expect).unwrap(), check);
+    }
+
+    #[test]
+    fn test_event_pool_created() {
+        let check = LotteryEvent::PoolCreated{
+            campaign_id: 9527,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, vec![17u8, 55, 37, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+    }
+
+    #[test]
+    fn test_event_mint_set() {
+        let check = LotteryEvent::MintSet{
+            mint: Pubkey::new_from_array([11u8;32]),
+            vault: Pubkey::new_from_array([12u8;32]),
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[18]);
+        expect.extend_from_slice(&[11;32]);
+        expect.extend_from_slice(&[12;32]);
+        assert_eq!(packed, expect);
+        assert_eq!(LotteryEvent::unpack(&expect).unwrap(), check);
+    }
+
+    #[test]
+    fn test_event_raffle() {
+        let check = LotteryEvent::RaffleCreated{
+            sponsor: Pubkey::new_from_array([2u8;32]),
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[19u8][..], &[2;32]].concat());
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+
+        let check = LotteryEvent::PrizeDeposited{
+            mint: Pubkey::new_from_array([3u8;32]),
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[20u8][..], &[3;32]].concat());
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+
+        let check = LotteryEvent::PrizeDrawn{
+            round: 9527,
+            winner: Pubkey::new_from_array([5u8;32]),
+            mint: Pubkey::new_from_array([3u8;32]),
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[21, 55, 37]);
+        expect.extend_from_slice(&[5;32]);
+        expect.extend_from_slice(&[3;32]);
+        assert_eq!(packed, expect);
+        assert_eq!(LotteryEvent::unpack(&expect).unwrap(), check);
+        assert!(LotteryEvent::unpack(&expect[..expect.len()-1]).is_err());
+
+        let check = LotteryEvent::PrizeClaimed{
+            winner: Pubkey::new_from_array([5u8;32]),
+            mint: Pubkey::new_from_array([3u8;32]),
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[22u8][..], &[5;32], &[3;32]].concat());
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+
+        let check = LotteryEvent::RaffleCancelled;
+        assert_eq!(check.pack(), vec![23u8]);
+        assert_eq!(LotteryEvent::unpack(&[23u8]).unwrap(), check);
+
+        let check = LotteryEvent::PrizeWithdrawn{
+            sponsor: Pubkey::new_from_array([2u8;32]),
+            mint: Pubkey::new_from_array([3u8;32]),
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[24u8][..], &[2;32], &[3;32]].concat());
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+    }
+
+    #[test]
+    fn test_event_lotto() {
+        let check = LotteryEvent::LottoDrawn{
+            numbers: 0b111111,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, vec![25u8, 63, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+
+        let check = LotteryEvent::LottoSettled{
+            winners: 3,
+            fund: 9527,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, vec![26u8, 3, 0, 55, 37, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+        assert!(LotteryEvent::unpack(&packed[..3]).is_err());
+    }
+
+    #[test]
+    fn test_event_scratch() {
+        let check = LotteryEvent::ScratchBought{
+            player: Pubkey::new_from_array([2u8;32]),
+            cards: 5,
+            slot: 9527,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[27u8][..], &[2;32], &[5, 0], &[55, 37, 0, 0, 0, 0, 0, 0]].concat());
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+        assert!(LotteryEvent::unpack(&packed[..40]).is_err());
+
+        let check = LotteryEvent::ScratchRevealed{
+            player: Pubkey::new_from_array([2u8;32]),
+            cards: 5,
+            won: 200,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[28u8][..], &[2;32], &[5, 0], &[200, 0, 0, 0, 0, 0, 0, 0]].concat());
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+
+        let check = LotteryEvent::ScratchClaimed{
+            player: Pubkey::new_from_array([2u8;32]),
+            amount: 200,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[29u8][..], &[2;32], &[200, 0, 0, 0, 0, 0, 0, 0]].concat());
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+    }
+
+    #[test]
+    fn test_event_refund() {
+        let check = LotteryEvent::RoundCancelled{
+            round: 9527,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, vec![30u8, 55, 37, 0, 0]);
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+        assert!(LotteryEvent::unpack(&packed[..4]).is_err());
+
+        let check = LotteryEvent::Refunded{
+            player: Pubkey::new_from_array([2u8;32]),
+            amount: 100,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[31u8][..], &[2;32], &[100, 0, 0, 0, 0, 0, 0, 0]].concat());
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+    }
+
+    #[test]
+    fn test_event_round_extended() {
+        let check = LotteryEvent::RoundExtended{
+            round: 9527,
+            until: 1608273769,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, vec![32u8, 55, 37, 0, 0, 105, 79, 220, 95, 0, 0, 0, 0]);
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+        assert!(LotteryEvent::unpack(&packed[..12]).is_err());
+    }
+
+    #[test]
+    fn test_event_referral() {
+        let check = LotteryEvent::Referred{
+            player: Pubkey::new_from_array([2u8;32]),
+            referrer: Pubkey::new_from_array([3u8;32]),
+            tickets: 3,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[33u8][..], &[2;32], &[3;32], &[3, 0]].concat());
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+        assert!(LotteryEvent::unpack(&packed[..66]).is_err());
+
+        let check = LotteryEvent::ReferralPaid{
+            referrer: Pubkey::new_from_array([3u8;32]),
+            amount: 50,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[34u8][..], &[3;32], &[50, 0, 0, 0, 0, 0, 0, 0]].concat());
+        assert_eq!(LotteryEvent::unpack(&packed).unwrap(), check);
+    }
+
+    #[test]
+    fn test_event_decode() {
+        let check = LotteryEvent::Rewarded{
+            player: Pubkey::new_from_array([4u8;32]),
+            award: 9527u64,
+        };
+        let line = check.encode();
+        assert!(line.starts_with(EVENT_PREFIX));
+        assert_eq!(LotteryEvent::decode(&line), Some(check.clone()));
+        let line = format!("Program log: {}", line);
+        assert_eq!(LotteryEvent::decode(&line), Some(check));
+
+        assert_eq!(LotteryEvent::decode("Program log: [solong-lottery]:Instruction: Roll"), None);
+        assert_eq!(LotteryEvent::decode(&format!("{}{}", EVENT_PREFIX, "not base64!")), None);
+        assert_eq!(LotteryEvent::decode(&format!("{}{}", EVENT_PREFIX, base64::encode([9u8]))), None);
+    }
+}
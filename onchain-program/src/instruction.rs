@@ -2,10 +2,13 @@
 
 use crate::{
     error::LotteryError,
+    state::{StreakTier, STREAK_TIERS, ScratchTier, MAX_SCRATCH_TIERS, MAX_ADMINS, MAX_LOTTO_PICKS, Role},
 };
 use solana_program::{
     program_error::ProgramError,
+    pubkey::Pubkey,
 };
+use num_enum::TryFromPrimitive;
 use std::mem::size_of;
 use std::convert::TryInto;
 
@@ -37,6 +40,235 @@ pub enum LotteryInstruction {
 
     /// Reward Instruction
     Reward,
+
+    /// Fund Instruction, sponsor the current round
+    Fund {
+        /// lamports to transfer into the pool , at least the ticket price
+        amount : u64,
+    },
+
+    /// SetRollover Instruction, configure the growth of rolled over funds
+    SetRollover {
+        /// growth of the fund per rollover , unit basis points , paid out of the collected fees
+        growth_bps : u16,
+        /// max fund reached by rollover growth , 0 for no cap
+        cap : u64,
+    },
+
+    /// SetClaimWindow Instruction, configure how long winners can claim
+    SetClaimWindow {
+        /// seconds a winner has to claim the award , 0 for never expire
+        seconds : i64,
+    },
+
+    /// Expire Instruction, return an unclaimed award past the claim window to the pool
+    Expire {
+        /// round of the award , index in billboard
+        round : u16,
+    },
+
+    /// Buy Instruction, buy a ticket of current round at the pool's price
+    Buy,
+
+    /// SetFee Instruction, configure the protocol fee and the treasury
+    SetFee {
+        /// fee taken from ticket sales , unit basis points
+        fee_bps : u16,
+        /// fee taken from awards , unit basis points
+        prize_fee_bps : u16,
+    },
+
+    /// WithdrawFees Instruction, send the collected fees to the treasury
+    WithdrawFees,
+
+    /// Register Instruction, create the player record before signing in
+    Register,
+
+    /// SetSignInRule Instruction, configure the anti-sybil checks of sign in
+    SetSignInRule {
+        /// lamports a player must hold to sign in , 0 for no limit
+        min_balance : u64,
+        /// slots since a player was first seen , 0 for no limit
+        min_age_slots : u64,
+    },
+
+    /// SetTimezone Instruction, configure the timezone days are counted in
+    SetTimezone {
+        /// offset from UTC , unit seconds
+        utc_offset : i32,
+    },
+
+    /// SetStreakSchedule Instruction, configure the sign in streak bonuses
+    SetStreakSchedule {
+        /// bonus tiers , days 0 for unused
+        tiers : [StreakTier; STREAK_TIERS],
+    },
+
+    /// SetPurchaseCap Instruction, configure the per player purchase limits
+    SetPurchaseCap {
+        /// tickets a player can buy in a round , 0 for no limit
+        max_tickets_per_round : u32,
+        /// lamports a player can spend on tickets in a day , 0 for no limit
+        max_daily_spend : u64,
+    },
+
+    /// Pause Instruction, stop sign in , buy and roll in an emergency
+    Pause,
+
+    /// Unpause Instruction, resume a paused pool
+    Unpause,
+
+    /// ApplyGM Instruction, apply the pending GM change once effective
+    ApplyGM,
+
+    /// CancelGM Instruction, drop the pending GM change
+    CancelGM,
+
+    /// SetGMDelay Instruction, configure how long GM changes wait
+    SetGMDelay {
+        /// delay of GM changes , unit seconds
+        seconds : i64,
+    },
+
+    /// SetAdmins Instruction, replace the admin set , approved by the current one
+    SetAdmins {
+        /// admin signers needed for privileged instructions
+        threshold : u8,
+        /// admin signers , default key for unused
+        admins : [Pubkey; MAX_ADMINS],
+    },
+
+    /// SetRole Instruction, assign a role , approved by the admin set
+    SetRole {
+        /// role to assign , not the owner
+        role : Role,
+        /// key holding the role , default key to revoke
+        key : Pubkey,
+    },
+
+    /// CreatePool Instruction, create the pool account of a campaign
+    CreatePool {
+        /// campaign the pool address is derived from
+        campaign_id : u64,
+    },
+
+    /// ExtendPool Instruction, grow a created pool toward its full size
+    ExtendPool,
+
+    /// SetSchedule Instruction, configure how often the pool rolls
+    SetSchedule {
+        /// seconds between two rolls , 0 for no limit
+        roll_interval : i64,
+    },
+
+    /// SetMint Instruction, denominate a fresh , unsponsored pool in the SPL mint of its vault
+    SetMint,
+
+    /// CreateRaffle Instruction, turn the next roll of a fresh round into an NFT raffle
+    CreateRaffle,
+
+    /// DepositPrize Instruction, the sponsor escrows an NFT for the raffle
+    DepositPrize,
+
+    /// CancelRaffle Instruction, call off a raffle before the draw
+    CancelRaffle,
+
+    /// ClaimPrize Instruction, the winner takes a drawn NFT out of the escrow
+    ClaimPrize {
+        /// index of the prize in the raffle
+        index : u8,
+    },
+
+    /// WithdrawPrize Instruction, the sponsor takes an NFT back from a cancelled raffle
+    WithdrawPrize {
+        /// index of the prize in the raffle
+        index : u8,
+    },
+
+    /// SetLotto Instruction, turn the pool into a pick-your-numbers lotto
+    SetLotto {
+        /// numbers a ticket picks , 0 for drawing one ticket
+        picks : u8,
+        /// tickets pick numbers from 1 to `range`
+        range : u8,
+        /// share of the fund for tickets matching `i+1` numbers , unit basis points
+        tier_bps : [u16; MAX_LOTTO_PICKS],
+    },
+
+    /// BuyLotto Instruction, buy a lotto ticket picking `numbers`
+    BuyLotto {
+        /// picked numbers , 0 for unused
+        numbers : [u8; MAX_LOTTO_PICKS],
+    },
+
+    /// SettleLotto Instruction, settle up to `count` tickets of the last lotto roll
+    SettleLotto {
+        /// ticket entries to go through
+        count : u16,
+    },
+
+    /// SetScratch Instruction, turn the pool into instant win scratch cards with the odds table `tiers`
+    SetScratch {
+        /// odds and multipliers of the winning tiers , unused tiers have 0 odds
+        tiers : [ScratchTier; MAX_SCRATCH_TIERS],
+    },
+
+    /// BuyScratch Instruction, buy `cards` scratch cards revealed by the hash of the current slot
+    BuyScratch {
+        /// cards to buy
+        cards : u16,
+    },
+
+    /// RevealScratch Instruction, reveal the pending scratch cards of a player
+    RevealScratch,
+
+    /// ClaimScratch Instruction, withdraw the instant winnings of the player
+    ClaimScratch,
+
+    /// CancelRound Instruction, call off the current round and refund its tickets
+    CancelRound,
+
+    /// Refund Instruction, pay back a ticket of a cancelled round
+    Refund {
+        /// index of the ticket entry in the pool
+        index : u16,
+    },
+
+    /// SetThreshold Instruction, configure the minimum a round needs to be drawn
+    SetThreshold {
+        /// distinct players , 0 for no minimum
+        min_players : u16,
+        /// tickets , 0 for no minimum
+        min_tickets : u64,
+        /// seconds a round under the minimum is extended by , 0 to cancel it instead
+        extension : i64,
+        /// times a round is extended before it is cancelled
+        max_extensions : u8,
+    },
+
+    /// SetReferral Instruction, configure what referrers earn
+    SetReferral {
+        /// bonus tickets for each player referred
+        tickets : u16,
+        /// most bonus tickets a referrer earns , 0 for no limit
+        ticket_cap : u32,
+        /// share of a referred player's purchases , unit basis points
+        bps : u16,
+        /// most a referrer earns from purchases , 0 for no limit
+        cap : u64,
+    },
+
+    /// ReferredSignIn Instruction, the first sign in of a player referred by `referrer`
+    ReferredSignIn {
+        /// referrer's account
+        referrer : Pubkey,
+    },
+
+    /// Claim Instruction, the winner takes an award before its deadline
+    Claim {
+        /// round of the award , index in billboard
+        round : u16,
+    },
 }
 
 
@@ -66,6 +298,234 @@ impl LotteryInstruction {
             }
             4 => Self::Roll,
             5 => Self::Reward,
+            6 => {
+                let (amount, _) = Self::unpack_u64(rest)?;
+                Self::Fund{
+                    amount,
+                }
+            }
+            7 => {
+                let (growth_bps, rest) = Self::unpack_u16(rest)?;
+                let (cap, _) = Self::unpack_u64(rest)?;
+                Self::SetRollover{
+                    growth_bps,
+                    cap,
+                }
+            }
+            8 => {
+                let (seconds, _) = Self::unpack_u64(rest)?;
+                Self::SetClaimWindow{
+                    seconds: seconds as i64,
+                }
+            }
+            9 => {
+                let (round, _) = Self::unpack_u16(rest)?;
+                Self::Expire{
+                    round,
+                }
+            }
+            10 => Self::Buy,
+            11 => {
+                let (fee_bps, rest) = Self::unpack_u16(rest)?;
+                let (prize_fee_bps, _) = Self::unpack_u16(rest)?;
+                Self::SetFee{
+                    fee_bps,
+                    prize_fee_bps,
+                }
+            }
+            12 => Self::WithdrawFees,
+            13 => Self::Register,
+            14 => {
+                let (min_balance, rest) = Self::unpack_u64(rest)?;
+                let (min_age_slots, _) = Self::unpack_u64(rest)?;
+                Self::SetSignInRule{
+                    min_balance,
+                    min_age_slots,
+                }
+            }
+            15 => {
+                let (utc_offset, _) = Self::unpack_u32(rest)?;
+                Self::SetTimezone{
+                    utc_offset: utc_offset as i32,
+                }
+            }
+            16 => {
+                let mut tiers = [StreakTier::default(); STREAK_TIERS];
+                let mut rest = rest;
+                for tier in tiers.iter_mut() {
+                    let (days, next) = Self::unpack_u16(rest)?;
+                    let (bonus, next) = Self::unpack_u16(next)?;
+                    *tier = StreakTier{days, bonus};
+                    rest = next;
+                }
+                Self::SetStreakSchedule{
+                    tiers,
+                }
+            }
+            17 => {
+                let (max_tickets_per_round, rest) = Self::unpack_u32(rest)?;
+                let (max_daily_spend, _) = Self::unpack_u64(rest)?;
+                Self::SetPurchaseCap{
+                    max_tickets_per_round,
+                    max_daily_spend,
+                }
+            }
+            18 => Self::Pause,
+            19 => Self::Unpause,
+            20 => Self::ApplyGM,
+            21 => Self::CancelGM,
+            22 => {
+                let (seconds, _) = Self::unpack_u64(rest)?;
+                Self::SetGMDelay{
+                    seconds: seconds as i64,
+                }
+            }
+            23 => {
+                let (&threshold, mut rest) = rest.split_first().ok_or(LotteryError::InvalidInstruction)?;
+                let mut admins = [Pubkey::default(); MAX_ADMINS];
+                for admin in admins.iter_mut() {
+                    let (key, next) = Self::unpack_pubkey(rest)?;
+                    *admin = key;
+                    rest = next;
+                }
+                Self::SetAdmins{
+                    threshold,
+                    admins,
+                }
+            }
+            24 => {
+                let (&role, rest) = rest.split_first().ok_or(LotteryError::InvalidInstruction)?;
+                let role = Role::try_from_primitive(role)
+                    .map_err(|_| LotteryError::InvalidInstruction)?;
+                let (key, _) = Self::unpack_pubkey(rest)?;
+                Self::SetRole{
+                    role,
+                    key,
+                }
+            }
+            25 => {
+                let (campaign_id, _) = Self::unpack_u64(rest)?;
+                Self::CreatePool{
+                    campaign_id,
+                }
+            }
+            26 => Self::ExtendPool,
+            27 => {
+                let (roll_interval, _) = Self::unpack_u64(rest)?;
+                Self::SetSchedule{
+                    roll_interval: roll_interval as i64,
+                }
+            }
+            28 => Self::SetMint,
+            29 => Self::CreateRaffle,
+            30 => Self::DepositPrize,
+            31 => Self::CancelRaffle,
+            32 => {
+                let (&index, _) = rest.split_first().ok_or(LotteryError::InvalidInstruction)?;
+                Self::ClaimPrize{
+                    index,
+                }
+            }
+            33 => {
+                let (&index, _) = rest.split_first().ok_or(LotteryError::InvalidInstruction)?;
+                Self::WithdrawPrize{
+                    index,
+                }
+            }
+            34 => {
+                let (&picks, rest) = rest.split_first().ok_or(LotteryError::InvalidInstruction)?;
+                let (&range, mut rest) = rest.split_first().ok_or(LotteryError::InvalidInstruction)?;
+                let mut tier_bps = [0u16; MAX_LOTTO_PICKS];
+                for bps in tier_bps.iter_mut() {
+                    let (value, next) = Self::unpack_u16(rest)?;
+                    *bps = value;
+                    rest = next;
+                }
+                Self::SetLotto{
+                    picks,
+                    range,
+                    tier_bps,
+                }
+            }
+            35 => {
+                let numbers = rest
+                    .get(..MAX_LOTTO_PICKS)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(LotteryError::InvalidInstruction)?;
+                Self::BuyLotto{
+                    numbers,
+                }
+            }
+            36 => {
+                let (count, _) = Self::unpack_u16(rest)?;
+                Self::SettleLotto{
+                    count,
+                }
+            }
+            37 => {
+                let mut tiers = [ScratchTier::default(); MAX_SCRATCH_TIERS];
+                let mut rest = rest;
+                for tier in tiers.iter_mut() {
+                    let (odds, next) = Self::unpack_u32(rest)?;
+                    let (multiplier, next) = Self::unpack_u32(next)?;
+                    *tier = ScratchTier{odds, multiplier};
+                    rest = next;
+                }
+                Self::SetScratch{
+                    tiers,
+                }
+            }
+            38 => {
+                let (cards, _) = Self::unpack_u16(rest)?;
+                Self::BuyScratch{
+                    cards,
+                }
+            }
+            39 => Self::RevealScratch,
+            40 => Self::ClaimScratch,
+            41 => Self::CancelRound,
+            42 => {
+                let (index, _) = Self::unpack_u16(rest)?;
+                Self::Refund{
+                    index,
+                }
+            }
+            43 => {
+                let (min_players, rest) = Self::unpack_u16(rest)?;
+                let (min_tickets, rest) = Self::unpack_u64(rest)?;
+                let (extension, rest) = Self::unpack_u64(rest)?;
+                let (&max_extensions, _) = rest.split_first().ok_or(LotteryError::InvalidInstruction)?;
+                Self::SetThreshold{
+                    min_players,
+                    min_tickets,
+                    extension: extension as i64,
+                    max_extensions,
+                }
+            }
+            44 => {
+                let (tickets, rest) = Self::unpack_u16(rest)?;
+                let (ticket_cap, rest) = Self::unpack_u32(rest)?;
+                let (bps, rest) = Self::unpack_u16(rest)?;
+                let (cap, _) = Self::unpack_u64(rest)?;
+                Self::SetReferral{
+                    tickets,
+                    ticket_cap,
+                    bps,
+                    cap,
+                }
+            }
+            45 => {
+                let (referrer, _) = Self::unpack_pubkey(rest)?;
+                Self::ReferredSignIn{
+                    referrer,
+                }
+            }
+            46 => {
+                let (round, _) = Self::unpack_u16(rest)?;
+                Self::Claim{
+                    round,
+                }
+            }
             _ => return Err(LotteryError::InvalidInstruction.into()),
         })
     }
@@ -96,24 +556,386 @@ impl LotteryInstruction {
                 price,    
             }=> {
                 buf = Vec::with_capacity(self_len);
-                buf.push(3); 
-                buf.extend_from_slice(&fund.to_le_bytes());
-                buf.extend_from_slice(&price.to_le_bytes());
+                buf.push(3); 
+                buf.extend_from_slice(&fund.to_le_bytes());
+                buf.extend_from_slice(&price.to_le_bytes());
+            }
+
+            Self::Roll=> {
+                buf = Vec::with_capacity(self_len);
+                buf.push(4); 
+            }
+
+            Self::Reward => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(5); 
+            }
+
+            Self::Fund {
+                amount,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(6); 
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+
+            Self::SetRollover {
+                growth_bps,
+                cap,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(7); 
+                buf.extend_from_slice(&growth_bps.to_le_bytes());
+                buf.extend_from_slice(&cap.to_le_bytes());
+            }
+
+            Self::SetClaimWindow {
+                seconds,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(8); 
+                buf.extend_from_slice(&seconds.to_le_bytes());
+            }
+
+            Self::Expire {
+                round,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(9); 
+                buf.extend_from_slice(&round.to_le_bytes());
+            }
+
+            Self::Buy => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(10); 
+            }
+
+            Self::SetFee {
+                fee_bps,
+                prize_fee_bps,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(11); 
+                buf.extend_from_slice(&fee_bps.to_le_bytes());
+                buf.extend_from_slice(&prize_fee_bps.to_le_bytes());
+            }
+
+            Self::WithdrawFees => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(12); 
+            }
+
+            Self::Register => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(13); 
+            }
+
+            Self::SetSignInRule {
+                min_balance,
+                min_age_slots,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(14); 
+                buf.extend_from_slice(&min_balance.to_le_bytes());
+                buf.extend_from_slice(&min_age_slots.to_le_bytes());
+            }
+
+            Self::SetTimezone {
+                utc_offset,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(15); 
+                buf.extend_from_slice(&utc_offset.to_le_bytes());
+            }
+
+            Self::SetStreakSchedule {
+                tiers,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(16); 
+                for tier in tiers {
+                    buf.extend_from_slice(&tier.days.to_le_bytes());
+                    buf.extend_from_slice(&tier.bonus.to_le_bytes());
+                }
+            }
+
+            Self::SetPurchaseCap {
+                max_tickets_per_round,
+                max_daily_spend,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(17); 
+                buf.extend_from_slice(&max_tickets_per_round.to_le_bytes());
+                buf.extend_from_slice(&max_daily_spend.to_le_bytes());
+            }
+
+            Self::Pause => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(18); 
+            }
+
+            Self::Unpause => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(19); 
+            }
+
+            Self::ApplyGM => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(20); 
+            }
+
+            Self::CancelGM => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(21); 
+            }
+
+            Self::SetGMDelay {
+                seconds,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(22); 
+                buf.extend_from_slice(&seconds.to_le_bytes());
+            }
+
+            Self::SetAdmins {
+                threshold,
+                admins,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(23); 
+                buf.push(*threshold);
+                for admin in admins {
+                    buf.extend_from_slice(admin.as_ref());
+                }
+            }
+
+            Self::SetRole {
+                role,
+                key,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(24); 
+                buf.push(*role as u8);
+                buf.extend_from_slice(key.as_ref());
+            }
+
+            Self::CreatePool {
+                campaign_id,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(25); 
+                buf.extend_from_slice(&campaign_id.to_le_bytes());
+            }
+
+            Self::ExtendPool => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(26); 
+            }
+
+            Self::SetSchedule {
+                roll_interval,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(27); 
+                buf.extend_from_slice(&roll_interval.to_le_bytes());
+            }
+
+            Self::SetMint => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(28); 
+            }
+
+            Self::CreateRaffle => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(29); 
+            }
+
+            Self::DepositPrize => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(30); 
+            }
+
+            Self::CancelRaffle => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(31); 
+            }
+
+            Self::ClaimPrize {
+                index,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(32); 
+                buf.push(*index);
+            }
+
+            Self::WithdrawPrize {
+                index,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(33); 
+                buf.push(*index);
+            }
+
+            Self::SetLotto {
+                picks,
+                range,
+                tier_bps,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(34); 
+                buf.push(*picks);
+                buf.push(*range);
+                for bps in tier_bps {
+                    buf.extend_from_slice(&bps.to_le_bytes());
+                }
+            }
+
+            Self::BuyLotto {
+                numbers,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(35); 
+                buf.extend_from_slice(numbers);
+            }
+
+            Self::SettleLotto {
+                count,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(36); 
+                buf.extend_from_slice(&count.to_le_bytes());
+            }
+
+            Self::SetScratch {
+                tiers,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(37); 
+                for tier in tiers {
+                    buf.extend_from_slice(&tier.odds.to_le_bytes());
+                    buf.extend_from_slice(&tier.multiplier.to_le_bytes());
+                }
+            }
+
+            Self::BuyScratch {
+                cards,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(38); 
+                buf.extend_from_slice(&cards.to_le_bytes());
+            }
+
+            Self::RevealScratch => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(39); 
+            }
+
+            Self::ClaimScratch => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(40); 
+            }
+
+            Self::CancelRound => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(41); 
+            }
+
+            Self::Refund {
+                index,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(42); 
+                buf.extend_from_slice(&index.to_le_bytes());
+            }
+
+            Self::SetThreshold {
+                min_players,
+                min_tickets,
+                extension,
+                max_extensions,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(43); 
+                buf.extend_from_slice(&min_players.to_le_bytes());
+                buf.extend_from_slice(&min_tickets.to_le_bytes());
+                buf.extend_from_slice(&extension.to_le_bytes());
+                buf.push(*max_extensions);
+            }
+
+            Self::SetReferral {
+                tickets,
+                ticket_cap,
+                bps,
+                cap,
+            } => {
+                buf = Vec::with_capacity(self_len);
+                buf.push(44); 
+                buf.extend_from_slice(&tickets.to_le_bytes());
+                buf.extend_from_slice(&ticket_cap.to_le_bytes());
+                buf.extend_from_slice(&bps.to_le_bytes());
+                buf.extend_from_slice(&cap.to_le_bytes());
             }
 
-            Self::Roll=> {
+            Self::ReferredSignIn {
+                referrer,
+            } => {
                 buf = Vec::with_capacity(self_len);
-                buf.push(4); 
+                buf.push(45); 
+                buf.extend_from_slice(referrer.as_ref());
             }
 
-            Self::Reward => {
+            Self::Claim {
+                round,
+            } => {
                 buf = Vec::with_capacity(self_len);
-                buf.push(5); 
+                buf.push(46); 
+                buf.extend_from_slice(&round.to_le_bytes());
             }
         };
         buf
     }    
 
+    fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+        if input.len() >= 32 {
+            let (key, rest) = input.split_at(32);
+            let key = key
+                .try_into()
+                .map(Pubkey::new_from_array)
+                .map_err(|_| LotteryError::InvalidInstruction)?;
+            Ok((key, rest))
+        } else {
+            Err(LotteryError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+        if input.len() >= 2 {
+            let (amount, rest) = input.split_at(2);
+            let amount = amount
+                .get(..2)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u16::from_le_bytes)
+                .ok_or(LotteryError::InvalidInstruction)?;
+            Ok((amount, rest))
+        } else {
+            Err(LotteryError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_u32(input: &[u8]) -> Result<(u32, &[u8]), ProgramError> {
+        if input.len() >= 4 {
+            let (amount, rest) = input.split_at(4);
+            let amount = amount
+                .get(..4)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u32::from_le_bytes)
+                .ok_or(LotteryError::InvalidInstruction)?;
+            Ok((amount, rest))
+        } else {
+            Err(LotteryError::InvalidInstruction.into())
+        }
+    }
+
     fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
         if input.len() >= 8 {
             let (amount, rest) = input.split_at(8);
@@ -265,4 +1087,443 @@ mod test {
         let unpacked = LotteryInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check); 
     }
+
+    #[test]
+    fn test_instruction_fund() {
+        let check = LotteryInstruction::Fund{
+            amount:10_000_000_000u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[6]);
+        expect.extend_from_slice(&[0, 228, 11, 84, 2, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_set_rollover() {
+        let check = LotteryInstruction::SetRollover{
+            growth_bps:1000u16,
+            cap:10_000_000_000u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[7]);
+        expect.extend_from_slice(&[232, 3]);
+        expect.extend_from_slice(&[0, 228, 11, 84, 2, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+
+        assert!(LotteryInstruction::unpack(&[7, 232]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_set_claim_window() {
+        let check = LotteryInstruction::SetClaimWindow{
+            seconds:86400i64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[8]);
+        expect.extend_from_slice(&[128, 81, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_expire() {
+        let check = LotteryInstruction::Expire{
+            round:9527u16,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[9]);
+        expect.extend_from_slice(&[55, 37]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_buy() {
+        let check = LotteryInstruction::Buy;
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[10]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_set_fee() {
+        let check = LotteryInstruction::SetFee{
+            fee_bps:250u16,
+            prize_fee_bps:100u16,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[11]);
+        expect.extend_from_slice(&[250, 0]);
+        expect.extend_from_slice(&[100, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_withdraw_fees() {
+        let check = LotteryInstruction::WithdrawFees;
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[12]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_register() {
+        let check = LotteryInstruction::Register;
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[13]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_set_signin_rule() {
+        let check = LotteryInstruction::SetSignInRule{
+            min_balance:1_000_000_000u64,
+            min_age_slots:9527u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[14]);
+        expect.extend_from_slice(&[0, 202, 154, 59, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_set_timezone() {
+        let check = LotteryInstruction::SetTimezone{
+            utc_offset:-28800i32,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[15]);
+        expect.extend_from_slice(&[128, 143, 255, 255]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_set_streak_schedule() {
+        let check = LotteryInstruction::SetStreakSchedule{
+            tiers: [
+                StreakTier{days: 7, bonus: 1},
+                StreakTier{days: 30, bonus: 3},
+                StreakTier::default(),
+                StreakTier::default(),
+            ],
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[16]);
+        expect.extend_from_slice(&[7, 0, 1, 0, 30, 0, 3, 0]);
+        expect.extend_from_slice(&[0u8;8]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+        assert!(LotteryInstruction::unpack(&expect[..12]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_set_purchase_cap() {
+        let check = LotteryInstruction::SetPurchaseCap{
+            max_tickets_per_round:10u32,
+            max_daily_spend:1_000_000_000u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[17]);
+        expect.extend_from_slice(&[10, 0, 0, 0]);
+        expect.extend_from_slice(&[0, 202, 154, 59, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_pause() {
+        let check = LotteryInstruction::Pause;
+        let packed = check.pack();
+        let expect = vec![18u8];
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+
+        let check = LotteryInstruction::Unpause;
+        let packed = check.pack();
+        let expect = vec![19u8];
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_gm_timelock() {
+        let check = LotteryInstruction::ApplyGM;
+        assert_eq!(check.pack(), vec![20u8]);
+        assert_eq!(LotteryInstruction::unpack(&[20u8]).unwrap(), check); 
+
+        let check = LotteryInstruction::CancelGM;
+        assert_eq!(check.pack(), vec![21u8]);
+        assert_eq!(LotteryInstruction::unpack(&[21u8]).unwrap(), check); 
+
+        let check = LotteryInstruction::SetGMDelay{
+            seconds:86400i64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[22]);
+        expect.extend_from_slice(&[128, 81, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+    }
+
+    #[test]
+    fn test_instruction_set_admins() {
+        let check = LotteryInstruction::SetAdmins{
+            threshold: 2,
+            admins: [
+                Pubkey::new_from_array([5u8;32]),
+                Pubkey::new_from_array([6u8;32]),
+                Pubkey::new_from_array([7u8;32]),
+                Pubkey::default(),
+                Pubkey::default(),
+            ],
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[23, 2]);
+        expect.extend_from_slice(&[5;32]);
+        expect.extend_from_slice(&[6;32]);
+        expect.extend_from_slice(&[7;32]);
+        expect.extend_from_slice(&[0;64]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+        assert!(LotteryInstruction::unpack(&expect[..expect.len()-1]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_set_role() {
+        let check = LotteryInstruction::SetRole{
+            role: Role::Treasurer,
+            key: Pubkey::new_from_array([9u8;32]),
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[24, 2]);
+        expect.extend_from_slice(&[9;32]);
+        assert_eq!(packed, expect);
+        let unpacked = LotteryInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check); 
+        expect[1] = 4;
+        assert!(LotteryInstruction::unpack(&expect).is_err());
+    }
+
+    #[test]
+    fn test_instruction_pools() {
+        let check = LotteryInstruction::CreatePool{
+            campaign_id: 9527u64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[25]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        assert_eq!(LotteryInstruction::unpack(&expect).unwrap(), check); 
+
+        let check = LotteryInstruction::ExtendPool;
+        assert_eq!(check.pack(), vec![26u8]);
+        assert_eq!(LotteryInstruction::unpack(&[26u8]).unwrap(), check); 
+
+        let check = LotteryInstruction::SetMint;
+        assert_eq!(check.pack(), vec![28u8]);
+        assert_eq!(LotteryInstruction::unpack(&[28u8]).unwrap(), check); 
+
+        let check = LotteryInstruction::SetSchedule{
+            roll_interval: 86400i64,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[27]);
+        expect.extend_from_slice(&[128, 81, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        assert_eq!(LotteryInstruction::unpack(&expect).unwrap(), check); 
+    }
+
+    #[test]
+    fn test_instruction_raffle() {
+        let check = LotteryInstruction::CreateRaffle;
+        assert_eq!(check.pack(), vec![29u8]);
+        assert_eq!(LotteryInstruction::unpack(&[29u8]).unwrap(), check); 
+
+        let check = LotteryInstruction::DepositPrize;
+        assert_eq!(check.pack(), vec![30u8]);
+        assert_eq!(LotteryInstruction::unpack(&[30u8]).unwrap(), check); 
+
+        let check = LotteryInstruction::CancelRaffle;
+        assert_eq!(check.pack(), vec![31u8]);
+        assert_eq!(LotteryInstruction::unpack(&[31u8]).unwrap(), check); 
+
+        let check = LotteryInstruction::ClaimPrize{
+            index: 3,
+        };
+        assert_eq!(check.pack(), vec![32u8, 3]);
+        assert_eq!(LotteryInstruction::unpack(&[32u8, 3]).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&[32u8]).is_err());
+
+        let check = LotteryInstruction::WithdrawPrize{
+            index: 9,
+        };
+        assert_eq!(check.pack(), vec![33u8, 9]);
+        assert_eq!(LotteryInstruction::unpack(&[33u8, 9]).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&[33u8]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_lotto() {
+        let check = LotteryInstruction::SetLotto{
+            picks: 6,
+            range: 49,
+            tier_bps: [0, 0, 500, 1000, 2000, 5000, 0, 0],
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[34, 6, 49]);
+        expect.extend_from_slice(&[0, 0, 0, 0, 244, 1, 232, 3, 208, 7, 136, 19, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        assert_eq!(LotteryInstruction::unpack(&expect).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&expect[..expect.len()-1]).is_err());
+
+        let check = LotteryInstruction::BuyLotto{
+            numbers: [3, 14, 15, 9, 26, 5, 0, 0],
+        };
+        let packed = check.pack();
+        assert_eq!(packed, vec![35u8, 3, 14, 15, 9, 26, 5, 0, 0]);
+        assert_eq!(LotteryInstruction::unpack(&packed).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&packed[..packed.len()-1]).is_err());
+
+        let check = LotteryInstruction::SettleLotto{
+            count: 500,
+        };
+        assert_eq!(check.pack(), vec![36u8, 244, 1]);
+        assert_eq!(LotteryInstruction::unpack(&[36u8, 244, 1]).unwrap(), check); 
+    }
+
+    #[test]
+    fn test_instruction_scratch() {
+        let check = LotteryInstruction::SetScratch{
+            tiers: [
+                ScratchTier{odds: 10, multiplier: 2},
+                ScratchTier{odds: 1000, multiplier: 100},
+                ScratchTier::default(),
+                ScratchTier::default(),
+            ],
+        };
+        let packed = check.pack();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[37]);
+        expect.extend_from_slice(&[10, 0, 0, 0, 2, 0, 0, 0, 232, 3, 0, 0, 100, 0, 0, 0]);
+        expect.extend_from_slice(&[0u8;16]);
+        assert_eq!(packed, expect);
+        assert_eq!(LotteryInstruction::unpack(&expect).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&expect[..expect.len()-1]).is_err());
+
+        let check = LotteryInstruction::BuyScratch{
+            cards: 5,
+        };
+        assert_eq!(check.pack(), vec![38u8, 5, 0]);
+        assert_eq!(LotteryInstruction::unpack(&[38u8, 5, 0]).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&[38u8, 5]).is_err());
+
+        assert_eq!(LotteryInstruction::RevealScratch.pack(), vec![39u8]);
+        assert_eq!(LotteryInstruction::unpack(&[39u8]).unwrap(), LotteryInstruction::RevealScratch); 
+        assert_eq!(LotteryInstruction::ClaimScratch.pack(), vec![40u8]);
+        assert_eq!(LotteryInstruction::unpack(&[40u8]).unwrap(), LotteryInstruction::ClaimScratch); 
+    }
+
+    #[test]
+    fn test_instruction_refund() {
+        assert_eq!(LotteryInstruction::CancelRound.pack(), vec![41u8]);
+        assert_eq!(LotteryInstruction::unpack(&[41u8]).unwrap(), LotteryInstruction::CancelRound); 
+
+        let check = LotteryInstruction::Refund{
+            index: 9527,
+        };
+        assert_eq!(check.pack(), vec![42u8, 55, 37]);
+        assert_eq!(LotteryInstruction::unpack(&[42u8, 55, 37]).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&[42u8, 55]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_threshold() {
+        let check = LotteryInstruction::SetThreshold{
+            min_players: 3,
+            min_tickets: 9527,
+            extension: 3600,
+            max_extensions: 2,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, vec![43u8, 3, 0, 55, 37, 0, 0, 0, 0, 0, 0, 16, 14, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(LotteryInstruction::unpack(&packed).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&packed[..19]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_referral() {
+        let check = LotteryInstruction::SetReferral{
+            tickets: 3,
+            ticket_cap: 30,
+            bps: 500,
+            cap: 9527,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, vec![44u8, 3, 0, 30, 0, 0, 0, 244, 1, 55, 37, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(LotteryInstruction::unpack(&packed).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&packed[..16]).is_err());
+
+        let check = LotteryInstruction::ReferredSignIn{
+            referrer: Pubkey::new_from_array([3u8;32]),
+        };
+        let packed = check.pack();
+        assert_eq!(packed, [&[45u8][..], &[3;32]].concat());
+        assert_eq!(LotteryInstruction::unpack(&packed).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&packed[..32]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_claim() {
+        let check = LotteryInstruction::Claim{
+            round: 3,
+        };
+        let packed = check.pack();
+        assert_eq!(packed, vec![46u8, 3, 0]);
+        assert_eq!(LotteryInstruction::unpack(&packed).unwrap(), check); 
+        assert!(LotteryInstruction::unpack(&packed[..2]).is_err());
+    }
 }
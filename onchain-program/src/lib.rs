@@ -6,8 +6,11 @@
 use solana_program::{
     msg,
 };
+use event::LotteryEvent;
 
+pub mod draw;
 pub mod error;
+pub mod event;
 pub mod instruction;
 pub mod processor;
 pub mod state;
@@ -24,3 +27,8 @@ pub use solana_program;
 pub(crate) fn log_info(message: &str) {
     msg!(format!("[{}]:{}", "solong-lottery", message).as_str());
 }
+
+// structured event logger
+pub(crate) fn log_event(event: &LotteryEvent) {
+    msg!(event.encode().as_str());
+}
@@ -3,21 +3,27 @@
 use crate::{
     error::LotteryError,
     instruction::LotteryInstruction,
-    state::{MAX_PLAYER, LOTTERY_STATE_LEN, AwardState, AwardBill},
+    event::LotteryEvent,
+    draw::{winning_ticket, raffle_tickets, lotto_numbers, find_entry, slot_hash, scratch_random, scratch_tier},
+    state::{MAX_PLAYER, MAX_AWARD, MAX_SPONSOR, MAX_UTC_OFFSET, BPS_DENOMINATOR, LOTTERY_STATE_LEN, LotteryState, TicketEntry, PlayerState, StreakTier, STREAK_TIERS, MAX_ADMINS, Role, AwardState, AwardBill, AwardStatus, SponsorBill, RaffleState, RafflePrize, RaffleStatus, PrizeStatus, PLAYER_SEED, POOL_SEED, VAULT_SEED, RAFFLE_SEED, MAX_RAFFLE_PRIZES, MAX_LOTTO_PICKS, MAX_LOTTO_RANGE, ScratchTier, MAX_SCRATCH_TIERS, MAX_SCRATCH_CARDS, SCRATCH_SCALE},
     log_info,
+    log_event,
 };
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    entrypoint::ProgramResult,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+    program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
     system_instruction,
-    program::invoke,
+    program::{invoke, invoke_signed},
     clock::Clock,
-    sysvar::Sysvar,
+    rent::Rent,
+    sysvar::{self, Sysvar},
 };
+use spl_token::state::{Account as TokenAccount, Mint};
 use std::str::FromStr;
-use arrayref::{array_mut_ref, array_ref, mut_array_refs};
+use arrayref::array_ref;
 
 
 /// Program state handler.
@@ -38,7 +44,7 @@ impl Processor {
 
             LotteryInstruction::SignIn => {
                 log_info("Instruction: SignIn");
-                Self::process_signin(program_id, accounts)
+                Self::process_signin(program_id, accounts, None)
             }
 
             LotteryInstruction::GM{
@@ -58,6 +64,277 @@ impl Processor {
                 log_info("Instruction: Reward");
                 Self::process_reward(program_id, accounts)
             }
+
+            LotteryInstruction::Fund{
+                amount
+            } => {
+                log_info("Instruction: Fund");
+                Self::process_fund(program_id, accounts, amount)
+            }
+
+            LotteryInstruction::SetRollover{
+                growth_bps,
+                cap,
+            } => {
+                log_info("Instruction: SetRollover");
+                Self::process_set_rollover(program_id, accounts, growth_bps, cap)
+            }
+
+            LotteryInstruction::SetClaimWindow{
+                seconds,
+            } => {
+                log_info("Instruction: SetClaimWindow");
+                Self::process_set_claim_window(program_id, accounts, seconds)
+            }
+
+            LotteryInstruction::Expire{
+                round,
+            } => {
+                log_info("Instruction: Expire");
+                Self::process_expire(program_id, accounts, round)
+            }
+
+            LotteryInstruction::Buy => {
+                log_info("Instruction: Buy");
+                Self::process_buy(program_id, accounts, None)
+            }
+
+            LotteryInstruction::SetFee{
+                fee_bps,
+                prize_fee_bps,
+            } => {
+                log_info("Instruction: SetFee");
+                Self::process_set_fee(program_id, accounts, fee_bps, prize_fee_bps)
+            }
+
+            LotteryInstruction::WithdrawFees => {
+                log_info("Instruction: WithdrawFees");
+                Self::process_withdraw_fees(program_id, accounts)
+            }
+
+            LotteryInstruction::Register => {
+                log_info("Instruction: Register");
+                Self::process_register(program_id, accounts)
+            }
+
+            LotteryInstruction::SetSignInRule{
+                min_balance,
+                min_age_slots,
+            } => {
+                log_info("Instruction: SetSignInRule");
+                Self::process_set_signin_rule(program_id, accounts, min_balance, min_age_slots)
+            }
+
+            LotteryInstruction::SetTimezone{
+                utc_offset,
+            } => {
+                log_info("Instruction: SetTimezone");
+                Self::process_set_timezone(program_id, accounts, utc_offset)
+            }
+
+            LotteryInstruction::SetStreakSchedule{
+                tiers,
+            } => {
+                log_info("Instruction: SetStreakSchedule");
+                Self::process_set_streak_schedule(program_id, accounts, tiers)
+            }
+
+            LotteryInstruction::SetPurchaseCap{
+                max_tickets_per_round,
+                max_daily_spend,
+            } => {
+                log_info("Instruction: SetPurchaseCap");
+                Self::process_set_purchase_cap(program_id, accounts, max_tickets_per_round, max_daily_spend)
+            }
+
+            LotteryInstruction::Pause => {
+                log_info("Instruction: Pause");
+                Self::process_set_paused(program_id, accounts, true)
+            }
+
+            LotteryInstruction::Unpause => {
+                log_info("Instruction: Unpause");
+                Self::process_set_paused(program_id, accounts, false)
+            }
+
+            LotteryInstruction::ApplyGM => {
+                log_info("Instruction: ApplyGM");
+                Self::process_apply_gm(program_id, accounts)
+            }
+
+            LotteryInstruction::CancelGM => {
+                log_info("Instruction: CancelGM");
+                Self::process_cancel_gm(program_id, accounts)
+            }
+
+            LotteryInstruction::SetGMDelay{
+                seconds,
+            } => {
+                log_info("Instruction: SetGMDelay");
+                Self::process_set_gm_delay(program_id, accounts, seconds)
+            }
+
+            LotteryInstruction::SetAdmins{
+                threshold,
+                admins,
+            } => {
+                log_info("Instruction: SetAdmins");
+                Self::process_set_admins(program_id, accounts, threshold, admins)
+            }
+
+            LotteryInstruction::SetRole{
+                role,
+                key,
+            } => {
+                log_info("Instruction: SetRole");
+                Self::process_set_role(program_id, accounts, role, key)
+            }
+
+            LotteryInstruction::CreatePool{
+                campaign_id,
+            } => {
+                log_info("Instruction: CreatePool");
+                Self::process_create_pool(program_id, accounts, campaign_id)
+            }
+
+            LotteryInstruction::ExtendPool => {
+                log_info("Instruction: ExtendPool");
+                Self::process_extend_pool(program_id, accounts)
+            }
+
+            LotteryInstruction::SetSchedule{
+                roll_interval,
+            } => {
+                log_info("Instruction: SetSchedule");
+                Self::process_set_schedule(program_id, accounts, roll_interval)
+            }
+
+            LotteryInstruction::SetMint => {
+                log_info("Instruction: SetMint");
+                Self::process_set_mint(program_id, accounts)
+            }
+
+            LotteryInstruction::CreateRaffle => {
+                log_info("Instruction: CreateRaffle");
+                Self::process_create_raffle(program_id, accounts)
+            }
+
+            LotteryInstruction::DepositPrize => {
+                log_info("Instruction: DepositPrize");
+                Self::process_deposit_prize(program_id, accounts)
+            }
+
+            LotteryInstruction::CancelRaffle => {
+                log_info("Instruction: CancelRaffle");
+                Self::process_cancel_raffle(program_id, accounts)
+            }
+
+            LotteryInstruction::ClaimPrize{
+                index,
+            } => {
+                log_info("Instruction: ClaimPrize");
+                Self::process_claim_prize(program_id, accounts, index)
+            }
+
+            LotteryInstruction::WithdrawPrize{
+                index,
+            } => {
+                log_info("Instruction: WithdrawPrize");
+                Self::process_withdraw_prize(program_id, accounts, index)
+            }
+
+            LotteryInstruction::SetLotto{
+                picks,
+                range,
+                tier_bps,
+            } => {
+                log_info("Instruction: SetLotto");
+                Self::process_set_lotto(program_id, accounts, picks, range, tier_bps)
+            }
+
+            LotteryInstruction::BuyLotto{
+                numbers,
+            } => {
+                log_info("Instruction: BuyLotto");
+                Self::process_buy(program_id, accounts, Some(numbers))
+            }
+
+            LotteryInstruction::SettleLotto{
+                count,
+            } => {
+                log_info("Instruction: SettleLotto");
+                Self::process_settle_lotto(program_id, accounts, count)
+            }
+
+            LotteryInstruction::SetScratch{
+                tiers,
+            } => {
+                log_info("Instruction: SetScratch");
+                Self::process_set_scratch(program_id, accounts, tiers)
+            }
+
+            LotteryInstruction::BuyScratch{
+                cards,
+            } => {
+                log_info("Instruction: BuyScratch");
+                Self::process_buy_scratch(program_id, accounts, cards)
+            }
+
+            LotteryInstruction::RevealScratch => {
+                log_info("Instruction: RevealScratch");
+                Self::process_reveal_scratch(program_id, accounts)
+            }
+
+            LotteryInstruction::ClaimScratch => {
+                log_info("Instruction: ClaimScratch");
+                Self::process_claim_scratch(program_id, accounts)
+            }
+
+            LotteryInstruction::CancelRound => {
+                log_info("Instruction: CancelRound");
+                Self::process_cancel_round(program_id, accounts)
+            }
+
+            LotteryInstruction::Refund{
+                index,
+            } => {
+                log_info("Instruction: Refund");
+                Self::process_refund(program_id, accounts, index)
+            }
+
+            LotteryInstruction::SetThreshold{
+                min_players,
+                min_tickets,
+                extension,
+                max_extensions,
+            } => {
+                log_info("Instruction: SetThreshold");
+                Self::process_set_threshold(program_id, accounts, min_players, min_tickets, extension, max_extensions)
+            }
+
+            LotteryInstruction::SetReferral{
+                tickets,
+                ticket_cap,
+                bps,
+                cap,
+            } => {
+                log_info("Instruction: SetReferral");
+                Self::process_set_referral(program_id, accounts, tickets, ticket_cap, bps, cap)
+            }
+
+            LotteryInstruction::ReferredSignIn{
+                referrer,
+            } => {
+                log_info("Instruction: ReferredSignIn");
+                Self::process_signin(program_id, accounts, Some(referrer))
+            }
+
+            LotteryInstruction::Claim{
+                round,
+            } => {
+                log_info("Instruction: Claim");
+                Self::process_claim(program_id, accounts, round)
+            }
         }
     }
 
@@ -73,11 +350,10 @@ impl Processor {
         let admin_info= next_account_info(account_info_iter)?;
         let pool_info= next_account_info(account_info_iter)?;
         let billboard_info= next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
 
         //check permission first
-        if Pubkey::from_str(Self::ADMIN_KEY).unwrap() != *admin_info.key {
-            return Err(LotteryError::InvalidPermission.into());
-        }
         if billboard_info.owner != program_id ||
             pool_info.owner != program_id ||
             !admin_info.is_signer{
@@ -90,62 +366,172 @@ impl Processor {
             return Err(LotteryError::InvalidAccountLength.into());
         }
 
-        let pool_data = &mut pool_info.data.borrow_mut();
-        let pool_buf = array_mut_ref![pool_data, 0, 42];
-        let (
-            fund_buf,
-            billboard_addr_buf,
-            player_count_buf,
-        ) = mut_array_refs![pool_buf, 8, 32, 2];
-        *fund_buf = fund.to_le_bytes();
-        billboard_addr_buf.copy_from_slice(billboard_info.key.as_ref());
-        *player_count_buf = 0u16.to_le_bytes();
+        // the admin set comes from CreatePool and has to approve it
+        let current = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        Self::check_admins(&current, accounts)?;
+        // re-initializing would drop unclaimed bills , fees and sponsors
+        if current.billboard != Pubkey::default() {
+            return Err(LotteryError::AlreadyInitialized.into());
+        }
 
+        // the starting prize has to be in the pool already , above its rent
+        let free = pool_info.lamports()
+            .saturating_sub(rent.minimum_balance(pool_info.data_len()));
+        if fund > free {
+            return Err(LotteryError::FundNotBacked.into());
+        }
+
+        // a billboard serves one pool , so pools can not settle each other's rounds
         let mut billboard= AwardState::unpack_unchecked(&billboard_info.data.borrow())?;
+        if billboard.pool != Pubkey::default() {
+            return Err(LotteryError::BillboardInUse.into());
+        }
+
+        let pool = LotteryState{
+            fund,
+            price,
+            billboard: *billboard_info.key,
+            admin_threshold: current.admin_threshold,
+            admins: current.admins,
+            campaign_id: current.campaign_id,
+            ..LotteryState::default()
+        };
+        pool.pack_into_slice(&mut pool_info.data.borrow_mut());
+
         billboard.billboard.clear();
+        billboard.pool = *pool_info.key;
         AwardState::pack(billboard, &mut billboard_info.data.borrow_mut())?;
 
+        log_event(&LotteryEvent::FundChanged{fund});
         Ok(())
     }
 
     /// Processes an [Initialize](enum.Instruction.html).
     pub fn process_signin(
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
         accounts: &[AccountInfo],
+        referrer: Option<Pubkey>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let account_info= next_account_info(account_info_iter)?;
         let pool_info= next_account_info(account_info_iter)?;
+        let record_info= next_account_info(account_info_iter)?;
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let system_program_info= next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
 
+        if pool_info.owner != program_id ||
+            !account_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        }
         if pool_info.data_len() != LOTTERY_STATE_LEN {
             return Err(LotteryError::InvalidAccountLength.into());
         }
 
-        let pool_data = &mut pool_info.data.borrow_mut();
-        let pool_buf = array_mut_ref![pool_data, 0, LOTTERY_STATE_LEN];
-        let (
-            _fund_buf,
-            _billboard_addr_buf,
-            player_count_buf,
-            players_buf,
-        ) = mut_array_refs![pool_buf, 8, 32, 2, MAX_PLAYER*32];
-        let player_count = u16::from_le_bytes(*player_count_buf);
-        if player_count >= MAX_PLAYER as u16 {
-            return Err(LotteryError::TooManyPlayers.into());  
+        let pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        if pool.paused {
+            return Err(LotteryError::Paused.into());
+        }
+        if pool.cancelled {
+            return Err(LotteryError::RoundCancelled.into());
         }
-        for i in 0..player_count {
-            let s = 32*i as usize;
-            let e = 32*(i+1) as usize;
-            let player = Pubkey::new(&players_buf[s..e]);
-            if player == *account_info.key {
-                return  Err(LotteryError::AlreadySignin.into());  
+        // lotto tickets pick their numbers, only bought ones do , and
+        // scratch pools have no draw to sign in for
+        if pool.is_lotto() || pool.is_scratch() {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        // anti-sybil checks, empty or fresh wallets can not farm tickets
+        if account_info.lamports() < pool.min_balance {
+            return Err(LotteryError::LowBalance.into());
+        }
+        let mut record = Self::load_player_record(
+            program_id,
+            pool_info,
+            account_info,
+            record_info,
+            system_program_info,
+            clock,
+            rent,
+        )?;
+        if pool.min_age_slots > 0 &&
+            clock.slot.saturating_sub(record.first_seen_slot) < pool.min_age_slots {
+            return Err(LotteryError::AccountTooNew.into());
+        }
+        // a referral is recorded at the first sign in , the referrer's record follows the system program
+        let mut referral = None;
+        if let Some(referrer) = referrer {
+            let referrer_info = next_account_info(account_info_iter)?;
+            if referrer == *account_info.key {
+                return Err(LotteryError::SelfReferral.into());
+            }
+            let (address, _) = PlayerState::find_address(program_id, pool_info.key, &referrer);
+            if address != *referrer_info.key || referrer_info.owner != program_id {
+                return Err(LotteryError::InvalidReferrer.into());
             }
+            let mut referrer_record = PlayerState::unpack(&referrer_info.data.borrow())?;
+            // referrers have played and were seen before the players they
+            // refer , so referrals can not form a cycle
+            if record.last_signin_day != 0 ||
+                record.referrer != Pubkey::default() ||
+                referrer_record.played_round == 0 ||
+                referrer_record.first_seen_slot >= record.first_seen_slot {
+                return Err(LotteryError::InvalidReferrer.into());
+            }
+            let bonus = pool.referral_bonus(referrer_record.referral_tickets);
+            referrer_record.referrals = referrer_record.referrals.saturating_add(1);
+            referrer_record.bonus_tickets = referrer_record.bonus_tickets.saturating_add(bonus);
+            referrer_record.referral_tickets = referrer_record.referral_tickets.saturating_add(bonus as u32);
+            PlayerState::pack(referrer_record, &mut referrer_info.data.borrow_mut())?;
+            record.referrer = referrer;
+            referral = Some((referrer, bonus));
+        }
+        // one free ticket per calendar day, no matter when rounds roll
+        let today = pool.day_of(clock.unix_timestamp);
+        if record.last_signin_day == today {
+            return Err(LotteryError::AlreadySignedInToday.into());
+        }
+        // a missed day resets the streak
+        record.streak = if record.last_signin_day == today-1 {
+            record.streak.saturating_add(1)
+        } else {
+            1
+        };
+        record.last_signin_day = today;
+        let streak = record.streak;
+        // bonus tickets earned by referrals join the next sign in
+        let tickets = (1+pool.streak_bonus(streak)).saturating_add(record.bonus_tickets);
+        record.bonus_tickets = 0;
+        let joined = record.join_round(&pool);
+        PlayerState::pack(record, &mut record_info.data.borrow_mut())?;
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        if joined {
+            pool.round_players += 1;
         }
-        *player_count_buf = (1u16+player_count).to_le_bytes(); 
-        let s = (player_count*32) as usize;
-        let player_buf = array_mut_ref![players_buf, s, 32];
-        player_buf.copy_from_slice(account_info.key.as_ref());
+        // the record allows one sign in a day , so a wallet that bought tickets
+        // or signed in on an earlier day of the round gets another entry ,
+        // bonus tickets raise its weight
+        Self::push_entry(pool_data, &mut pool, tickets as u64, TicketEntry{
+            owner: *account_info.key,
+            ..TicketEntry::default()
+        })?;
+        pool.pack_into_slice(pool_data);
 
+        log_event(&LotteryEvent::SignedIn{
+            player: *account_info.key,
+            streak,
+            tickets,
+        });
+        if let Some((referrer, tickets)) = referral {
+            log_event(&LotteryEvent::Referred{
+                player: *account_info.key,
+                referrer,
+                tickets,
+            });
+        }
         Ok(())
     }
 
@@ -154,17 +540,15 @@ impl Processor {
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         fund:u64,
-        _price:u64,
+        price:u64,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let admin_info= next_account_info(account_info_iter)?;
         let pool_info= next_account_info(account_info_iter)?;
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
 
 
         //check permission first
-        if Pubkey::from_str(Self::ADMIN_KEY).unwrap() != *admin_info.key {
-            return Err(LotteryError::InvalidPermission.into());
-        }
         if pool_info.owner != program_id ||
             !admin_info.is_signer{
             return Err(LotteryError::InvalidPermission.into());
@@ -175,133 +559,2838 @@ impl Processor {
             return Err(LotteryError::InvalidAccountLength.into());
         }
 
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
         let pool_data = &mut pool_info.data.borrow_mut();
-        let pool_buf = array_mut_ref![pool_data, 0, 42];
-        let fund_buf = array_mut_ref![pool_buf,0, 8];
-        *fund_buf = fund.to_le_bytes();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_role(&pool, accounts, Role::Treasurer)?;
+        // GM can only lower the prize , Fund is the way to raise it
+        if fund > pool.fund {
+            return Err(LotteryError::FundNotBacked.into());
+        }
+        pool.pending_fund = fund;
+        pool.pending_price = price;
+        pool.pending_at = clock.unix_timestamp.checked_add(pool.gm_delay)
+            .ok_or(LotteryError::Overflow)?;
+        log_event(&LotteryEvent::GMQueued{
+            fund,
+            price,
+            effective: pool.pending_at,
+        });
+
+        // without a delay the change takes effect right away
+        if pool.gm_delay == 0 {
+            Self::apply_pending_gm(&mut pool)?;
+        }
+        pool.pack_into_slice(pool_data);
         Ok(())
     }
 
-    /// Processes an [Initialize](enum.Instruction.html).
-    pub fn process_roll(
+    /// Processes an [ApplyGM](enum.Instruction.html).
+    pub fn process_apply_gm(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let clock_sysvar_info = next_account_info(account_info_iter)?;
-        let admin_info = next_account_info(account_info_iter)?;
         let pool_info= next_account_info(account_info_iter)?;
-        let award_info = next_account_info(account_info_iter)?;
+
+        if pool_info.owner != program_id {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
         let clock = &Clock::from_account_info(clock_sysvar_info)?;
-        
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        if pool.pending_at == 0 {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        if clock.unix_timestamp < pool.pending_at {
+            return Err(LotteryError::NotEffectiveYet.into());
+        }
+        Self::apply_pending_gm(&mut pool)?;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes an [CancelGM](enum.Instruction.html).
+    pub fn process_cancel_gm(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
         //check permission first
-        if Pubkey::from_str(Self::ADMIN_KEY).unwrap() != *admin_info.key {
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
             return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
         }
-        if award_info.owner != program_id ||
-            pool_info.owner != program_id ||
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_role(&pool, accounts, Role::Treasurer)?;
+        if pool.pending_at == 0 {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        pool.pending_fund = 0;
+        pool.pending_price = 0;
+        pool.pending_at = 0;
+        pool.pack_into_slice(pool_data);
+
+        log_event(&LotteryEvent::GMCancelled);
+        Ok(())
+    }
+
+    /// Processes an [SetGMDelay](enum.Instruction.html).
+    pub fn process_set_gm_delay(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seconds: i64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
             !admin_info.is_signer{
             return Err(LotteryError::InvalidPermission.into());
         } 
 
         // check account's data length
-        if pool_info.data_len() != LOTTERY_STATE_LEN ||
-            award_info.data_len() != AwardState::LEN{
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
             return Err(LotteryError::InvalidAccountLength.into());
         }
 
+        if seconds < 0 {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
         let pool_data = &mut pool_info.data.borrow_mut();
-        let pool_buf = array_mut_ref![pool_data, 0, LOTTERY_STATE_LEN];
-        let (
-            fund_buf,
-            _billboard_addr_buf,
-            player_count_buf,
-            players_buf,
-        ) = mut_array_refs![pool_buf, 8, 32, 2, MAX_PLAYER*32];
-        let fund = u64::from_le_bytes(*fund_buf);
-        let player_count = u16::from_le_bytes(*player_count_buf);
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        pool.gm_delay = seconds;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
 
-        if player_count == 0 {
-            return Ok(());
+    /// Processes a [SetAdmins](enum.Instruction.html).
+    pub fn process_set_admins(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        threshold: u8,
+        admins: [Pubkey; MAX_ADMINS],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
         }
 
-        //log_info(&format!("unix_timestamp is {}", clock.unix_timestamp));
-        //log_info(&format!("player count is {}", player_count));
-        let l:u64 = (clock.unix_timestamp as u64) % (player_count as u64);
-        log_info(&format!("l for winner is {}", l));
-        let s = (l*32) as usize;
-        let winner = Pubkey::new(array_ref!(players_buf,s,32));
+        // every signer once , enough of them to reach the threshold
+        let signers: Vec<&Pubkey> = admins.iter().filter(|admin| **admin != Pubkey::default()).collect();
+        if threshold == 0 ||
+            signers.len() < threshold as usize ||
+            signers.iter().enumerate().any(|(i, admin)| signers[..i].contains(admin)) {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
 
-        log_info(&format!("winner is {}", winner));
-        
-        let mut award= AwardState::unpack_unchecked(&award_info.data.borrow())?;
-        let bill = AwardBill{
-            account: winner,
-            award: fund,
-            rewarded: false,
-            timestamp:clock.unix_timestamp,
-        };
-        award.billboard.push(bill);
-        AwardState::pack(award, &mut award_info.data.borrow_mut())?;
-        *player_count_buf = 0u16.to_le_bytes();
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        pool.admin_threshold = threshold;
+        pool.admins = admins;
+        pool.pack_into_slice(pool_data);
 
+        log_event(&LotteryEvent::AdminsChanged{
+            threshold,
+            signers: signers.len() as u8,
+        });
         Ok(())
     }
 
-    /// Processes an [Initialize](enum.Instruction.html).
-    pub fn process_reward(
+    /// Processes a [SetRole](enum.Instruction.html).
+    pub fn process_set_role(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
+        role: Role,
+        key: Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        let system_program_info= next_account_info(account_info_iter)?;
-        let admin_info = next_account_info(account_info_iter)?;
-        let account_info= next_account_info(account_info_iter)?;
-        let award_info = next_account_info(account_info_iter)?;
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
 
         //check permission first
-        if Pubkey::from_str(Self::ADMIN_KEY).unwrap() != *admin_info.key {
-            return Err(LotteryError::InvalidPermission.into());
-        }
-        if award_info.owner != program_id ||
+        if pool_info.owner != program_id ||
             !admin_info.is_signer{
             return Err(LotteryError::InvalidPermission.into());
         } 
 
         // check account's data length
-        if award_info.data_len() != AwardState::LEN{
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
             return Err(LotteryError::InvalidAccountLength.into());
         }
-        
-        let mut award= AwardState::unpack_unchecked(&award_info.data.borrow())?;
 
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        match role {
+            // the owner is the admin set , changed by SetAdmins
+            Role::Owner => return Err(LotteryError::InvalidInstruction.into()),
+            Role::Roller => pool.roller = key,
+            Role::Treasurer => pool.treasurer = key,
+            Role::Pauser => pool.pauser = key,
+        }
+        pool.pack_into_slice(pool_data);
 
-        for val in &mut award.billboard {
-            log_info(&format!("come to send award: {}:{}:{}", val.account, val.award, val.rewarded));
-            if ! val.rewarded  {
-                if val.account != *account_info.key {
-                    continue;
-                }
-                // need not check balance Cau'z it will fail
-                log_info(&format!("send award to {}", val.account));
+        log_event(&LotteryEvent::RoleChanged{
+            role,
+            key,
+        });
+        Ok(())
+    }
+
+    /// Processes a [CreatePool](enum.Instruction.html).
+    ///
+    /// Creates the pool account at the address derived from `campaign_id`.
+    /// An account can only grow by `MAX_PERMITTED_DATA_INCREASE` per
+    /// instruction, so the pool starts small but funded for its full size,
+    /// and `ExtendPool` grows it before `Initialize`.
+    pub fn process_create_pool(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        campaign_id: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let system_program_info= next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+        if !payer_info.is_signer {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        let (address, bump) = LotteryState::find_address(program_id, campaign_id);
+        if address != *pool_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if pool_info.owner == program_id {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        // allocate and assign instead of create_account, which fails if
+        // someone already sent lamports to the address
+        let required = rent
+            .minimum_balance(LOTTERY_STATE_LEN)
+            .saturating_sub(pool_info.lamports());
+        if required > 0 {
+            invoke(
+                &system_instruction::transfer(payer_info.key, pool_info.key, required),
+                &[
+                    payer_info.clone(),
+                    pool_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        let seeds: &[&[u8]] = &[POOL_SEED, &campaign_id.to_le_bytes(), &[bump]];
+        let space = LOTTERY_STATE_LEN.min(MAX_PERMITTED_DATA_INCREASE);
+        invoke_signed(
+            &system_instruction::allocate(pool_info.key, space as u64),
+            &[pool_info.clone(), system_program_info.clone()],
+            &[seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(pool_info.key, program_id),
+            &[pool_info.clone(), system_program_info.clone()],
+            &[seeds],
+        )?;
+
+        let pool = LotteryState{
+            campaign_id,
+            ..LotteryState::default()
+        };
+        pool.pack_into_slice(&mut pool_info.data.borrow_mut());
+
+        log_event(&LotteryEvent::PoolCreated{campaign_id});
+        Ok(())
+    }
+
+    /// Processes an [ExtendPool](enum.Instruction.html).
+    ///
+    /// Anyone can crank it, the rent of the full size was paid at creation.
+    pub fn process_extend_pool(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let pool_info= next_account_info(account_info_iter)?;
+
+        if pool_info.owner != program_id {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        let len = pool_info.data_len();
+        if !(LotteryState::LEN..LOTTERY_STATE_LEN).contains(&len) {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        if LotteryState::find_address(program_id, pool.campaign_id).0 != *pool_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        pool_info.realloc((len+MAX_PERMITTED_DATA_INCREASE).min(LOTTERY_STATE_LEN), true)?;
+        Ok(())
+    }
+
+    /// Processes a [SetSchedule](enum.Instruction.html).
+    pub fn process_set_schedule(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        roll_interval: i64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        if roll_interval < 0 {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        pool.roll_interval = roll_interval;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [SetThreshold](enum.Instruction.html).
+    pub fn process_set_threshold(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        min_players: u16,
+        min_tickets: u64,
+        extension: i64,
+        max_extensions: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        // an extended round needs a limit , or it could wait forever
+        if extension < 0 || (extension > 0 && max_extensions == 0) {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        pool.min_players = min_players;
+        pool.min_tickets = min_tickets;
+        pool.threshold_extension = extension;
+        pool.max_extensions = max_extensions;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [SetReferral](enum.Instruction.html).
+    pub fn process_set_referral(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        tickets: u16,
+        ticket_cap: u32,
+        bps: u16,
+        cap: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        // a single referral can not take over the round
+        if tickets as usize >= MAX_PLAYER ||
+            bps as u64 > BPS_DENOMINATOR {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        pool.referral_tickets = tickets;
+        pool.referral_ticket_cap = ticket_cap;
+        pool.referral_bps = bps;
+        pool.referral_cap = cap;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [SetMint](enum.Instruction.html).
+    ///
+    /// Only a pool which has not sold, rolled or been sponsored yet can change
+    /// what it is denominated in, lamports already deposited would otherwise
+    /// be owed in tokens. Its vault must be owned by the vault authority and
+    /// nobody else may move or close it.
+    pub fn process_set_mint(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let vault_info= next_account_info(account_info_iter)?;
+        let billboard_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            billboard_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN ||
+            billboard_info.data_len() != AwardState::LEN{
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        if pool.billboard != *billboard_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        let billboard = AwardState::unpack_unchecked(&billboard_info.data.borrow())?;
+        if pool.round != 0 || pool.player_count != 0 || pool.fees != 0 ||
+            pool.fund != 0 || !billboard.sponsors.is_empty() {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        if *vault_info.owner != spl_token::id() {
+            return Err(LotteryError::InvalidTokenAccount.into());
+        }
+        let vault = TokenAccount::unpack(&vault_info.data.borrow())?;
+        let (authority, _) = LotteryState::find_vault_authority(program_id, pool_info.key);
+        if vault.owner != authority ||
+            vault.delegate.is_some() ||
+            vault.close_authority.is_some() {
+            return Err(LotteryError::InvalidTokenAccount.into());
+        }
+        pool.mint = vault.mint;
+        pool.vault = *vault_info.key;
+        pool.pack_into_slice(pool_data);
+
+        log_event(&LotteryEvent::MintSet{
+            mint: pool.mint,
+            vault: pool.vault,
+        });
+        Ok(())
+    }
+
+    /// Processes a [CreateRaffle](enum.Instruction.html).
+    ///
+    /// The raffle lives at the address derived from the pool, the sponsor
+    /// pays its rent. A pool raffles one set of prizes at a time, a new raffle
+    /// replaces the last one once all its prizes have left the escrow.
+    pub fn process_create_raffle(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let raffle_info= next_account_info(account_info_iter)?;
+        let sponsor_info= next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let system_program_info= next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer ||
+            !sponsor_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        // players must know what they are playing for before the first ticket
+        if pool.raffle || pool.is_lotto() || pool.is_scratch() || pool.player_count != 0 {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        let (address, bump) = RaffleState::find_address(program_id, pool_info.key);
+        if address != *raffle_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if raffle_info.owner == program_id {
+            let last = RaffleState::unpack(&raffle_info.data.borrow())?;
+            if !last.is_settled() {
+                return Err(LotteryError::InvalidInstruction.into());
+            }
+        } else {
+            // allocate and assign instead of create_account, which fails if
+            // someone already sent lamports to the address
+            let required = rent
+                .minimum_balance(RaffleState::LEN)
+                .saturating_sub(raffle_info.lamports());
+            if required > 0 {
                 invoke(
-                    &system_instruction::transfer(
-                        admin_info.key,
-                        account_info.key,
-                        val.award,
-                    ),
+                    &system_instruction::transfer(sponsor_info.key, raffle_info.key, required),
                     &[
-                        admin_info.clone(),
-                        account_info.clone(),
+                        sponsor_info.clone(),
+                        raffle_info.clone(),
                         system_program_info.clone(),
                     ],
                 )?;
-                val.rewarded = true;
             }
+            let seeds: &[&[u8]] = &[RAFFLE_SEED, pool_info.key.as_ref(), &[bump]];
+            invoke_signed(
+                &system_instruction::allocate(raffle_info.key, RaffleState::LEN as u64),
+                &[raffle_info.clone(), system_program_info.clone()],
+                &[seeds],
+            )?;
+            invoke_signed(
+                &system_instruction::assign(raffle_info.key, program_id),
+                &[raffle_info.clone(), system_program_info.clone()],
+                &[seeds],
+            )?;
         }
-       
+
+        let raffle = RaffleState{
+            is_initialized: true,
+            pool: *pool_info.key,
+            sponsor: *sponsor_info.key,
+            status: RaffleStatus::Open,
+            prizes: Vec::new(),
+        };
+        RaffleState::pack(raffle, &mut raffle_info.data.borrow_mut())?;
+        pool.raffle = true;
+        pool.pack_into_slice(pool_data);
+
+        log_event(&LotteryEvent::RaffleCreated{sponsor: *sponsor_info.key});
+        Ok(())
+    }
+
+    /// Processes a [DepositPrize](enum.Instruction.html).
+    ///
+    /// The prize has to be a single token nobody can mint more of. The escrow
+    /// is a token account of the NFT owned by the vault authority, one per
+    /// prize, nobody else may move or close it.
+    pub fn process_deposit_prize(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let sponsor_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let raffle_info= next_account_info(account_info_iter)?;
+        let mint_info= next_account_info(account_info_iter)?;
+        let source_info= next_account_info(account_info_iter)?;
+        let escrow_info= next_account_info(account_info_iter)?;
+        let token_program_info= next_account_info(account_info_iter)?;
+
+        let mut raffle = Self::load_raffle(program_id, pool_info, raffle_info)?;
+        if !sponsor_info.is_signer ||
+            raffle.sponsor != *sponsor_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        let pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        if raffle.status != RaffleStatus::Open || pool.player_count != 0 {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        if raffle.prizes.len() >= MAX_RAFFLE_PRIZES {
+            return Err(LotteryError::TooManyPrizes.into());
+        }
+
+        if *token_program_info.key != spl_token::id() ||
+            *mint_info.owner != spl_token::id() ||
+            *escrow_info.owner != spl_token::id() {
+            return Err(LotteryError::InvalidTokenAccount.into());
+        }
+        let mint = Mint::unpack(&mint_info.data.borrow())?;
+        if mint.supply != 1 || mint.decimals != 0 || mint.mint_authority.is_some() {
+            return Err(LotteryError::NotNft.into());
+        }
+        let escrow = TokenAccount::unpack(&escrow_info.data.borrow())?;
+        let (authority, _) = LotteryState::find_vault_authority(program_id, pool_info.key);
+        if escrow.owner != authority ||
+            escrow.mint != *mint_info.key ||
+            escrow.delegate.is_some() ||
+            escrow.close_authority.is_some() ||
+            raffle.prizes.iter().any(|prize| prize.escrow == *escrow_info.key) {
+            return Err(LotteryError::InvalidTokenAccount.into());
+        }
+
+        invoke(
+            &spl_token::instruction::transfer(
+                token_program_info.key,
+                source_info.key,
+                escrow_info.key,
+                sponsor_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                source_info.clone(),
+                escrow_info.clone(),
+                sponsor_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        raffle.prizes.push(RafflePrize{
+            mint: *mint_info.key,
+            escrow: *escrow_info.key,
+            winner: Pubkey::default(),
+            round: 0,
+            status: PrizeStatus::Escrowed,
+        });
+        RaffleState::pack(raffle, &mut raffle_info.data.borrow_mut())?;
+
+        log_event(&LotteryEvent::PrizeDeposited{mint: *mint_info.key});
+        Ok(())
+    }
+
+    /// Processes a [CancelRaffle](enum.Instruction.html).
+    ///
+    /// The pool goes back to rolling its fund, the sponsor withdraws the prizes.
+    pub fn process_cancel_raffle(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let raffle_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        let mut raffle = Self::load_raffle(program_id, pool_info, raffle_info)?;
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        if raffle.status != RaffleStatus::Open {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        raffle.status = RaffleStatus::Cancelled;
+        RaffleState::pack(raffle, &mut raffle_info.data.borrow_mut())?;
+        pool.raffle = false;
+        pool.pack_into_slice(pool_data);
+
+        log_event(&LotteryEvent::RaffleCancelled);
+        Ok(())
+    }
+
+    /// Processes a [ClaimPrize](enum.Instruction.html).
+    ///
+    /// Accounts after the billboard are the token program, the escrow, the
+    /// vault authority and a token account of the winner.
+    pub fn process_claim_prize(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        index: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let winner_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let raffle_info= next_account_info(account_info_iter)?;
+        let award_info = next_account_info(account_info_iter)?;
+
+        //check permission first
+        if award_info.owner != program_id ||
+            !winner_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+        if award_info.data_len() != AwardState::LEN{
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let mut raffle = Self::load_raffle(program_id, pool_info, raffle_info)?;
+        let pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        if pool.billboard != *award_info.key {
+            return Err(LotteryError::InvalidAccountForReward.into());
+        }
+        let prize = raffle.prizes
+            .get_mut(index as usize)
+            .ok_or(LotteryError::InvalidInstruction)?;
+        if prize.status != PrizeStatus::Won || prize.winner != *winner_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        let mut award= AwardState::unpack_unchecked(&award_info.data.borrow())?;
+        let bill = award.billboard
+            .get_mut(prize.round as usize)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Self::release_prize(program_id, pool_info, prize, winner_info, account_info_iter.as_slice())?;
+        prize.status = PrizeStatus::Claimed;
+        bill.status = AwardStatus::Rewarded;
+        let event = LotteryEvent::PrizeClaimed{
+            winner: prize.winner,
+            mint: prize.mint,
+        };
+
         AwardState::pack(award, &mut award_info.data.borrow_mut())?;
+        RaffleState::pack(raffle, &mut raffle_info.data.borrow_mut())?;
+        log_event(&event);
+        Ok(())
+    }
+
+    /// Processes a [WithdrawPrize](enum.Instruction.html).
+    ///
+    /// Accounts after the raffle are the token program, the escrow, the vault
+    /// authority and a token account of the sponsor.
+    pub fn process_withdraw_prize(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        index: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let sponsor_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let raffle_info= next_account_info(account_info_iter)?;
+
+        let mut raffle = Self::load_raffle(program_id, pool_info, raffle_info)?;
+        if !sponsor_info.is_signer ||
+            raffle.sponsor != *sponsor_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if raffle.status != RaffleStatus::Cancelled {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        let prize = raffle.prizes
+            .get_mut(index as usize)
+            .ok_or(LotteryError::InvalidInstruction)?;
+        if prize.status != PrizeStatus::Escrowed {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        Self::release_prize(program_id, pool_info, prize, sponsor_info, account_info_iter.as_slice())?;
+        prize.status = PrizeStatus::Withdrawn;
+        let event = LotteryEvent::PrizeWithdrawn{
+            sponsor: *sponsor_info.key,
+            mint: prize.mint,
+        };
+
+        RaffleState::pack(raffle, &mut raffle_info.data.borrow_mut())?;
+        log_event(&event);
+        Ok(())
+    }
+
+    /// Loads the raffle of `pool_info`, both accounts checked to be the program's.
+    fn load_raffle(
+        program_id: &Pubkey,
+        pool_info: &AccountInfo,
+        raffle_info: &AccountInfo,
+    ) -> Result<RaffleState, ProgramError> {
+        if pool_info.owner != program_id ||
+            raffle_info.owner != program_id {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if pool_info.data_len() != LOTTERY_STATE_LEN ||
+            raffle_info.data_len() != RaffleState::LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        let raffle = RaffleState::unpack(&raffle_info.data.borrow())?;
+        if raffle.pool != *pool_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        Ok(raffle)
+    }
+
+    /// Transfers the NFT of `prize` out of its escrow to `recipient_info`,
+    /// `token_accounts` starting with the token program, the escrow, the vault
+    /// authority and a token account of the recipient.
+    fn release_prize<'a>(
+        program_id: &Pubkey,
+        pool_info: &AccountInfo<'a>,
+        prize: &RafflePrize,
+        recipient_info: &AccountInfo<'a>,
+        token_accounts: &[AccountInfo<'a>],
+    ) -> ProgramResult {
+        let account_info_iter = &mut token_accounts.iter();
+        let token_program_info = next_account_info(account_info_iter)?;
+        let escrow_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let (authority, bump) = LotteryState::find_vault_authority(program_id, pool_info.key);
+        if *token_program_info.key != spl_token::id() ||
+            *escrow_info.key != prize.escrow ||
+            *authority_info.key != authority {
+            return Err(LotteryError::InvalidTokenAccount.into());
+        }
+        let destination = TokenAccount::unpack(&destination_info.data.borrow())?;
+        if *destination_info.owner != spl_token::id() ||
+            destination.owner != *recipient_info.key ||
+            destination.mint != prize.mint {
+            return Err(LotteryError::InvalidTokenAccount.into());
+        }
+
+        let seeds: &[&[u8]] = &[VAULT_SEED, pool_info.key.as_ref(), &[bump]];
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program_info.key,
+                escrow_info.key,
+                destination_info.key,
+                authority_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                escrow_info.clone(),
+                destination_info.clone(),
+                authority_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[seeds],
+        )
+    }
+
+    /// Processes a [SetLotto](enum.Instruction.html).
+    ///
+    /// Only changes between rounds, `picks` 0 turns the pool back to drawing
+    /// one ticket.
+    pub fn process_set_lotto(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        picks: u8,
+        range: u8,
+        tier_bps: [u16; MAX_LOTTO_PICKS],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        // every tier pays a share of the same fund, and only up to `picks` matches exist
+        let total_bps: u64 = tier_bps.iter().map(|bps| *bps as u64).sum();
+        if picks as usize > MAX_LOTTO_PICKS ||
+            range > MAX_LOTTO_RANGE ||
+            picks > range ||
+            total_bps > BPS_DENOMINATOR ||
+            tier_bps[picks as usize..].iter().any(|bps| *bps != 0) {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        if pool.player_count != 0 || pool.lotto_pending() || pool.raffle || pool.is_scratch() {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        pool.lotto_picks = picks;
+        pool.lotto_range = if picks == 0 { 0 } else { range };
+        pool.lotto_tier_bps = tier_bps;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [SettleLotto](enum.Instruction.html).
+    ///
+    /// Anyone can crank it after a lotto roll. The first call draws the
+    /// numbers from the hash of the slot the roll closed sales in, once that
+    /// slot is over; should the hash have expired, it waits for the hash of
+    /// the current slot instead. The first pass counts the winning tickets of
+    /// each tier over `count` entries per call, the second pass pushes an
+    /// award bill for each of them. Each bill takes a round, tier shares
+    /// nobody won stay in the fund for the next round.
+    pub fn process_settle_lotto(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        count: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let caller_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let award_info = next_account_info(account_info_iter)?;
+        let slot_hashes_info = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+
+        if award_info.owner != program_id ||
+            pool_info.owner != program_id ||
+            *slot_hashes_info.key != sysvar::slot_hashes::id() ||
+            !caller_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN ||
+            award_info.data_len() != AwardState::LEN{
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        if pool.billboard != *award_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if !pool.lotto_pending() {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        let mut events = Vec::new();
+        if pool.lotto_drawn == 0 {
+            if clock.slot <= pool.lotto_slot {
+                return Err(LotteryError::RevealTooEarly.into());
+            }
+            match slot_hash(&slot_hashes_info.data.borrow(), pool.lotto_slot) {
+                Some(hash) => {
+                    pool.lotto_drawn = lotto_numbers(&hash, pool.lotto_picks, pool.lotto_range);
+                    events.push(LotteryEvent::LottoDrawn{numbers: pool.lotto_drawn});
+                }
+                None => {
+                    // sales are closed , so no ticket can pick the hash of
+                    // the current slot either
+                    log_info(&format!("hash of slot {} expired , drawing from slot {}", pool.lotto_slot, clock.slot));
+                    pool.lotto_slot = clock.slot;
+                    pool.pack_into_slice(pool_data);
+                    return Ok(());
+                }
+            }
+        }
+
+        let mut award= AwardState::unpack_unchecked(&award_info.data.borrow())?;
+        let end = pool.lotto_cursor.saturating_add(count).min(pool.player_count);
+        let mut collected = 0u64;
+        for i in pool.lotto_cursor..end {
+            let entry = TicketEntry::unpack_from_slice(&pool_data[LotteryState::entry_offset(i as usize)..])?;
+            let tier = match pool.lotto_tier(entry.picks) {
+                Some(tier) => tier,
+                None => continue,
+            };
+            if !pool.lotto_paying {
+                pool.lotto_winners[tier] += 1;
+                continue;
+            }
+            if award.billboard.len() >= MAX_AWARD {
+                return Err(LotteryError::BillboardFull.into());
+            }
+            let (prize, fee) = LotteryState::split_fee(pool.lotto_share(tier), pool.prize_fee_bps);
+            collected = collected
+                .checked_add(fee)
+                .ok_or(LotteryError::Overflow)?;
+            award.billboard.push(AwardBill{
+                account: entry.owner,
+                award: prize,
+                status: AwardStatus::Unclaimed,
+                timestamp:clock.unix_timestamp,
+                extensions: pool.round_extensions,
+                deadline: pool.claim_deadline(clock.unix_timestamp),
+            });
+            pool.round += 1;
+            events.push(LotteryEvent::Rolled{
+                winner: entry.owner,
+                award: prize,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+        pool.lotto_cursor = end;
+        pool.fees = pool.fees
+            .checked_add(collected)
+            .ok_or(LotteryError::Overflow)?;
+
+        let winners: u16 = pool.lotto_winners.iter().sum();
+        let finished = end == pool.player_count && (pool.lotto_paying || winners == 0);
+        if end == pool.player_count && !finished {
+            pool.lotto_paying = true;
+            pool.lotto_cursor = 0;
+        }
+        if finished {
+            let fund = if winners == 0 {
+                // nobody won, record the round and carry the fund over
+                if award.billboard.len() >= MAX_AWARD {
+                    return Err(LotteryError::BillboardFull.into());
+                }
+                award.billboard.push(AwardBill{
+                    account: Pubkey::default(),
+                    award: pool.fund,
+                    status: AwardStatus::RolledOver,
+                    timestamp:clock.unix_timestamp,
+                    extensions: pool.round_extensions,
+                    deadline: 0,
+                });
+                pool.round += 1;
+                let next_fund = pool.roll_over();
+                events.push(LotteryEvent::RolledOver{
+                    fund: next_fund,
+                    timestamp: clock.unix_timestamp,
+                });
+                next_fund
+            } else {
+                let awarded = (0..MAX_LOTTO_PICKS)
+                    .map(|tier| pool.lotto_share(tier) * pool.lotto_winners[tier] as u64)
+                    .sum::<u64>();
+                pool.fund.saturating_sub(awarded)
+            };
+            events.push(LotteryEvent::LottoSettled{winners, fund});
+            events.push(LotteryEvent::FundChanged{fund});
+            pool.fund = fund;
+            pool.player_count = 0;
+            pool.paid_entries = 0;
+            pool.round_players = 0;
+            pool.round_extensions = 0;
+            pool.extended_until = 0;
+            pool.lotto_drawn = 0;
+            pool.lotto_slot = 0;
+            pool.lotto_fund = 0;
+            pool.lotto_cursor = 0;
+            pool.lotto_paying = false;
+            pool.lotto_winners = [0; MAX_LOTTO_PICKS];
+        }
+        AwardState::pack(award, &mut award_info.data.borrow_mut())?;
+        pool.pack_into_slice(pool_data);
+
+        for event in &events {
+            log_event(event);
+        }
+        if collected > 0 {
+            log_event(&LotteryEvent::FeeCollected{fee: collected, fees: pool.fees});
+        }
+        Ok(())
+    }
+
+    /// Processes a [SetScratch](enum.Instruction.html).
+    ///
+    /// The table must keep an edge for the pool, a card returns less than its
+    /// price on average.
+    pub fn process_set_scratch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        tiers: [ScratchTier; MAX_SCRATCH_TIERS],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        // a tier pays iff it can be won, and the expected return stays under
+        // the price, which also keeps the chances within the scale
+        let expected: u64 = tiers
+            .iter()
+            .map(|tier| tier.chance() * tier.multiplier as u64)
+            .sum();
+        if tiers.iter().any(|tier| tier.odds as u64 > SCRATCH_SCALE || (tier.odds == 0) != (tier.multiplier == 0)) ||
+            expected >= SCRATCH_SCALE {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        if pool.scratch_liability != 0 {
+            return Err(LotteryError::CardsPending.into());
+        }
+        if pool.player_count != 0 || pool.lotto_pending() || pool.raffle || pool.is_lotto() {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        pool.scratch_tiers = tiers;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [BuyScratch](enum.Instruction.html).
+    ///
+    /// The cards are revealed by the hash of the slot they are bought in, so
+    /// the fund must cover all pending cards winning the top tier.
+    pub fn process_buy_scratch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        cards: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let system_program_info= next_account_info(account_info_iter)?;
+        let player_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let record_info= next_account_info(account_info_iter)?;
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+        if pool_info.owner != program_id ||
+            !player_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let mut pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        if pool.paused {
+            return Err(LotteryError::Paused.into());
+        }
+        if !pool.is_scratch() ||
+            pool.price == 0 ||
+            cards == 0 ||
+            cards > MAX_SCRATCH_CARDS {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        let price = pool.price;
+        let cost = price
+            .checked_mul(cards as u64)
+            .ok_or(LotteryError::Overflow)?;
+        let (amount, fee) = LotteryState::split_fee(cost, pool.fee_bps);
+        let fund = pool.fund
+            .checked_add(amount)
+            .ok_or(LotteryError::Overflow)?;
+        let fees = pool.fees
+            .checked_add(fee)
+            .ok_or(LotteryError::Overflow)?;
+        let liability = pool
+            .scratch_max_payout(price)
+            .and_then(|payout| payout.checked_mul(cards as u64))
+            .and_then(|payout| payout.checked_add(pool.scratch_liability))
+            .ok_or(LotteryError::Overflow)?;
+        if liability > fund {
+            return Err(LotteryError::LiabilityExceeded.into());
+        }
+
+        // per player caps
+        let mut record = Self::load_player_record(
+            program_id,
+            pool_info,
+            player_info,
+            record_info,
+            system_program_info,
+            clock,
+            rent,
+        )?;
+        if record.scratch_cards != 0 {
+            return Err(LotteryError::CardsPending.into());
+        }
+        let today = pool.day_of(clock.unix_timestamp);
+        if let Some(left) = record.spend_left(&pool, today) {
+            if left < cost {
+                return Err(LotteryError::SpendCapReached.into());
+            }
+        }
+        if record.spend_day != today {
+            record.spend_day = today;
+            record.day_spent = 0;
+        }
+        record.day_spent = record.day_spent.saturating_add(cost);
+        record.scratch_slot = clock.slot;
+        record.scratch_cards = cards;
+        record.scratch_price = price;
+
+        Self::deposit(
+            &pool,
+            pool_info,
+            player_info,
+            system_program_info,
+            account_info_iter.as_slice(),
+            cost,
+        )?;
+
+        PlayerState::pack(record, &mut record_info.data.borrow_mut())?;
+        pool.fund = fund;
+        pool.fees = fees;
+        pool.scratch_liability = liability;
+        pool.pack_into_slice(&mut pool_info.data.borrow_mut());
+
+        log_event(&LotteryEvent::ScratchBought{
+            player: *player_info.key,
+            cards,
+            slot: clock.slot,
+        });
+        if fee > 0 {
+            log_event(&LotteryEvent::FeeCollected{fee, fees});
+        }
+        log_event(&LotteryEvent::FundChanged{fund});
+        Ok(())
+    }
+
+    /// Processes a [RevealScratch](enum.Instruction.html).
+    ///
+    /// Anyone can reveal the pending cards of a player once their slot is
+    /// over. Cards whose slot hash is no longer kept by the SlotHashes sysvar
+    /// lose, otherwise a player could wait out a losing hash.
+    pub fn process_reveal_scratch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let slot_hashes_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let player_info = next_account_info(account_info_iter)?;
+        let record_info= next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+
+        if pool_info.owner != program_id ||
+            record_info.owner != program_id ||
+            *slot_hashes_info.key != sysvar::slot_hashes::id() {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        let (address, _) = PlayerState::find_address(program_id, pool_info.key, player_info.key);
+        if address != *record_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        let mut record = PlayerState::unpack(&record_info.data.borrow())?;
+        if record.scratch_cards == 0 {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        if clock.slot <= record.scratch_slot {
+            return Err(LotteryError::RevealTooEarly.into());
+        }
+        let cards = record.scratch_cards;
+        let price = record.scratch_price;
+        let liability = pool
+            .scratch_max_payout(price)
+            .and_then(|payout| payout.checked_mul(cards as u64))
+            .ok_or(LotteryError::Overflow)?;
+
+        let mut won = 0u64;
+        match slot_hash(&slot_hashes_info.data.borrow(), record.scratch_slot) {
+            Some(hash) => {
+                for card in 0..cards {
+                    let random = scratch_random(&hash, player_info.key, card);
+                    if let Some(tier) = scratch_tier(random, &pool.scratch_tiers) {
+                        won += price * pool.scratch_tiers[tier].multiplier as u64;
+                    }
+                }
+            }
+            None => log_info(&format!("cards of slot {} expired", record.scratch_slot)),
+        }
+        // the liability check at purchase keeps the winnings within the fund
+        let fund = pool.fund
+            .checked_sub(won)
+            .ok_or(LotteryError::InsufficentFunds)?;
+        let (net, fee) = LotteryState::split_fee(won, pool.prize_fee_bps);
+        let fees = pool.fees
+            .checked_add(fee)
+            .ok_or(LotteryError::Overflow)?;
+        record.balance = record.balance
+            .checked_add(net)
+            .ok_or(LotteryError::Overflow)?;
+        record.scratch_slot = 0;
+        record.scratch_cards = 0;
+        record.scratch_price = 0;
+
+        PlayerState::pack(record, &mut record_info.data.borrow_mut())?;
+        pool.fund = fund;
+        pool.fees = fees;
+        pool.scratch_liability = pool.scratch_liability.saturating_sub(liability);
+        pool.pack_into_slice(pool_data);
+
+        log_event(&LotteryEvent::ScratchRevealed{
+            player: *player_info.key,
+            cards,
+            won: net,
+        });
+        if fee > 0 {
+            log_event(&LotteryEvent::FeeCollected{fee, fees});
+        }
+        if won > 0 {
+            log_event(&LotteryEvent::FundChanged{fund});
+        }
+        Ok(())
+    }
+
+    /// Processes a [ClaimScratch](enum.Instruction.html).
+    pub fn process_claim_scratch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let player_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let record_info= next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+        if pool_info.owner != program_id ||
+            record_info.owner != program_id ||
+            !player_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        let (address, _) = PlayerState::find_address(program_id, pool_info.key, player_info.key);
+        if address != *record_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        let pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        let mut record = PlayerState::unpack(&record_info.data.borrow())?;
+        let amount = record.balance;
+        if amount == 0 {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        Self::withdraw(
+            program_id,
+            &pool,
+            pool_info,
+            player_info,
+            rent,
+            account_info_iter.as_slice(),
+            amount,
+        )?;
+        record.balance = 0;
+        PlayerState::pack(record, &mut record_info.data.borrow_mut())?;
+
+        log_event(&LotteryEvent::ScratchClaimed{
+            player: *player_info.key,
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Processes a [CancelRound](enum.Instruction.html).
+    ///
+    /// The round stops selling and rolling until every paid ticket is
+    /// refunded, then it is recorded as cancelled and the next one starts.
+    pub fn process_cancel_round(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let admin_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let award_info = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+
+        //check permission first
+        if award_info.owner != program_id ||
+            pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN ||
+            award_info.data_len() != AwardState::LEN{
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        if pool.billboard != *award_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if pool.is_scratch() {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        if pool.cancelled {
+            return Err(LotteryError::RoundCancelled.into());
+        }
+        if pool.lotto_pending() {
+            return Err(LotteryError::SettlementPending.into());
+        }
+        pool.cancelled = true;
+        log_event(&LotteryEvent::RoundCancelled{round: pool.round});
+        if pool.paid_entries == 0 {
+            Self::close_cancelled_round(&mut pool, award_info, clock)?;
+        }
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [Refund](enum.Instruction.html).
+    ///
+    /// Anyone can refund a ticket of a cancelled round, the price goes back
+    /// to the owner of the entry. The last refund closes the round.
+    pub fn process_refund(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        index: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let award_info = next_account_info(account_info_iter)?;
+        let player_info = next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+        if award_info.owner != program_id ||
+            pool_info.owner != program_id {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN ||
+            award_info.data_len() != AwardState::LEN{
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let mut pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        if pool.billboard != *award_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if !pool.cancelled || index >= pool.player_count {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        let offset = LotteryState::entry_offset(index as usize);
+        let mut entry = TicketEntry::unpack_from_slice(&pool_info.data.borrow()[offset..])?;
+        if entry.owner != *player_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if entry.paid == 0 || entry.refunded {
+            return Err(LotteryError::NotRefundable.into());
+        }
+
+        // the referrer's share comes back out of their balance as far as it
+        // was not claimed, the player's record and the referrer's follow
+        // the rent sysvar
+        let mut clawback = 0;
+        if entry.referral > 0 {
+            let record_info = next_account_info(account_info_iter)?;
+            let referrer_info = next_account_info(account_info_iter)?;
+            let (address, _) = PlayerState::find_address(program_id, pool_info.key, player_info.key);
+            if address != *record_info.key || record_info.owner != program_id {
+                return Err(LotteryError::InvalidPermission.into());
+            }
+            let record = PlayerState::unpack(&record_info.data.borrow())?;
+            let (address, _) = PlayerState::find_address(program_id, pool_info.key, &record.referrer);
+            if address != *referrer_info.key || referrer_info.owner != program_id {
+                return Err(LotteryError::InvalidReferrer.into());
+            }
+            let mut referrer_record = PlayerState::unpack(&referrer_info.data.borrow())?;
+            clawback = entry.referral.min(referrer_record.balance);
+            referrer_record.balance -= clawback;
+            referrer_record.referral_earned = referrer_record.referral_earned.saturating_sub(clawback);
+            PlayerState::pack(referrer_record, &mut referrer_info.data.borrow_mut())?;
+        }
+
+        // the fee taken from the price goes back too, as far as it was not withdrawn,
+        // what was neither taken back from fees nor from the referrer comes from the fund
+        let amount = entry.paid;
+        let fee = entry.fee.min(pool.fees);
+        pool.fund = pool.fund
+            .checked_sub(amount - fee - clawback)
+            .ok_or(LotteryError::InsufficentFunds)?;
+        pool.fees -= fee;
+        Self::withdraw(
+            program_id,
+            &pool,
+            pool_info,
+            player_info,
+            rent,
+            account_info_iter.as_slice(),
+            amount,
+        )?;
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        entry.refunded = true;
+        entry.pack_into_slice(&mut pool_data[offset..]);
+        pool.paid_entries -= 1;
+        log_event(&LotteryEvent::Refunded{
+            player: entry.owner,
+            amount,
+        });
+        log_event(&LotteryEvent::FundChanged{fund: pool.fund});
+        if pool.paid_entries == 0 {
+            Self::close_cancelled_round(&mut pool, award_info, clock)?;
+        }
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Records the cancelled round on the billboard and starts the next one,
+    /// the fund carried over.
+    fn close_cancelled_round(
+        pool: &mut LotteryState,
+        award_info: &AccountInfo,
+        clock: &Clock,
+    ) -> ProgramResult {
+        let mut award = AwardState::unpack_unchecked(&award_info.data.borrow())?;
+        if award.billboard.len() >= MAX_AWARD {
+            return Err(LotteryError::BillboardFull.into());
+        }
+        award.billboard.push(AwardBill{
+            account: Pubkey::default(),
+            award: pool.fund,
+            status: AwardStatus::Cancelled,
+            timestamp: clock.unix_timestamp,
+            extensions: pool.round_extensions,
+            deadline: 0,
+        });
+        AwardState::pack(award, &mut award_info.data.borrow_mut())?;
+        pool.round += 1;
+        pool.player_count = 0;
+        pool.paid_entries = 0;
+        pool.round_players = 0;
+        pool.round_extensions = 0;
+        pool.extended_until = 0;
+        pool.cancelled = false;
+        Ok(())
+    }
+
+    /// Moves `amount` from `payer_info` into the pool.
+    ///
+    /// Lamport pools take a system transfer; token pools take an SPL Token
+    /// transfer, `token_accounts` starting with the token program, the
+    /// payer's token account and the pool's vault.
+    fn deposit<'a>(
+        pool: &LotteryState,
+        pool_info: &AccountInfo<'a>,
+        payer_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        token_accounts: &[AccountInfo<'a>],
+        amount: u64,
+    ) -> ProgramResult {
+        if !pool.is_token_pool() {
+            return invoke(
+                &system_instruction::transfer(payer_info.key, pool_info.key, amount),
+                &[
+                    payer_info.clone(),
+                    pool_info.clone(),
+                    system_program_info.clone(),
+                ],
+            );
+        }
+
+        let account_info_iter = &mut token_accounts.iter();
+        let token_program_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        if *token_program_info.key != spl_token::id() ||
+            *vault_info.key != pool.vault {
+            return Err(LotteryError::InvalidTokenAccount.into());
+        }
+        invoke(
+            &spl_token::instruction::transfer(
+                token_program_info.key,
+                source_info.key,
+                vault_info.key,
+                payer_info.key,
+                &[],
+                amount,
+            )?,
+            &[
+                source_info.clone(),
+                vault_info.clone(),
+                payer_info.clone(),
+                token_program_info.clone(),
+            ],
+        )
+    }
+
+    /// Pays `amount` out of the pool to `recipient_info`.
+    ///
+    /// Lamport pools pay from the pool account, which must stay rent exempt;
+    /// token pools pay from the vault, `token_accounts` starting with the
+    /// token program, the vault, the vault authority and a token account of
+    /// the recipient.
+    fn withdraw<'a>(
+        program_id: &Pubkey,
+        pool: &LotteryState,
+        pool_info: &AccountInfo<'a>,
+        recipient_info: &AccountInfo<'a>,
+        rent: &Rent,
+        token_accounts: &[AccountInfo<'a>],
+        amount: u64,
+    ) -> ProgramResult {
+        if !pool.is_token_pool() {
+            let reserve = rent.minimum_balance(pool_info.data_len());
+            let available = pool_info.lamports().saturating_sub(reserve);
+            if available < amount {
+                return Err(LotteryError::InsufficentFunds.into());
+            }
+            **pool_info.try_borrow_mut_lamports()? -= amount;
+            **recipient_info.try_borrow_mut_lamports()? += amount;
+            return Ok(());
+        }
+
+        let account_info_iter = &mut token_accounts.iter();
+        let token_program_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let (authority, bump) = LotteryState::find_vault_authority(program_id, pool_info.key);
+        if *token_program_info.key != spl_token::id() ||
+            *vault_info.key != pool.vault ||
+            *authority_info.key != authority {
+            return Err(LotteryError::InvalidTokenAccount.into());
+        }
+        // the tokens go to an account of the recipient, in the pool's mint
+        let destination = TokenAccount::unpack(&destination_info.data.borrow())?;
+        if *destination_info.owner != spl_token::id() ||
+            destination.owner != *recipient_info.key ||
+            destination.mint != pool.mint {
+            return Err(LotteryError::InvalidTokenAccount.into());
+        }
+        let vault = TokenAccount::unpack(&vault_info.data.borrow())?;
+        if vault.amount < amount {
+            return Err(LotteryError::InsufficentFunds.into());
+        }
+
+        let seeds: &[&[u8]] = &[VAULT_SEED, pool_info.key.as_ref(), &[bump]];
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program_info.key,
+                vault_info.key,
+                destination_info.key,
+                authority_info.key,
+                &[],
+                amount,
+            )?,
+            &[
+                vault_info.clone(),
+                destination_info.clone(),
+                authority_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[seeds],
+        )
+    }
+
+    /// Checks an instruction needing `role` is signed by the key holding it,
+    /// the owner keeps every role and approves through the admin set.
+    fn check_role(pool: &LotteryState, accounts: &[AccountInfo], role: Role) -> ProgramResult {
+        if let Some(holder) = pool.role_holder(role) {
+            if holder != Pubkey::default() &&
+                accounts.iter().any(|account| account.is_signer && *account.key == holder) {
+                return Ok(());
+            }
+        }
+        Self::check_admins(pool, accounts)
+    }
+
+    /// Checks a privileged instruction is approved by the pool's admin set:
+    /// at least `admin_threshold` of its signers among the signers of
+    /// `accounts`, or the program's admin key while no admin set is configured.
+    ///
+    /// Co-signers are passed as extra accounts after the instruction's own.
+    fn check_admins(pool: &LotteryState, accounts: &[AccountInfo]) -> ProgramResult {
+        let signers: Vec<Pubkey> = accounts
+            .iter()
+            .filter(|account| account.is_signer)
+            .map(|account| *account.key)
+            .collect();
+        let approved = if pool.admin_threshold == 0 {
+            signers.contains(&Pubkey::from_str(Self::ADMIN_KEY).unwrap())
+        } else {
+            pool.admin_approvals(&signers) >= pool.admin_threshold as usize
+        };
+        if !approved {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        Ok(())
+    }
+
+    /// Moves the pending GM change into the pool, keeping the prize from
+    /// dropping under players who already hold tickets this round.
+    /// The prize may have been paid out while the change was pending, so
+    /// it is checked against the current fund again.
+    fn apply_pending_gm(pool: &mut LotteryState) -> ProgramResult {
+        if pool.pending_fund > pool.fund {
+            return Err(LotteryError::FundNotBacked.into());
+        }
+        if (pool.player_count > 0 && pool.pending_fund < pool.fund) ||
+            pool.pending_fund < pool.scratch_liability {
+            return Err(LotteryError::PrizeDecrease.into());
+        }
+        // the lamports backing the cut stay in the pool , the treasury takes
+        // them with the fees
+        let cut = pool.fund - pool.pending_fund;
+        let fees = pool.fees
+            .checked_add(cut)
+            .ok_or(LotteryError::Overflow)?;
+        pool.fund = pool.pending_fund;
+        pool.fees = fees;
+        pool.price = pool.pending_price;
+        pool.pending_fund = 0;
+        pool.pending_price = 0;
+        pool.pending_at = 0;
+
+        log_event(&LotteryEvent::GMApplied{
+            fund: pool.fund,
+            price: pool.price,
+        });
+        if cut > 0 {
+            log_event(&LotteryEvent::FeeCollected{fee: cut, fees});
+        }
+        log_event(&LotteryEvent::FundChanged{fund: pool.fund});
+        Ok(())
+    }
+
+    /// Processes an [Initialize](enum.Instruction.html).
+    pub fn process_roll(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let admin_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let award_info = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+        
+        //check permission first
+        if award_info.owner != program_id ||
+            pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN ||
+            award_info.data_len() != AwardState::LEN{
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_role(&pool, accounts, Role::Roller)?;
+        if pool.billboard != *award_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if pool.paused {
+            return Err(LotteryError::Paused.into());
+        }
+        // scratch cards are won when revealed, there is nothing to roll
+        if pool.is_scratch() {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        if pool.cancelled {
+            return Err(LotteryError::RoundCancelled.into());
+        }
+        if pool.lotto_pending() {
+            return Err(LotteryError::SettlementPending.into());
+        }
+        if !pool.can_roll(clock.unix_timestamp) {
+            return Err(LotteryError::RollTooEarly.into());
+        }
+        let player_count = pool.player_count;
+        if player_count > 0 &&
+            !pool.meets_threshold(Self::total_tickets(pool_data, player_count)?) {
+            if pool.threshold_extension > 0 && pool.round_extensions < pool.max_extensions {
+                // too few took part , give the round more time to fill up
+                pool.extended_until = clock.unix_timestamp.saturating_add(pool.threshold_extension);
+                pool.round_extensions = pool.round_extensions.saturating_add(1);
+                pool.pack_into_slice(pool_data);
+
+                log_event(&LotteryEvent::RoundExtended{
+                    round: pool.round,
+                    until: pool.extended_until,
+                });
+                return Ok(());
+            }
+            // too few took part and out of extensions , the round is cancelled
+            // and its tickets refunded
+            pool.cancelled = true;
+            log_event(&LotteryEvent::RoundCancelled{round: pool.round});
+            if pool.paid_entries == 0 {
+                Self::close_cancelled_round(&mut pool, award_info, clock)?;
+            }
+            pool.pack_into_slice(pool_data);
+            return Ok(());
+        }
+        pool.last_roll = clock.unix_timestamp;
+        let fund = pool.fund;
+
+        let mut award= AwardState::unpack_unchecked(&award_info.data.borrow())?;
+        if award.billboard.len() >= MAX_AWARD {
+            return Err(LotteryError::BillboardFull.into());
+        }
+
+        if player_count == 0 {
+            // nobody played, record the round and carry the fund over
+            let next_fund = pool.roll_over();
+            log_info(&format!("rolled over {} to {}", fund, next_fund));
+            award.billboard.push(AwardBill{
+                account: Pubkey::default(),
+                award: fund,
+                status: AwardStatus::RolledOver,
+                timestamp:clock.unix_timestamp,
+                extensions: pool.round_extensions,
+                deadline: 0,
+            });
+            AwardState::pack(award, &mut award_info.data.borrow_mut())?;
+            pool.round += 1;
+            pool.pack_into_slice(pool_data);
+
+            log_event(&LotteryEvent::RolledOver{
+                fund: next_fund,
+                timestamp: clock.unix_timestamp,
+            });
+            if next_fund != fund {
+                log_event(&LotteryEvent::FundChanged{fund: next_fund});
+            }
+            return Ok(());
+        }
+
+        if pool.raffle {
+            // the prizes are drawn instead of the fund, which stays for the next round
+            let raffle_info = next_account_info(account_info_iter)?;
+            if raffle_info.owner != program_id {
+                return Err(LotteryError::InvalidPermission.into());
+            }
+            if raffle_info.data_len() != RaffleState::LEN {
+                return Err(LotteryError::InvalidAccountLength.into());
+            }
+            let mut raffle = RaffleState::unpack(&raffle_info.data.borrow())?;
+            if raffle.pool != *pool_info.key || raffle.prizes.is_empty() {
+                return Err(LotteryError::InvalidInstruction.into());
+            }
+            if award.billboard.len()+raffle.prizes.len() > MAX_AWARD {
+                return Err(LotteryError::BillboardFull.into());
+            }
+
+            let total = Self::total_tickets(pool_data, player_count)?;
+            let tickets = raffle_tickets(clock.unix_timestamp, total, raffle.prizes.len());
+            let mut events = Vec::new();
+            for (prize, ticket) in raffle.prizes.iter_mut().zip(tickets) {
+                let l = find_entry(player_count as usize, ticket, |i| {
+                    let s = LotteryState::entry_offset(i)+32;
+                    u64::from_le_bytes(*array_ref!(pool_data,s,8))
+                });
+                log_info(&format!("ticket {} of {} for {}, entry {}", ticket, total, prize.mint, l));
+                let s = LotteryState::entry_offset(l);
+                prize.winner = Pubkey::new_from_array(*array_ref!(pool_data,s,32));
+                prize.round = award.billboard.len() as u16;
+                prize.status = PrizeStatus::Won;
+                award.billboard.push(AwardBill{
+                    account: prize.winner,
+                    award: 0,
+                    status: AwardStatus::Prize,
+                    timestamp:clock.unix_timestamp,
+                    extensions: pool.round_extensions,
+                    deadline: 0,
+                });
+                events.push(LotteryEvent::PrizeDrawn{
+                    round: prize.round,
+                    winner: prize.winner,
+                    mint: prize.mint,
+                });
+            }
+            raffle.status = RaffleStatus::Drawn;
+            RaffleState::pack(raffle, &mut raffle_info.data.borrow_mut())?;
+            AwardState::pack(award, &mut award_info.data.borrow_mut())?;
+            // every prize takes a round, keeping rounds and award bills in step
+            pool.player_count = 0;
+            pool.paid_entries = 0;
+            pool.round_players = 0;
+            pool.round_extensions = 0;
+            pool.extended_until = 0;
+            pool.round += events.len() as u32;
+            pool.raffle = false;
+            pool.pack_into_slice(pool_data);
+
+            for event in &events {
+                log_event(event);
+            }
+            return Ok(());
+        }
+
+        if pool.is_lotto() {
+            // BuyLotto left a bill of room for every entry , so the
+            // settlement can always finish
+            // the roll only closes sales , SettleLotto draws from the hash
+            // of this slot once it is over and nobody can buy anymore
+            // tickets stay frozen until SettleLotto has paid the winners
+            pool.lotto_slot = clock.slot;
+            pool.lotto_fund = fund;
+            pool.lotto_cursor = 0;
+            pool.lotto_paying = false;
+            pool.lotto_winners = [0; MAX_LOTTO_PICKS];
+            pool.pack_into_slice(pool_data);
+            return Ok(());
+        }
+
+        //log_info(&format!("unix_timestamp is {}", clock.unix_timestamp));
+        //log_info(&format!("player count is {}", player_count));
+        let total = Self::total_tickets(pool_data, player_count)?;
+        let ticket = winning_ticket(clock.unix_timestamp, total);
+        let l = find_entry(player_count as usize, ticket, |i| {
+            let s = LotteryState::entry_offset(i)+32;
+            u64::from_le_bytes(*array_ref!(pool_data,s,8))
+        });
+        log_info(&format!("ticket {} of {} for winner, entry {}", ticket, total, l));
+        let s = LotteryState::entry_offset(l);
+        let winner = Pubkey::new_from_array(*array_ref!(pool_data,s,32));
+
+        log_info(&format!("winner is {}", winner));
+
+        let (prize, fee) = LotteryState::split_fee(fund, pool.prize_fee_bps);
+        let fees = pool.fees
+            .checked_add(fee)
+            .ok_or(LotteryError::Overflow)?;
+        let bill = AwardBill{
+            account: winner,
+            award: prize,
+            status: AwardStatus::Unclaimed,
+            timestamp:clock.unix_timestamp,
+            extensions: pool.round_extensions,
+            deadline: pool.claim_deadline(clock.unix_timestamp),
+        };
+        award.billboard.push(bill);
+        AwardState::pack(award, &mut award_info.data.borrow_mut())?;
+        pool.player_count = 0;
+        pool.paid_entries = 0;
+        pool.round_players = 0;
+        pool.round_extensions = 0;
+        pool.extended_until = 0;
+        pool.round += 1;
+        // the fund now belongs to the winner, next round starts from scratch
+        pool.fund = 0;
+        pool.fees = fees;
+        pool.pack_into_slice(pool_data);
+
+        log_event(&LotteryEvent::Rolled{
+            winner,
+            award: prize,
+            timestamp: clock.unix_timestamp,
+        });
+        if fee > 0 {
+            log_event(&LotteryEvent::FeeCollected{fee, fees});
+        }
+        log_event(&LotteryEvent::FundChanged{fund: 0});
+        Ok(())
+    }
+
+    /// Processes an [Initialize](enum.Instruction.html).
+    pub fn process_reward(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let _system_program_info= next_account_info(account_info_iter)?;
+        let admin_info = next_account_info(account_info_iter)?;
+        let account_info= next_account_info(account_info_iter)?;
+        let award_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+        //check permission first
+        if award_info.owner != program_id ||
+            pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN ||
+            award_info.data_len() != AwardState::LEN{
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        Self::check_role(&pool, accounts, Role::Treasurer)?;
+
+        // the billboard must belong to the pool paying the award
+        if pool.billboard != *award_info.key {
+            return Err(LotteryError::InvalidAccountForReward.into());
+        }
+        
+        let mut award= AwardState::unpack_unchecked(&award_info.data.borrow())?;
+
+
+        for val in &mut award.billboard {
+            log_info(&format!("come to send award: {}:{}:{:?}", val.account, val.award, val.status));
+            if val.status == AwardStatus::Unclaimed {
+                if val.account != *account_info.key {
+                    continue;
+                }
+                log_info(&format!("send award to {}", val.account));
+                Self::withdraw(
+                    program_id,
+                    &pool,
+                    pool_info,
+                    account_info,
+                    rent,
+                    account_info_iter.as_slice(),
+                    val.award,
+                )?;
+                val.status = AwardStatus::Rewarded;
+                log_event(&LotteryEvent::Rewarded{
+                    player: val.account,
+                    award: val.award,
+                });
+            }
+        }
+       
+        AwardState::pack(award, &mut award_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [Claim](enum.Instruction.html).
+    ///
+    /// The winner signs for the award of `round` before its deadline, accounts
+    /// after the rent are the token accounts of a mint pool.
+    pub fn process_claim(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        round: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let winner_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let award_info = next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+        //check permission first
+        if award_info.owner != program_id ||
+            pool_info.owner != program_id ||
+            !winner_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN ||
+            award_info.data_len() != AwardState::LEN{
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        if pool.billboard != *award_info.key {
+            return Err(LotteryError::InvalidAccountForReward.into());
+        }
+
+        let mut award= AwardState::unpack_unchecked(&award_info.data.borrow())?;
+        let bill = award.billboard
+            .get_mut(round as usize)
+            .ok_or(LotteryError::InvalidInstruction)?;
+        if bill.status != AwardStatus::Unclaimed || bill.account != *winner_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if bill.is_expired(clock.unix_timestamp) {
+            return Err(LotteryError::AwardExpired.into());
+        }
+        Self::withdraw(
+            program_id,
+            &pool,
+            pool_info,
+            winner_info,
+            rent,
+            account_info_iter.as_slice(),
+            bill.award,
+        )?;
+        bill.status = AwardStatus::Rewarded;
+        let event = LotteryEvent::Rewarded{
+            player: bill.account,
+            award: bill.award,
+        };
+
+        AwardState::pack(award, &mut award_info.data.borrow_mut())?;
+        log_event(&event);
+        Ok(())
+    }
+
+    /// Processes a [Fund](enum.Instruction.html).
+    pub fn process_fund(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let system_program_info= next_account_info(account_info_iter)?;
+        let sponsor_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let award_info = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+
+        if award_info.owner != program_id ||
+            pool_info.owner != program_id ||
+            !sponsor_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN ||
+            award_info.data_len() != AwardState::LEN{
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let mut pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        if pool.billboard != *award_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        // every sponsorship takes a slot , so it has to be worth a ticket at least
+        if amount < pool.price.max(1) {
+            return Err(LotteryError::SponsorTooSmall.into());
+        }
+        let fund = pool.fund
+            .checked_add(amount)
+            .ok_or(LotteryError::Overflow)?;
+
+        // only the current round's sponsors are kept , past ones live on in
+        // the Funded events
+        let mut award= AwardState::unpack_unchecked(&award_info.data.borrow())?;
+        let round = award.billboard.len() as u16;
+        award.sponsors.retain(|sponsor| sponsor.round == round);
+        if award.sponsors.len() >= MAX_SPONSOR {
+            return Err(LotteryError::TooManySponsors.into());
+        }
+        award.sponsors.push(SponsorBill{
+            account: *sponsor_info.key,
+            amount,
+            round,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Self::deposit(
+            &pool,
+            pool_info,
+            sponsor_info,
+            system_program_info,
+            account_info_iter.as_slice(),
+            amount,
+        )?;
+
+        AwardState::pack(award, &mut award_info.data.borrow_mut())?;
+        pool.fund = fund;
+        pool.pack_into_slice(&mut pool_info.data.borrow_mut());
+
+        log_event(&LotteryEvent::Funded{
+            sponsor: *sponsor_info.key,
+            amount,
+        });
+        log_event(&LotteryEvent::FundChanged{fund});
+        Ok(())
+    }
+
+    /// Processes a [SetRollover](enum.Instruction.html).
+    pub fn process_set_rollover(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        growth_bps: u16,
+        cap: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_role(&pool, accounts, Role::Treasurer)?;
+        pool.rollover_bps = growth_bps;
+        pool.rollover_cap = cap;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [SetClaimWindow](enum.Instruction.html).
+    pub fn process_set_claim_window(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seconds: i64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        if seconds < 0 {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        pool.claim_window = seconds;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes an [Expire](enum.Instruction.html).
+    ///
+    /// Anyone can crank it once the claim window of the award has passed,
+    /// the award goes back to the fund of the current round.
+    pub fn process_expire(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        round: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let caller_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let award_info = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+
+        if award_info.owner != program_id ||
+            pool_info.owner != program_id ||
+            !caller_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN ||
+            award_info.data_len() != AwardState::LEN{
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        if pool.billboard != *award_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        let mut award= AwardState::unpack_unchecked(&award_info.data.borrow())?;
+        let bill = award.billboard
+            .get_mut(round as usize)
+            .ok_or(LotteryError::InvalidInstruction)?;
+        if bill.status != AwardStatus::Unclaimed ||
+            !bill.is_expired(clock.unix_timestamp) {
+            return Err(LotteryError::NotExpired.into());
+        }
+        let fund = pool.fund
+            .checked_add(bill.award)
+            .ok_or(LotteryError::Overflow)?;
+        bill.status = AwardStatus::Expired;
+        let event = LotteryEvent::Expired{
+            round,
+            player: bill.account,
+            award: bill.award,
+        };
+
+        AwardState::pack(award, &mut award_info.data.borrow_mut())?;
+        pool.fund = fund;
+        pool.pack_into_slice(pool_data);
+
+        log_event(&event);
+        log_event(&LotteryEvent::FundChanged{fund});
+        Ok(())
+    }
+
+    /// Processes a [Buy](enum.Instruction.html).
+    ///
+    /// Lotto pools only sell tickets picking `numbers`, see [BuyLotto](enum.Instruction.html).
+    pub fn process_buy(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        numbers: Option<[u8; MAX_LOTTO_PICKS]>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let system_program_info= next_account_info(account_info_iter)?;
+        let player_info = next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let record_info= next_account_info(account_info_iter)?;
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+        if pool_info.owner != program_id ||
+            !player_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let mut pool = LotteryState::unpack_from_slice(&pool_info.data.borrow())?;
+        if pool.paused {
+            return Err(LotteryError::Paused.into());
+        }
+        // a pool without price does not sell tickets , a scratch pool sells cards
+        if pool.price == 0 || pool.is_scratch() {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+        if pool.cancelled {
+            return Err(LotteryError::RoundCancelled.into());
+        }
+        let picks = match numbers {
+            Some(numbers) if pool.is_lotto() => pool
+                .lotto_mask(&numbers)
+                .ok_or(LotteryError::InvalidPicks)?,
+            None if !pool.is_lotto() => 0,
+            _ => return Err(LotteryError::InvalidInstruction.into()),
+        };
+        if pool.lotto_pending() {
+            return Err(LotteryError::SettlementPending.into());
+        }
+        if pool.player_count as usize >= MAX_PLAYER {
+            return Err(LotteryError::TooManyPlayers.into());  
+        }
+        // a lotto ticket may win a bill of its own and the billboard holds a
+        // bill per round , so sales stop at the room left for the draw
+        if pool.is_lotto() &&
+            pool.round as usize + pool.player_count as usize >= MAX_AWARD {
+            return Err(LotteryError::BillboardFull.into());
+        }
+        let price = pool.price;
+        let (amount, fee) = LotteryState::split_fee(price, pool.fee_bps);
+        let fund = pool.fund
+            .checked_add(amount)
+            .ok_or(LotteryError::Overflow)?;
+        let fees = pool.fees
+            .checked_add(fee)
+            .ok_or(LotteryError::Overflow)?;
+
+        // per player caps
+        let mut record = Self::load_player_record(
+            program_id,
+            pool_info,
+            player_info,
+            record_info,
+            system_program_info,
+            clock,
+            rent,
+        )?;
+        let today = pool.day_of(clock.unix_timestamp);
+        if record.tickets_left(&pool) == Some(0) {
+            return Err(LotteryError::TicketCapReached.into());
+        }
+        if let Some(left) = record.spend_left(&pool, today) {
+            if left < price {
+                return Err(LotteryError::SpendCapReached.into());
+            }
+        }
+        if record.round != pool.round {
+            record.round = pool.round;
+            record.round_tickets = 0;
+        }
+        if record.spend_day != today {
+            record.spend_day = today;
+            record.day_spent = 0;
+        }
+        record.round_tickets = record.round_tickets.saturating_add(1);
+        record.day_spent = record.day_spent.saturating_add(price);
+        if record.join_round(&pool) {
+            pool.round_players += 1;
+        }
+
+        // a referred player's purchase pays the referrer a share of the pool's part,
+        // the referrer's record follows the rent sysvar
+        let mut referral = None;
+        if record.referrer != Pubkey::default() {
+            let referrer_info = next_account_info(account_info_iter)?;
+            let (address, _) = PlayerState::find_address(program_id, pool_info.key, &record.referrer);
+            if address != *referrer_info.key || referrer_info.owner != program_id {
+                return Err(LotteryError::InvalidReferrer.into());
+            }
+            let mut referrer_record = PlayerState::unpack(&referrer_info.data.borrow())?;
+            let share = pool.referral_share(price, referrer_record.referral_earned).min(amount);
+            if share > 0 {
+                referrer_record.balance = referrer_record.balance
+                    .checked_add(share)
+                    .ok_or(LotteryError::Overflow)?;
+                referrer_record.referral_earned = referrer_record.referral_earned.saturating_add(share);
+                PlayerState::pack(referrer_record, &mut referrer_info.data.borrow_mut())?;
+                referral = Some((record.referrer, share));
+            }
+        }
+        let fund = fund - referral.map_or(0, |(_, share)| share);
+
+        Self::deposit(
+            &pool,
+            pool_info,
+            player_info,
+            system_program_info,
+            account_info_iter.as_slice(),
+            price,
+        )?;
+
+        PlayerState::pack(record, &mut record_info.data.borrow_mut())?;
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let share = referral.map_or(0, |(_, share)| share);
+        Self::push_entry(pool_data, &mut pool, 1, TicketEntry{
+            owner: *player_info.key,
+            picks,
+            paid: price,
+            referral: share,
+            fee,
+            ..TicketEntry::default()
+        })?;
+        pool.fund = fund;
+        pool.fees = fees;
+        pool.pack_into_slice(pool_data);
+
+        log_event(&LotteryEvent::TicketBought{
+            player: *player_info.key,
+            price,
+        });
+        if let Some((referrer, amount)) = referral {
+            log_event(&LotteryEvent::ReferralPaid{referrer, amount});
+        }
+        if fee > 0 {
+            log_event(&LotteryEvent::FeeCollected{fee, fees});
+        }
+        log_event(&LotteryEvent::FundChanged{fund});
+        Ok(())
+    }
+
+    /// Processes a [SetFee](enum.Instruction.html).
+    pub fn process_set_fee(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        fee_bps: u16,
+        prize_fee_bps: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let treasury_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        if fee_bps as u64 > BPS_DENOMINATOR ||
+            prize_fee_bps as u64 > BPS_DENOMINATOR {
+            return Err(LotteryError::InvaliedFee.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_role(&pool, accounts, Role::Treasurer)?;
+        pool.fee_bps = fee_bps;
+        pool.prize_fee_bps = prize_fee_bps;
+        pool.treasury = *treasury_info.key;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [WithdrawFees](enum.Instruction.html).
+    pub fn process_withdraw_fees(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let treasury_info= next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_role(&pool, accounts, Role::Treasurer)?;
+        if pool.treasury != *treasury_info.key {
+            return Err(LotteryError::InvaliedFee.into());
+        }
+        let amount = pool.fees;
+        if amount == 0 {
+            return Err(LotteryError::InsufficentFunds.into());
+        }
+        Self::withdraw(
+            program_id,
+            &pool,
+            pool_info,
+            treasury_info,
+            rent,
+            account_info_iter.as_slice(),
+            amount,
+        )?;
+        pool.fees = 0;
+        pool.pack_into_slice(pool_data);
+
+        log_event(&LotteryEvent::FeesWithdrawn{
+            treasury: *treasury_info.key,
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Tickets held by the first `count` entries of a pool account.
+    fn total_tickets(pool_data: &[u8], count: u16) -> Result<u64, ProgramError> {
+        if count == 0 {
+            return Ok(0);
+        }
+        let last = TicketEntry::unpack_from_slice(&pool_data[LotteryState::entry_offset(count as usize-1)..])?;
+        Ok(last.cumulative)
+    }
+
+    /// Appends `entry` holding `tickets` tickets to a pool account, its
+    /// cumulative count following the entries before it.
+    fn push_entry(
+        pool_data: &mut [u8],
+        pool: &mut LotteryState,
+        tickets: u64,
+        mut entry: TicketEntry,
+    ) -> ProgramResult {
+        let count = pool.player_count;
+        if count as usize >= MAX_PLAYER {
+            return Err(LotteryError::TooManyPlayers.into());
+        }
+        entry.cumulative = Self::total_tickets(pool_data, count)?
+            .checked_add(tickets)
+            .ok_or(LotteryError::Overflow)?;
+        entry.pack_into_slice(&mut pool_data[LotteryState::entry_offset(count as usize)..]);
+        pool.player_count = count+1;
+        if entry.paid > 0 {
+            pool.paid_entries += 1;
+        }
+        Ok(())
+    }
+
+    /// Loads the record of `player_info` in `pool_info`, creating it on first sight.
+    ///
+    /// The player pays the rent of a new record.
+    fn load_player_record<'a>(
+        program_id: &Pubkey,
+        pool_info: &AccountInfo<'a>,
+        player_info: &AccountInfo<'a>,
+        record_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        clock: &Clock,
+        rent: &Rent,
+    ) -> Result<PlayerState, ProgramError> {
+        let (address, bump) = PlayerState::find_address(program_id, pool_info.key, player_info.key);
+        if address != *record_info.key {
+            return Err(LotteryError::InvalidPermission.into());
+        }
+        if record_info.owner == program_id {
+            return PlayerState::unpack(&record_info.data.borrow());
+        }
+
+        // allocate and assign instead of create_account, which fails if
+        // someone already sent lamports to the address
+        let required = rent
+            .minimum_balance(PlayerState::LEN)
+            .saturating_sub(record_info.lamports());
+        if required > 0 {
+            invoke(
+                &system_instruction::transfer(player_info.key, record_info.key, required),
+                &[
+                    player_info.clone(),
+                    record_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        let seeds: &[&[u8]] = &[PLAYER_SEED, pool_info.key.as_ref(), player_info.key.as_ref(), &[bump]];
+        invoke_signed(
+            &system_instruction::allocate(record_info.key, PlayerState::LEN as u64),
+            &[record_info.clone(), system_program_info.clone()],
+            &[seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(record_info.key, program_id),
+            &[record_info.clone(), system_program_info.clone()],
+            &[seeds],
+        )?;
+
+        let record = PlayerState{
+            is_initialized: true,
+            first_seen_slot: clock.slot,
+            ..PlayerState::default()
+        };
+        PlayerState::pack(record.clone(), &mut record_info.data.borrow_mut())?;
+        Ok(record)
+    }
+
+    /// Processes a [Register](enum.Instruction.html).
+    ///
+    /// Starts the account age of the player, does nothing if already registered.
+    pub fn process_register(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+        let record_info= next_account_info(account_info_iter)?;
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+        let system_program_info= next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(clock_sysvar_info)?;
+        let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+        if pool_info.owner != program_id ||
+            !account_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        }
+
+        let record = Self::load_player_record(
+            program_id,
+            pool_info,
+            account_info,
+            record_info,
+            system_program_info,
+            clock,
+            rent,
+        )?;
+        log_info(&format!("{} first seen at slot {}", account_info.key, record.first_seen_slot));
+        Ok(())
+    }
+
+    /// Processes a [SetSignInRule](enum.Instruction.html).
+    pub fn process_set_signin_rule(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        min_balance: u64,
+        min_age_slots: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        pool.min_balance = min_balance;
+        pool.min_age_slots = min_age_slots;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [SetTimezone](enum.Instruction.html).
+    pub fn process_set_timezone(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        utc_offset: i32,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        if !(-MAX_UTC_OFFSET..=MAX_UTC_OFFSET).contains(&utc_offset) {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        pool.utc_offset = utc_offset;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [SetStreakSchedule](enum.Instruction.html).
+    pub fn process_set_streak_schedule(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        tiers: [StreakTier; STREAK_TIERS],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+        // a single sign in can not take over the round
+        if tiers.iter().any(|tier| tier.bonus as usize >= MAX_PLAYER) {
+            return Err(LotteryError::InvalidInstruction.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        pool.streak_schedule = tiers;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [SetPurchaseCap](enum.Instruction.html).
+    pub fn process_set_purchase_cap(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_tickets_per_round: u32,
+        max_daily_spend: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_admins(&pool, accounts)?;
+        pool.max_tickets_per_round = max_tickets_per_round;
+        pool.max_daily_spend = max_daily_spend;
+        pool.pack_into_slice(pool_data);
+        Ok(())
+    }
+
+    /// Processes a [Pause](enum.Instruction.html) or an [Unpause](enum.Instruction.html).
+    ///
+    /// Only sign in , buy and roll stop, winners can still be rewarded.
+    pub fn process_set_paused(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        paused: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info= next_account_info(account_info_iter)?;
+        let pool_info= next_account_info(account_info_iter)?;
+
+        //check permission first
+        if pool_info.owner != program_id ||
+            !admin_info.is_signer{
+            return Err(LotteryError::InvalidPermission.into());
+        } 
+
+        // check account's data length
+        if pool_info.data_len() != LOTTERY_STATE_LEN {
+            return Err(LotteryError::InvalidAccountLength.into());
+        }
+
+        let pool_data = &mut pool_info.data.borrow_mut();
+        let mut pool = LotteryState::unpack_from_slice(pool_data)?;
+        Self::check_role(&pool, accounts, Role::Pauser)?;
+        pool.paused = paused;
+        pool.pack_into_slice(pool_data);
+
+        log_event(&LotteryEvent::PauseChanged{paused});
         Ok(())
     }
- 
 }
@@ -2,20 +2,1166 @@
 
 
 use arrayref::{array_mut_ref, array_ref};
-//use num_enum::TryFromPrimitive;
+use num_enum::TryFromPrimitive;
 use solana_program::{
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
-    clock::UnixTimestamp,
+    clock::{Slot, UnixTimestamp},
 };
 
 
-/// max player count
+/// max ticket entry count of a round
 pub const MAX_PLAYER: usize = 10000;
-/// LotteryState data lenght.
-pub const LOTTERY_STATE_LEN: usize = 8+32+2+32*MAX_PLAYER;
+/// LotteryState data lenght, the header followed by the ticket entries.
+pub const LOTTERY_STATE_LEN: usize = LotteryState::LEN+TicketEntry::LEN*MAX_PLAYER;
+/// max award bill count in billboard
+pub const MAX_AWARD: usize = 1000;
+/// max sponsor bill count in billboard
+pub const MAX_SPONSOR: usize = 1000;
+/// basis points of 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+/// seconds of a day
+pub const SECONDS_PER_DAY: i64 = 86_400;
+/// max offset of a pool's timezone from UTC , unit seconds
+pub const MAX_UTC_OFFSET: i32 = 14*3600;
+/// count of sign in streak bonus tiers
+pub const STREAK_TIERS: usize = 4;
+/// seed of the player record address, followed by the pool and the player
+pub const PLAYER_SEED: &[u8] = b"player";
+/// seed of a pool address, followed by the campaign id
+pub const POOL_SEED: &[u8] = b"pool";
+/// seed of the authority owning a pool's token vault, followed by the pool
+pub const VAULT_SEED: &[u8] = b"vault";
+/// max signers of a pool's admin set
+pub const MAX_ADMINS: usize = 5;
+/// seed of a pool's raffle address, followed by the pool
+pub const RAFFLE_SEED: &[u8] = b"raffle";
+/// max prizes escrowed by a raffle
+pub const MAX_RAFFLE_PRIZES: usize = 10;
+/// max numbers picked by a lotto ticket
+pub const MAX_LOTTO_PICKS: usize = 8;
+/// max number a lotto ticket can pick , numbers are kept as bits of a u64
+pub const MAX_LOTTO_RANGE: u8 = 64;
+/// max tiers of a scratch card odds table
+pub const MAX_SCRATCH_TIERS: usize = 4;
+/// max scratch cards bought at once
+pub const MAX_SCRATCH_CARDS: u16 = 16;
+/// resolution of scratch card odds , a card draws one of this many outcomes
+pub const SCRATCH_SCALE: u64 = 1_000_000;
 
+/// LotteryState data, the header of the pool account.
+///
+/// The header is followed by `player_count` ticket entries, see
+/// [unpack_entries](struct.LotteryState.html#method.unpack_entries).
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LotteryState {
+    /// fund of current round
+    pub fund: u64,
+    /// billboard's account
+    pub billboard: Pubkey,
+    /// growth of the fund per rollover , unit basis points
+    pub rollover_bps: u16,
+    /// max fund reached by rollover growth , 0 for no cap
+    pub rollover_cap: u64,
+    /// seconds a winner has to claim the award , 0 for never expire
+    pub claim_window: i64,
+    /// price of a ticket , unit lamports , 0 for no ticket sales
+    pub price: u64,
+    /// protocol fee taken from ticket sales , unit basis points
+    pub fee_bps: u16,
+    /// protocol fee taken from awards , unit basis points
+    pub prize_fee_bps: u16,
+    /// treasury's account , receiver of the withdrawn fees
+    pub treasury: Pubkey,
+    /// fees collected in the pool and not withdrawn yet , unit lamports
+    pub fees: u64,
+    /// lamports a player must hold to sign in , 0 for no limit
+    pub min_balance: u64,
+    /// slots since a player was first seen before signing in , 0 for no limit
+    pub min_age_slots: u64,
+    /// offset of the pool's timezone from UTC , unit seconds
+    pub utc_offset: i32,
+    /// extra tickets for sign in streaks
+    pub streak_schedule: [StreakTier; STREAK_TIERS],
+    /// rounds rolled so far , index of the current round
+    pub round: u32,
+    /// tickets a player can buy in a round , 0 for no limit
+    pub max_tickets_per_round: u32,
+    /// lamports a player can spend on tickets in a day , 0 for no limit
+    pub max_daily_spend: u64,
+    /// whether sign in , buy and roll are stopped
+    pub paused: bool,
+    /// seconds a GM change waits before it can be applied
+    pub gm_delay: i64,
+    /// fund of the pending GM change
+    pub pending_fund: u64,
+    /// price of the pending GM change
+    pub pending_price: u64,
+    /// timestamp the pending GM change can be applied at , 0 for none pending
+    pub pending_at: UnixTimestamp,
+    /// admin signers needed for privileged instructions , 0 for the program's admin key
+    pub admin_threshold: u8,
+    /// admin signers , default key for unused
+    pub admins: [Pubkey; MAX_ADMINS],
+    /// key allowed to roll , default key for the admins only
+    pub roller: Pubkey,
+    /// key allowed to move funds and change prizes , default key for the admins only
+    pub treasurer: Pubkey,
+    /// key allowed to pause , default key for the admins only
+    pub pauser: Pubkey,
+    /// campaign the pool address is derived from
+    pub campaign_id: u64,
+    /// seconds between two rolls , 0 for no limit
+    pub roll_interval: i64,
+    /// timestamp of the last roll
+    pub last_roll: UnixTimestamp,
+    /// SPL mint the pool is denominated in , default key for lamports
+    pub mint: Pubkey,
+    /// token account holding the pool's tokens , owned by the vault authority
+    pub vault: Pubkey,
+    /// whether the next roll draws the prizes of the pool's raffle
+    pub raffle: bool,
+    /// numbers a lotto ticket picks , 0 for drawing one ticket
+    pub lotto_picks: u8,
+    /// lotto tickets pick numbers from 1 to `lotto_range`
+    pub lotto_range: u8,
+    /// share of the fund for tickets matching `i+1` numbers , unit basis points
+    pub lotto_tier_bps: [u16; MAX_LOTTO_PICKS],
+    /// numbers drawn by the roll being settled , bit `n-1` for `n` , 0 when none
+    pub lotto_drawn: u64,
+    /// fund of the roll being settled
+    pub lotto_fund: u64,
+    /// ticket entries settled in the current pass
+    pub lotto_cursor: u16,
+    /// whether the settlement pays winners , after counting them
+    pub lotto_paying: bool,
+    /// winning tickets of each tier , counted by the first pass
+    pub lotto_winners: [u16; MAX_LOTTO_PICKS],
+    /// odds table of the instant win scratch cards sold instead of tickets , unused tiers have 0 odds
+    pub scratch_tiers: [ScratchTier; MAX_SCRATCH_TIERS],
+    /// most the scratch cards not revealed yet can win , backed by the fund
+    pub scratch_liability: u64,
+    /// whether the round was cancelled , its tickets refunded instead of drawn
+    pub cancelled: bool,
+    /// entries of current round paid for and not refunded
+    pub paid_entries: u16,
+    /// players a round needs to be drawn , 0 for no minimum
+    pub min_players: u16,
+    /// tickets a round needs to be drawn , 0 for no minimum
+    pub min_tickets: u64,
+    /// seconds a round under the minimum is extended by , 0 to cancel it instead
+    pub threshold_extension: i64,
+    /// the current round can not roll before , after being extended
+    pub extended_until: UnixTimestamp,
+    /// times the current round was extended
+    pub round_extensions: u8,
+    /// distinct players holding tickets of current round
+    pub round_players: u16,
+    /// bonus tickets a referrer earns for each player referred
+    pub referral_tickets: u16,
+    /// most bonus tickets a referrer earns , 0 for no limit
+    pub referral_ticket_cap: u32,
+    /// share of a referred player's purchases paid to the referrer , unit basis points
+    pub referral_bps: u16,
+    /// most a referrer earns from purchases , 0 for no limit
+    pub referral_cap: u64,
+    /// times a round under the minimum is extended before it is cancelled
+    pub max_extensions: u8,
+    /// slot whose hash draws the lotto numbers , set when Roll closes sales , 0 while open
+    pub lotto_slot: Slot,
+    /// ticket entries of current round
+    pub player_count: u16,
+}
+
+impl LotteryState {
+    /// Offset of the ticket entry `index` in a pool account.
+    pub fn entry_offset(index: usize) -> usize {
+        Self::LEN+index*TicketEntry::LEN
+    }
+
+    /// Unpacks the ticket entries following the header of a pool account.
+    pub fn unpack_entries(src: &[u8]) -> Result<Vec<TicketEntry>, ProgramError> {
+        let state = Self::unpack_from_slice(src)?;
+        let count = state.player_count as usize;
+        if count > MAX_PLAYER || src.len() < Self::entry_offset(count) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        (0..count)
+            .map(|i| TicketEntry::unpack_from_slice(&src[Self::entry_offset(i)..]))
+            .collect()
+    }
+
+    /// Fund of the next round when the current one rolls over.
+    ///
+    /// The fund grows by `rollover_bps`, without going above `rollover_cap`
+    /// unless it was already above it. The growth is paid out of the collected
+    /// fees , so it never grows by more than the fees held in the pool.
+    pub fn rollover_fund(&self) -> u64 {
+        let grown = (self.fund as u128) * (BPS_DENOMINATOR + self.rollover_bps as u64) as u128
+            / BPS_DENOMINATOR as u128;
+        let mut grown = if grown > u64::MAX as u128 { u64::MAX } else { grown as u64 };
+        if self.rollover_cap != 0 && grown > self.rollover_cap {
+            grown = self.rollover_cap;
+        }
+        grown.min(self.fund.saturating_add(self.fees)).max(self.fund)
+    }
+
+    /// Carries the fund over to the next round , moving its growth out of the fees.
+    pub fn roll_over(&mut self) -> u64 {
+        let next_fund = self.rollover_fund();
+        self.fees -= next_fund - self.fund;
+        self.fund = next_fund;
+        next_fund
+    }
+
+    /// Splits `amount` into the part kept by the pool and the protocol fee of `bps`.
+    pub fn split_fee(amount: u64, bps: u16) -> (u64, u64) {
+        let fee = (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        (amount - fee, fee)
+    }
+
+    /// Calendar day of `timestamp` in the pool's timezone, counted from 1970-01-01.
+    pub fn day_of(&self, timestamp: UnixTimestamp) -> i64 {
+        timestamp
+            .saturating_add(self.utc_offset as i64)
+            .div_euclid(SECONDS_PER_DAY)
+    }
+
+    /// Extra tickets of a sign in on day `streak` of a streak.
+    ///
+    /// The best tier reached applies, tiers with 0 days are unused.
+    pub fn streak_bonus(&self, streak: u32) -> u16 {
+        self.streak_schedule
+            .iter()
+            .filter(|tier| tier.days > 0 && streak >= tier.days as u32)
+            .map(|tier| tier.bonus)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Bonus tickets a referral earns a referrer who earned `earned` of them so far.
+    pub fn referral_bonus(&self, earned: u32) -> u16 {
+        if self.referral_ticket_cap == 0 {
+            return self.referral_tickets;
+        }
+        let left = self.referral_ticket_cap.saturating_sub(earned);
+        left.min(self.referral_tickets as u32) as u16
+    }
+
+    /// Share of a purchase at `price` paid to a referrer who earned `earned` so far.
+    pub fn referral_share(&self, price: u64, earned: u64) -> u64 {
+        let share = (price as u128 * self.referral_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        if self.referral_cap == 0 {
+            return share;
+        }
+        share.min(self.referral_cap.saturating_sub(earned))
+    }
+
+    /// Deadline to claim an award drawn at `timestamp` , 0 for never expire.
+    pub fn claim_deadline(&self, timestamp: UnixTimestamp) -> UnixTimestamp {
+        if self.claim_window > 0 {
+            timestamp.saturating_add(self.claim_window)
+        } else {
+            0
+        }
+    }
+
+    /// Finds the pool address of `campaign_id`.
+    pub fn find_address(program_id: &Pubkey, campaign_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[POOL_SEED, &campaign_id.to_le_bytes()], program_id)
+    }
+
+    /// Finds the authority owning the token vault of `pool`.
+    pub fn find_vault_authority(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[VAULT_SEED, pool.as_ref()], program_id)
+    }
+
+    /// Whether the pool is denominated in an SPL mint rather than lamports.
+    pub fn is_token_pool(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+
+    /// Whether the schedule lets the pool roll at `now`, and any extension
+    /// of the current round has run out.
+    pub fn can_roll(&self, now: UnixTimestamp) -> bool {
+        (self.roll_interval == 0 || now >= self.last_roll.saturating_add(self.roll_interval)) &&
+            now >= self.extended_until
+    }
+
+    /// Whether a round of `tickets` tickets reaches the minimum to be drawn.
+    pub fn meets_threshold(&self, tickets: u64) -> bool {
+        self.round_players >= self.min_players && tickets >= self.min_tickets
+    }
+
+    /// Whether tickets pick numbers and rolls draw a winning set.
+    pub fn is_lotto(&self) -> bool {
+        self.lotto_picks > 0
+    }
+
+    /// Whether a lotto roll closed sales and its settlement is not over yet.
+    pub fn lotto_pending(&self) -> bool {
+        self.lotto_slot != 0 || self.lotto_drawn != 0
+    }
+
+    /// Whether the pool sells instant win scratch cards instead of tickets.
+    pub fn is_scratch(&self) -> bool {
+        self.scratch_tiers.iter().any(|tier| tier.odds > 0)
+    }
+
+    /// Most a scratch card bought at `price` can win.
+    pub fn scratch_max_payout(&self, price: u64) -> Option<u64> {
+        let multiplier = self.scratch_tiers
+            .iter()
+            .map(|tier| tier.multiplier)
+            .max()
+            .unwrap_or(0);
+        price.checked_mul(multiplier as u64)
+    }
+
+    /// Picks of a lotto ticket choosing `numbers`, bit `n-1` for `n`.
+    ///
+    /// A ticket picks exactly `lotto_picks` distinct numbers from 1 to
+    /// `lotto_range`, unused slots are 0; anything else is `None`.
+    pub fn lotto_mask(&self, numbers: &[u8; MAX_LOTTO_PICKS]) -> Option<u64> {
+        let (picked, unused) = numbers.split_at(self.lotto_picks as usize);
+        if unused.iter().any(|n| *n != 0) {
+            return None;
+        }
+        let mut mask = 0u64;
+        for n in picked {
+            if *n == 0 || *n > self.lotto_range || mask & 1 << (n-1) != 0 {
+                return None;
+            }
+            mask |= 1 << (n-1);
+        }
+        Some(mask)
+    }
+
+    /// Prize tier of a ticket with `picks` against the drawn numbers, the
+    /// index of `lotto_tier_bps`; `None` when it wins nothing.
+    pub fn lotto_tier(&self, picks: u64) -> Option<usize> {
+        let matches = (picks & self.lotto_drawn).count_ones() as usize;
+        if matches == 0 || self.lotto_tier_bps[matches-1] == 0 {
+            return None;
+        }
+        Some(matches-1)
+    }
+
+    /// Award of each winning ticket of `tier`, its share of the settled fund
+    /// split between its winners.
+    pub fn lotto_share(&self, tier: usize) -> u64 {
+        let winners = self.lotto_winners[tier] as u128;
+        if winners == 0 {
+            return 0;
+        }
+        (self.lotto_fund as u128 * self.lotto_tier_bps[tier] as u128
+            / BPS_DENOMINATOR as u128 / winners) as u64
+    }
+
+    /// Key holding `role`, `None` for the owner , which is the admin set.
+    pub fn role_holder(&self, role: Role) -> Option<Pubkey> {
+        match role {
+            Role::Owner => None,
+            Role::Roller => Some(self.roller),
+            Role::Treasurer => Some(self.treasurer),
+            Role::Pauser => Some(self.pauser),
+        }
+    }
+
+    /// Count of configured admins among `signers`.
+    pub fn admin_approvals(&self, signers: &[Pubkey]) -> usize {
+        self.admins
+            .iter()
+            .filter(|admin| **admin != Pubkey::default() && signers.contains(admin))
+            .count()
+    }
+}
+
+impl Sealed for LotteryState {}
+impl IsInitialized for LotteryState {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+impl Pack for LotteryState {
+    const LEN: usize = 8+32+2+8+8+8+2+2+32+8+8+8+4+STREAK_TIERS*StreakTier::LEN+4+4+8+1+8+8+8+8+1+32*MAX_ADMINS+32*3+8+8+8+32+32+1+1+1+2*MAX_LOTTO_PICKS+8+8+2+1+2*MAX_LOTTO_PICKS+MAX_SCRATCH_TIERS*ScratchTier::LEN+8+1+2+2+8+8+8+1+2+2+4+2+8+1+8+2;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let fund_buf = array_ref![src, 0, 8];
+        let fund = u64::from_le_bytes(*fund_buf);
+        let billboard_buf = array_ref![src, 8, 32];
+        let billboard = Pubkey::new_from_array(*billboard_buf);
+        let rollover_bps_buf = array_ref![src, 40, 2];
+        let rollover_bps = u16::from_le_bytes(*rollover_bps_buf);
+        let rollover_cap_buf = array_ref![src, 42, 8];
+        let rollover_cap = u64::from_le_bytes(*rollover_cap_buf);
+        let claim_window_buf = array_ref![src, 50, 8];
+        let claim_window = i64::from_le_bytes(*claim_window_buf);
+        let price_buf = array_ref![src, 58, 8];
+        let price = u64::from_le_bytes(*price_buf);
+        let fee_bps_buf = array_ref![src, 66, 2];
+        let fee_bps = u16::from_le_bytes(*fee_bps_buf);
+        let prize_fee_bps_buf = array_ref![src, 68, 2];
+        let prize_fee_bps = u16::from_le_bytes(*prize_fee_bps_buf);
+        let treasury_buf = array_ref![src, 70, 32];
+        let treasury = Pubkey::new_from_array(*treasury_buf);
+        let fees_buf = array_ref![src, 102, 8];
+        let fees = u64::from_le_bytes(*fees_buf);
+        let min_balance_buf = array_ref![src, 110, 8];
+        let min_balance = u64::from_le_bytes(*min_balance_buf);
+        let min_age_slots_buf = array_ref![src, 118, 8];
+        let min_age_slots = u64::from_le_bytes(*min_age_slots_buf);
+        let utc_offset_buf = array_ref![src, 126, 4];
+        let utc_offset = i32::from_le_bytes(*utc_offset_buf);
+        let mut streak_schedule = [StreakTier::default(); STREAK_TIERS];
+        for (i, tier) in streak_schedule.iter_mut().enumerate() {
+            *tier = StreakTier::unpack_from_slice(&src[130+i*StreakTier::LEN..])?;
+        }
+        let round_buf = array_ref![src, 146, 4];
+        let round = u32::from_le_bytes(*round_buf);
+        let max_tickets_per_round_buf = array_ref![src, 150, 4];
+        let max_tickets_per_round = u32::from_le_bytes(*max_tickets_per_round_buf);
+        let max_daily_spend_buf = array_ref![src, 154, 8];
+        let max_daily_spend = u64::from_le_bytes(*max_daily_spend_buf);
+        let paused = match src[162] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let gm_delay_buf = array_ref![src, 163, 8];
+        let gm_delay = i64::from_le_bytes(*gm_delay_buf);
+        let pending_fund_buf = array_ref![src, 171, 8];
+        let pending_fund = u64::from_le_bytes(*pending_fund_buf);
+        let pending_price_buf = array_ref![src, 179, 8];
+        let pending_price = u64::from_le_bytes(*pending_price_buf);
+        let pending_at_buf = array_ref![src, 187, 8];
+        let pending_at = UnixTimestamp::from_le_bytes(*pending_at_buf);
+        let admin_threshold = src[195];
+        let mut admins = [Pubkey::default(); MAX_ADMINS];
+        for (i, admin) in admins.iter_mut().enumerate() {
+            *admin = Pubkey::new_from_array(*array_ref![src, 196+i*32, 32]);
+        }
+        let roller = Pubkey::new_from_array(*array_ref![src, 356, 32]);
+        let treasurer = Pubkey::new_from_array(*array_ref![src, 388, 32]);
+        let pauser = Pubkey::new_from_array(*array_ref![src, 420, 32]);
+        let campaign_id = u64::from_le_bytes(*array_ref![src, 452, 8]);
+        let roll_interval = i64::from_le_bytes(*array_ref![src, 460, 8]);
+        let last_roll = UnixTimestamp::from_le_bytes(*array_ref![src, 468, 8]);
+        let mint = Pubkey::new_from_array(*array_ref![src, 476, 32]);
+        let vault = Pubkey::new_from_array(*array_ref![src, 508, 32]);
+        let raffle = match src[540] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let lotto_picks = src[541];
+        let lotto_range = src[542];
+        let mut lotto_tier_bps = [0u16; MAX_LOTTO_PICKS];
+        for (i, bps) in lotto_tier_bps.iter_mut().enumerate() {
+            *bps = u16::from_le_bytes(*array_ref![src, 543+i*2, 2]);
+        }
+        let lotto_drawn = u64::from_le_bytes(*array_ref![src, 559, 8]);
+        let lotto_fund = u64::from_le_bytes(*array_ref![src, 567, 8]);
+        let lotto_cursor = u16::from_le_bytes(*array_ref![src, 575, 2]);
+        let lotto_paying = match src[577] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let mut lotto_winners = [0u16; MAX_LOTTO_PICKS];
+        for (i, winners) in lotto_winners.iter_mut().enumerate() {
+            *winners = u16::from_le_bytes(*array_ref![src, 578+i*2, 2]);
+        }
+        let mut scratch_tiers = [ScratchTier::default(); MAX_SCRATCH_TIERS];
+        for (i, tier) in scratch_tiers.iter_mut().enumerate() {
+            *tier = ScratchTier::unpack_from_slice(&src[594+i*ScratchTier::LEN..])?;
+        }
+        let scratch_liability = u64::from_le_bytes(*array_ref![src, 626, 8]);
+        let cancelled = match src[634] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let paid_entries = u16::from_le_bytes(*array_ref![src, 635, 2]);
+        let min_players = u16::from_le_bytes(*array_ref![src, 637, 2]);
+        let min_tickets = u64::from_le_bytes(*array_ref![src, 639, 8]);
+        let threshold_extension = i64::from_le_bytes(*array_ref![src, 647, 8]);
+        let extended_until = UnixTimestamp::from_le_bytes(*array_ref![src, 655, 8]);
+        let round_extensions = src[663];
+        let round_players = u16::from_le_bytes(*array_ref![src, 664, 2]);
+        let referral_tickets = u16::from_le_bytes(*array_ref![src, 666, 2]);
+        let referral_ticket_cap = u32::from_le_bytes(*array_ref![src, 668, 4]);
+        let referral_bps = u16::from_le_bytes(*array_ref![src, 672, 2]);
+        let referral_cap = u64::from_le_bytes(*array_ref![src, 674, 8]);
+        let max_extensions = src[682];
+        let lotto_slot = Slot::from_le_bytes(*array_ref![src, 683, 8]);
+        let count_buf = array_ref![src, 691, 2];
+        let player_count = u16::from_le_bytes(*count_buf);
+
+        Ok(LotteryState {
+            fund,
+            billboard,
+            rollover_bps,
+            rollover_cap,
+            claim_window,
+            price,
+            fee_bps,
+            prize_fee_bps,
+            treasury,
+            fees,
+            min_balance,
+            min_age_slots,
+            utc_offset,
+            streak_schedule,
+            round,
+            max_tickets_per_round,
+            max_daily_spend,
+            paused,
+            gm_delay,
+            pending_fund,
+            pending_price,
+            pending_at,
+            admin_threshold,
+            admins,
+            roller,
+            treasurer,
+            pauser,
+            campaign_id,
+            roll_interval,
+            last_roll,
+            mint,
+            vault,
+            raffle,
+            lotto_picks,
+            lotto_range,
+            lotto_tier_bps,
+            lotto_drawn,
+            lotto_fund,
+            lotto_cursor,
+            lotto_paying,
+            lotto_winners,
+            scratch_tiers,
+            scratch_liability,
+            cancelled,
+            paid_entries,
+            min_players,
+            min_tickets,
+            threshold_extension,
+            extended_until,
+            round_extensions,
+            round_players,
+            referral_tickets,
+            referral_ticket_cap,
+            referral_bps,
+            referral_cap,
+            max_extensions,
+            lotto_slot,
+            player_count,
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let fund_buf = array_mut_ref![dst, 0, 8];
+        fund_buf.copy_from_slice(&self.fund.to_le_bytes());
+        let billboard_buf = array_mut_ref![dst, 8, 32];
+        billboard_buf.copy_from_slice(self.billboard.as_ref());
+        let rollover_bps_buf = array_mut_ref![dst, 40, 2];
+        rollover_bps_buf.copy_from_slice(&self.rollover_bps.to_le_bytes());
+        let rollover_cap_buf = array_mut_ref![dst, 42, 8];
+        rollover_cap_buf.copy_from_slice(&self.rollover_cap.to_le_bytes());
+        let claim_window_buf = array_mut_ref![dst, 50, 8];
+        claim_window_buf.copy_from_slice(&self.claim_window.to_le_bytes());
+        let price_buf = array_mut_ref![dst, 58, 8];
+        price_buf.copy_from_slice(&self.price.to_le_bytes());
+        let fee_bps_buf = array_mut_ref![dst, 66, 2];
+        fee_bps_buf.copy_from_slice(&self.fee_bps.to_le_bytes());
+        let prize_fee_bps_buf = array_mut_ref![dst, 68, 2];
+        prize_fee_bps_buf.copy_from_slice(&self.prize_fee_bps.to_le_bytes());
+        let treasury_buf = array_mut_ref![dst, 70, 32];
+        treasury_buf.copy_from_slice(self.treasury.as_ref());
+        let fees_buf = array_mut_ref![dst, 102, 8];
+        fees_buf.copy_from_slice(&self.fees.to_le_bytes());
+        let min_balance_buf = array_mut_ref![dst, 110, 8];
+        min_balance_buf.copy_from_slice(&self.min_balance.to_le_bytes());
+        let min_age_slots_buf = array_mut_ref![dst, 118, 8];
+        min_age_slots_buf.copy_from_slice(&self.min_age_slots.to_le_bytes());
+        let utc_offset_buf = array_mut_ref![dst, 126, 4];
+        utc_offset_buf.copy_from_slice(&self.utc_offset.to_le_bytes());
+        for (i, tier) in self.streak_schedule.iter().enumerate() {
+            tier.pack_into_slice(&mut dst[130+i*StreakTier::LEN..]);
+        }
+        let round_buf = array_mut_ref![dst, 146, 4];
+        round_buf.copy_from_slice(&self.round.to_le_bytes());
+        let max_tickets_per_round_buf = array_mut_ref![dst, 150, 4];
+        max_tickets_per_round_buf.copy_from_slice(&self.max_tickets_per_round.to_le_bytes());
+        let max_daily_spend_buf = array_mut_ref![dst, 154, 8];
+        max_daily_spend_buf.copy_from_slice(&self.max_daily_spend.to_le_bytes());
+        dst[162] = self.paused as u8;
+        let gm_delay_buf = array_mut_ref![dst, 163, 8];
+        gm_delay_buf.copy_from_slice(&self.gm_delay.to_le_bytes());
+        let pending_fund_buf = array_mut_ref![dst, 171, 8];
+        pending_fund_buf.copy_from_slice(&self.pending_fund.to_le_bytes());
+        let pending_price_buf = array_mut_ref![dst, 179, 8];
+        pending_price_buf.copy_from_slice(&self.pending_price.to_le_bytes());
+        let pending_at_buf = array_mut_ref![dst, 187, 8];
+        pending_at_buf.copy_from_slice(&self.pending_at.to_le_bytes());
+        dst[195] = self.admin_threshold;
+        for (i, admin) in self.admins.iter().enumerate() {
+            array_mut_ref![dst, 196+i*32, 32].copy_from_slice(admin.as_ref());
+        }
+        array_mut_ref![dst, 356, 32].copy_from_slice(self.roller.as_ref());
+        array_mut_ref![dst, 388, 32].copy_from_slice(self.treasurer.as_ref());
+        array_mut_ref![dst, 420, 32].copy_from_slice(self.pauser.as_ref());
+        array_mut_ref![dst, 452, 8].copy_from_slice(&self.campaign_id.to_le_bytes());
+        array_mut_ref![dst, 460, 8].copy_from_slice(&self.roll_interval.to_le_bytes());
+        array_mut_ref![dst, 468, 8].copy_from_slice(&self.last_roll.to_le_bytes());
+        array_mut_ref![dst, 476, 32].copy_from_slice(self.mint.as_ref());
+        array_mut_ref![dst, 508, 32].copy_from_slice(self.vault.as_ref());
+        dst[540] = self.raffle as u8;
+        dst[541] = self.lotto_picks;
+        dst[542] = self.lotto_range;
+        for (i, bps) in self.lotto_tier_bps.iter().enumerate() {
+            array_mut_ref![dst, 543+i*2, 2].copy_from_slice(&bps.to_le_bytes());
+        }
+        array_mut_ref![dst, 559, 8].copy_from_slice(&self.lotto_drawn.to_le_bytes());
+        array_mut_ref![dst, 567, 8].copy_from_slice(&self.lotto_fund.to_le_bytes());
+        array_mut_ref![dst, 575, 2].copy_from_slice(&self.lotto_cursor.to_le_bytes());
+        dst[577] = self.lotto_paying as u8;
+        for (i, winners) in self.lotto_winners.iter().enumerate() {
+            array_mut_ref![dst, 578+i*2, 2].copy_from_slice(&winners.to_le_bytes());
+        }
+        for (i, tier) in self.scratch_tiers.iter().enumerate() {
+            tier.pack_into_slice(&mut dst[594+i*ScratchTier::LEN..]);
+        }
+        array_mut_ref![dst, 626, 8].copy_from_slice(&self.scratch_liability.to_le_bytes());
+        dst[634] = self.cancelled as u8;
+        array_mut_ref![dst, 635, 2].copy_from_slice(&self.paid_entries.to_le_bytes());
+        array_mut_ref![dst, 637, 2].copy_from_slice(&self.min_players.to_le_bytes());
+        array_mut_ref![dst, 639, 8].copy_from_slice(&self.min_tickets.to_le_bytes());
+        array_mut_ref![dst, 647, 8].copy_from_slice(&self.threshold_extension.to_le_bytes());
+        array_mut_ref![dst, 655, 8].copy_from_slice(&self.extended_until.to_le_bytes());
+        dst[663] = self.round_extensions;
+        array_mut_ref![dst, 664, 2].copy_from_slice(&self.round_players.to_le_bytes());
+        array_mut_ref![dst, 666, 2].copy_from_slice(&self.referral_tickets.to_le_bytes());
+        array_mut_ref![dst, 668, 4].copy_from_slice(&self.referral_ticket_cap.to_le_bytes());
+        array_mut_ref![dst, 672, 2].copy_from_slice(&self.referral_bps.to_le_bytes());
+        array_mut_ref![dst, 674, 8].copy_from_slice(&self.referral_cap.to_le_bytes());
+        dst[682] = self.max_extensions;
+        array_mut_ref![dst, 683, 8].copy_from_slice(&self.lotto_slot.to_le_bytes());
+        let count_buf = array_mut_ref![dst, 691, 2];
+        count_buf.copy_from_slice(&self.player_count.to_le_bytes());
+    }
+}
+
+/// TicketEntry, tickets got by one sign in or purchase
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TicketEntry {
+    /// player's account
+    pub owner: Pubkey,
+    /// tickets of this entry and all the entries before it
+    pub cumulative: u64,
+    /// numbers picked by a lotto ticket , bit `n-1` for `n` , 0 outside lotto pools
+    pub picks: u64,
+    /// price paid for the entry , 0 for sign in tickets
+    pub paid: u64,
+    /// whether the price was refunded by a cancelled round
+    pub refunded: bool,
+    /// share of the price paid to the player's referrer
+    pub referral: u64,
+    /// share of the price taken into the fees at purchase
+    pub fee: u64,
+}
+
+impl TicketEntry {
+    /// packed length of an entry
+    pub const LEN: usize = 32+8+8+8+1+8+8;
+
+    /// Unpacks an entry from the start of `src`.
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let owner_buf = array_ref![src, 0, 32];
+        let cumulative_buf = array_ref![src, 32, 8];
+        let picks_buf = array_ref![src, 40, 8];
+        let paid_buf = array_ref![src, 48, 8];
+        let refunded = match src[56] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let referral_buf = array_ref![src, 57, 8];
+        let fee_buf = array_ref![src, 65, 8];
+        Ok(TicketEntry {
+            owner: Pubkey::new_from_array(*owner_buf),
+            cumulative: u64::from_le_bytes(*cumulative_buf),
+            picks: u64::from_le_bytes(*picks_buf),
+            paid: u64::from_le_bytes(*paid_buf),
+            refunded,
+            referral: u64::from_le_bytes(*referral_buf),
+            fee: u64::from_le_bytes(*fee_buf),
+        })
+    }
+
+    /// Packs the entry into the start of `dst`.
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let owner_buf = array_mut_ref![dst, 0, 32];
+        owner_buf.copy_from_slice(self.owner.as_ref());
+        let cumulative_buf = array_mut_ref![dst, 32, 8];
+        cumulative_buf.copy_from_slice(&self.cumulative.to_le_bytes());
+        let picks_buf = array_mut_ref![dst, 40, 8];
+        picks_buf.copy_from_slice(&self.picks.to_le_bytes());
+        let paid_buf = array_mut_ref![dst, 48, 8];
+        paid_buf.copy_from_slice(&self.paid.to_le_bytes());
+        dst[56] = self.refunded as u8;
+        let referral_buf = array_mut_ref![dst, 57, 8];
+        referral_buf.copy_from_slice(&self.referral.to_le_bytes());
+        let fee_buf = array_mut_ref![dst, 65, 8];
+        fee_buf.copy_from_slice(&self.fee.to_le_bytes());
+    }
+}
+
+/// StreakTier, extra tickets from a sign in streak of `days`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StreakTier {
+    /// consecutive days to reach the tier , 0 for unused
+    pub days: u16,
+    /// extra tickets of each sign in within the tier
+    pub bonus: u16,
+}
+
+impl StreakTier {
+    /// packed length of a tier
+    pub const LEN: usize = 2+2;
+
+    /// Unpacks a tier from the start of `src`.
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let days_buf = array_ref![src, 0, 2];
+        let bonus_buf = array_ref![src, 2, 2];
+        Ok(StreakTier {
+            days: u16::from_le_bytes(*days_buf),
+            bonus: u16::from_le_bytes(*bonus_buf),
+        })
+    }
+
+    /// Packs the tier into the start of `dst`.
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let days_buf = array_mut_ref![dst, 0, 2];
+        days_buf.copy_from_slice(&self.days.to_le_bytes());
+        let bonus_buf = array_mut_ref![dst, 2, 2];
+        bonus_buf.copy_from_slice(&self.bonus.to_le_bytes());
+    }
+}
+
+/// ScratchTier, a row of the scratch card odds table
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScratchTier {
+    /// a card wins the tier 1 in `odds` , 0 for unused
+    pub odds: u32,
+    /// the tier pays the card's price times `multiplier`
+    pub multiplier: u32,
+}
+
+impl ScratchTier {
+    /// packed length of a tier
+    pub const LEN: usize = 4+4;
+
+    /// Outcomes out of [SCRATCH_SCALE] winning the tier.
+    pub fn chance(&self) -> u64 {
+        if self.odds == 0 {
+            return 0;
+        }
+        SCRATCH_SCALE / self.odds as u64
+    }
+
+    /// Unpacks a tier from the start of `src`.
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let odds_buf = array_ref![src, 0, 4];
+        let multiplier_buf = array_ref![src, 4, 4];
+        Ok(ScratchTier {
+            odds: u32::from_le_bytes(*odds_buf),
+            multiplier: u32::from_le_bytes(*multiplier_buf),
+        })
+    }
+
+    /// Packs the tier into the start of `dst`.
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let odds_buf = array_mut_ref![dst, 0, 4];
+        odds_buf.copy_from_slice(&self.odds.to_le_bytes());
+        let multiplier_buf = array_mut_ref![dst, 4, 4];
+        multiplier_buf.copy_from_slice(&self.multiplier.to_le_bytes());
+    }
+}
+
+/// PlayerState data, the record of a player in a pool.
+///
+/// Lives at the program address of [PLAYER_SEED], the pool and the player,
+/// see [find_address](struct.PlayerState.html#method.find_address).
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerState {
+    /// whether the record has been created
+    pub is_initialized: bool,
+    /// slot the player was first seen by the pool
+    pub first_seen_slot: Slot,
+    /// day of the last sign in , see [day_of](struct.LotteryState.html#method.day_of) , 0 before the first one
+    pub last_signin_day: i64,
+    /// consecutive days signed in until the last sign in
+    pub streak: u32,
+    /// round of `round_tickets`
+    pub round: u32,
+    /// tickets bought in `round`
+    pub round_tickets: u32,
+    /// day of `day_spent`
+    pub spend_day: i64,
+    /// lamports spent on tickets in `spend_day`
+    pub day_spent: u64,
+    /// instant winnings and referral rewards the player can claim
+    pub balance: u64,
+    /// slot the pending scratch cards were bought in , its hash reveals them
+    pub scratch_slot: Slot,
+    /// scratch cards bought and not revealed yet
+    pub scratch_cards: u16,
+    /// price paid for each pending scratch card
+    pub scratch_price: u64,
+    /// last round the player held tickets of , plus 1 , 0 before the first one
+    pub played_round: u32,
+    /// player who referred this one , default key for none
+    pub referrer: Pubkey,
+    /// players referred by this one
+    pub referrals: u32,
+    /// bonus tickets earned by referrals , added to the next sign in
+    pub bonus_tickets: u16,
+    /// bonus tickets earned by referrals in total
+    pub referral_tickets: u32,
+    /// earned from the purchases of referred players in total
+    pub referral_earned: u64,
+}
+
+impl PlayerState {
+    /// Tickets the player can still buy in the current round of `pool`, none for no limit.
+    pub fn tickets_left(&self, pool: &LotteryState) -> Option<u32> {
+        if pool.max_tickets_per_round == 0 {
+            return None;
+        }
+        let bought = if self.round == pool.round { self.round_tickets } else { 0 };
+        Some(pool.max_tickets_per_round.saturating_sub(bought))
+    }
+
+    /// Lamports the player can still spend on tickets on `today`, none for no limit.
+    pub fn spend_left(&self, pool: &LotteryState, today: i64) -> Option<u64> {
+        if pool.max_daily_spend == 0 {
+            return None;
+        }
+        let spent = if self.spend_day == today { self.day_spent } else { 0 };
+        Some(pool.max_daily_spend.saturating_sub(spent))
+    }
+
+    /// Marks the player as holding tickets of the current round of `pool`,
+    /// returns whether it is the first time this round.
+    pub fn join_round(&mut self, pool: &LotteryState) -> bool {
+        let round = pool.round.wrapping_add(1);
+        if self.played_round == round {
+            return false;
+        }
+        self.played_round = round;
+        true
+    }
+
+    /// Finds the record address of `player` in `pool`.
+    pub fn find_address(program_id: &Pubkey, pool: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PLAYER_SEED, pool.as_ref(), player.as_ref()], program_id)
+    }
+}
+
+impl Sealed for PlayerState {}
+impl IsInitialized for PlayerState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for PlayerState {
+    const LEN: usize = 1+8+8+4+4+4+8+8+8+8+2+8+4+32+4+2+4+8;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PlayerState::LEN];
+        let is_initialized = match src[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let first_seen_slot_buf = array_ref![src, 1, 8];
+        let first_seen_slot = Slot::from_le_bytes(*first_seen_slot_buf);
+        let last_signin_day_buf = array_ref![src, 9, 8];
+        let last_signin_day = i64::from_le_bytes(*last_signin_day_buf);
+        let streak_buf = array_ref![src, 17, 4];
+        let streak = u32::from_le_bytes(*streak_buf);
+        let round_buf = array_ref![src, 21, 4];
+        let round = u32::from_le_bytes(*round_buf);
+        let round_tickets_buf = array_ref![src, 25, 4];
+        let round_tickets = u32::from_le_bytes(*round_tickets_buf);
+        let spend_day_buf = array_ref![src, 29, 8];
+        let spend_day = i64::from_le_bytes(*spend_day_buf);
+        let day_spent_buf = array_ref![src, 37, 8];
+        let day_spent = u64::from_le_bytes(*day_spent_buf);
+        let balance = u64::from_le_bytes(*array_ref![src, 45, 8]);
+        let scratch_slot = Slot::from_le_bytes(*array_ref![src, 53, 8]);
+        let scratch_cards = u16::from_le_bytes(*array_ref![src, 61, 2]);
+        let scratch_price = u64::from_le_bytes(*array_ref![src, 63, 8]);
+        let played_round = u32::from_le_bytes(*array_ref![src, 71, 4]);
+        let referrer = Pubkey::new_from_array(*array_ref![src, 75, 32]);
+        let referrals = u32::from_le_bytes(*array_ref![src, 107, 4]);
+        let bonus_tickets = u16::from_le_bytes(*array_ref![src, 111, 2]);
+        let referral_tickets = u32::from_le_bytes(*array_ref![src, 113, 4]);
+        let referral_earned = u64::from_le_bytes(*array_ref![src, 117, 8]);
+
+        Ok(PlayerState {
+            is_initialized,
+            first_seen_slot,
+            last_signin_day,
+            streak,
+            round,
+            round_tickets,
+            spend_day,
+            day_spent,
+            balance,
+            scratch_slot,
+            scratch_cards,
+            scratch_price,
+            played_round,
+            referrer,
+            referrals,
+            bonus_tickets,
+            referral_tickets,
+            referral_earned,
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PlayerState::LEN];
+        dst[0] = self.is_initialized as u8;
+        let first_seen_slot_buf = array_mut_ref![dst, 1, 8];
+        first_seen_slot_buf.copy_from_slice(&self.first_seen_slot.to_le_bytes());
+        let last_signin_day_buf = array_mut_ref![dst, 9, 8];
+        last_signin_day_buf.copy_from_slice(&self.last_signin_day.to_le_bytes());
+        let streak_buf = array_mut_ref![dst, 17, 4];
+        streak_buf.copy_from_slice(&self.streak.to_le_bytes());
+        let round_buf = array_mut_ref![dst, 21, 4];
+        round_buf.copy_from_slice(&self.round.to_le_bytes());
+        let round_tickets_buf = array_mut_ref![dst, 25, 4];
+        round_tickets_buf.copy_from_slice(&self.round_tickets.to_le_bytes());
+        let spend_day_buf = array_mut_ref![dst, 29, 8];
+        spend_day_buf.copy_from_slice(&self.spend_day.to_le_bytes());
+        let day_spent_buf = array_mut_ref![dst, 37, 8];
+        day_spent_buf.copy_from_slice(&self.day_spent.to_le_bytes());
+        array_mut_ref![dst, 45, 8].copy_from_slice(&self.balance.to_le_bytes());
+        array_mut_ref![dst, 53, 8].copy_from_slice(&self.scratch_slot.to_le_bytes());
+        array_mut_ref![dst, 61, 2].copy_from_slice(&self.scratch_cards.to_le_bytes());
+        array_mut_ref![dst, 63, 8].copy_from_slice(&self.scratch_price.to_le_bytes());
+        array_mut_ref![dst, 71, 4].copy_from_slice(&self.played_round.to_le_bytes());
+        array_mut_ref![dst, 75, 32].copy_from_slice(self.referrer.as_ref());
+        array_mut_ref![dst, 107, 4].copy_from_slice(&self.referrals.to_le_bytes());
+        array_mut_ref![dst, 111, 2].copy_from_slice(&self.bonus_tickets.to_le_bytes());
+        array_mut_ref![dst, 113, 4].copy_from_slice(&self.referral_tickets.to_le_bytes());
+        array_mut_ref![dst, 117, 8].copy_from_slice(&self.referral_earned.to_le_bytes());
+    }
+}
+
+/// Role of a key in a pool
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
+pub enum Role {
+    /// the admin set , initializes , configures and assigns the other roles
+    Owner = 0,
+    /// triggers Roll
+    Roller = 1,
+    /// moves funds and changes prizes
+    Treasurer = 2,
+    /// pauses and unpauses the pool
+    Pauser = 3,
+}
+
+/// RaffleStatus
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
+pub enum RaffleStatus {
+    /// the sponsor deposits prizes , the next roll draws them
+    Open = 0,
+    /// the prizes were assigned to winners
+    Drawn = 1,
+    /// the raffle was called off , the sponsor withdraws the prizes
+    Cancelled = 2,
+}
+
+/// PrizeStatus
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
+pub enum PrizeStatus {
+    /// held by the escrow , waiting for the draw
+    Escrowed = 0,
+    /// assigned to a winner , not claimed yet
+    Won = 1,
+    /// transferred to the winner
+    Claimed = 2,
+    /// transferred back to the sponsor
+    Withdrawn = 3,
+}
+
+/// RafflePrize, an NFT escrowed by a raffle
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RafflePrize {
+    /// mint of the NFT , supply 1
+    pub mint: Pubkey,
+    /// token account holding the NFT , owned by the vault authority
+    pub escrow: Pubkey,
+    /// winner's account , default key before the draw
+    pub winner: Pubkey,
+    /// round drawn, index of its award bill in billboard
+    pub round: u16,
+    /// where the NFT is
+    pub status: PrizeStatus,
+}
+
+impl RafflePrize {
+    /// packed length of a prize
+    pub const LEN: usize = 32+32+32+2+1;
+
+    /// Unpacks a prize from the start of `src`.
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let status = PrizeStatus::try_from_primitive(src[98])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok(RafflePrize {
+            mint: Pubkey::new_from_array(*array_ref![src, 0, 32]),
+            escrow: Pubkey::new_from_array(*array_ref![src, 32, 32]),
+            winner: Pubkey::new_from_array(*array_ref![src, 64, 32]),
+            round: u16::from_le_bytes(*array_ref![src, 96, 2]),
+            status,
+        })
+    }
+
+    /// Packs the prize into the start of `dst`.
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        array_mut_ref![dst, 0, 32].copy_from_slice(self.mint.as_ref());
+        array_mut_ref![dst, 32, 32].copy_from_slice(self.escrow.as_ref());
+        array_mut_ref![dst, 64, 32].copy_from_slice(self.winner.as_ref());
+        array_mut_ref![dst, 96, 2].copy_from_slice(&self.round.to_le_bytes());
+        dst[98] = self.status as u8;
+    }
+}
+
+/// RaffleState data, the NFT prizes a pool raffles off.
+///
+/// Lives at the program address of [RAFFLE_SEED] and the pool, see
+/// [find_address](struct.RaffleState.html#method.find_address).
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RaffleState {
+    /// whether the raffle has been created
+    pub is_initialized: bool,
+    /// pool drawing the raffle
+    pub pool: Pubkey,
+    /// key depositing the prizes , and withdrawing them if cancelled
+    pub sponsor: Pubkey,
+    /// stage of the raffle
+    pub status: RaffleStatus,
+    /// escrowed prizes , drawn in order
+    pub prizes: Vec<RafflePrize>,
+}
+
+impl RaffleState {
+    /// Finds the raffle address of `pool`.
+    pub fn find_address(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[RAFFLE_SEED, pool.as_ref()], program_id)
+    }
+
+    /// Whether every prize has left the escrow , so the raffle can be replaced.
+    pub fn is_settled(&self) -> bool {
+        self.prizes
+            .iter()
+            .all(|prize| prize.status == PrizeStatus::Claimed || prize.status == PrizeStatus::Withdrawn)
+    }
+}
+
+impl Sealed for RaffleState {}
+impl IsInitialized for RaffleState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for RaffleState {
+    const LEN: usize = 1+32+32+1+1+MAX_RAFFLE_PRIZES*RafflePrize::LEN;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, RaffleState::LEN];
+        let is_initialized = match src[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let pool = Pubkey::new_from_array(*array_ref![src, 1, 32]);
+        let sponsor = Pubkey::new_from_array(*array_ref![src, 33, 32]);
+        let status = RaffleStatus::try_from_primitive(src[65])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let count = src[66] as usize;
+        if count > MAX_RAFFLE_PRIZES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let prizes = (0..count)
+            .map(|i| RafflePrize::unpack_from_slice(&src[67+i*RafflePrize::LEN..]))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(RaffleState {
+            is_initialized,
+            pool,
+            sponsor,
+            status,
+            prizes,
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, RaffleState::LEN];
+        dst[0] = self.is_initialized as u8;
+        array_mut_ref![dst, 1, 32].copy_from_slice(self.pool.as_ref());
+        array_mut_ref![dst, 33, 32].copy_from_slice(self.sponsor.as_ref());
+        dst[65] = self.status as u8;
+        dst[66] = self.prizes.len() as u8;
+        for (i, prize) in self.prizes.iter().enumerate() {
+            prize.pack_into_slice(&mut dst[67+i*RafflePrize::LEN..]);
+        }
+    }
+}
+
+/// AwardStatus
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, TryFromPrimitive)]
+pub enum AwardStatus {
+    /// the winner has not been rewarded yet
+    #[default]
+    Unclaimed = 0,
+    /// the winner has been rewarded
+    Rewarded = 1,
+    /// nobody played, the fund went to the next round
+    RolledOver = 2,
+    /// the winner did not claim in time, the award went back to the pool
+    Expired = 3,
+    /// the winner drew a raffle prize , claimed with ClaimPrize
+    Prize = 4,
+    /// the round was cancelled and its tickets refunded , the fund went to the next round
+    Cancelled = 5,
+}
 
 /// AwardBill
 #[repr(C)]
@@ -25,8 +1171,33 @@ pub struct AwardBill{
     pub account: Pubkey,
     /// award for the winner
     pub award: u64,
-    /// if winner has rewarded
-    pub rewarded: bool,
+    /// whether the winner has rewarded, or the round rolled over
+    pub status: AwardStatus,
+    /// timestamp for this
+    pub timestamp:UnixTimestamp,
+    /// times the round was extended for falling short of the minimum
+    pub extensions: u8,
+    /// last moment the winner can claim , 0 for never expire
+    pub deadline: UnixTimestamp,
+}
+
+impl AwardBill {
+    /// Whether the award is past its claim deadline at `now`.
+    pub fn is_expired(&self, now: UnixTimestamp) -> bool {
+        self.deadline > 0 && now >= self.deadline
+    }
+}
+
+/// SponsorBill
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SponsorBill{
+    /// sponsor's account
+    pub account: Pubkey,
+    /// lamports funded
+    pub amount: u64,
+    /// round funded, index of its award bill in billboard
+    pub round: u16,
     /// timestamp for this
     pub timestamp:UnixTimestamp,
 }
@@ -37,6 +1208,10 @@ pub struct AwardBill{
 pub struct AwardState {
     /// all winner billboard
     pub billboard: Vec<AwardBill>,
+    /// all sponsor contributions
+    pub sponsors: Vec<SponsorBill>,
+    /// pool the billboard was initialized for
+    pub pool: Pubkey,
 }
 
 impl Sealed for AwardState {}
@@ -46,32 +1221,67 @@ impl IsInitialized for AwardState {
     }
 }
 impl Pack for AwardState {
-    const LEN: usize = 2+1000*(32+8+1+8);
+    const LEN: usize = 2+MAX_AWARD*(32+8+1+8+1+8)+2+MAX_SPONSOR*(32+8+2+8)+32;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let mut billboard = Vec::new();
         let count_buf = array_ref![src, 0, 2];
         let count =  u16::from_le_bytes(*count_buf);
         for i in 0..count {
             let i = i as usize;
-            let offset:usize = 2+i*(32+8+1+8) ;
+            let offset:usize = 2+i*(32+8+1+8+1+8) ;
             let account_buf = array_ref![src,offset, 32];
             let account= Pubkey::new_from_array(*account_buf);
             let award_buf= array_ref![src,offset+32, 8];
             let award = u64::from_le_bytes(*award_buf);
-            let rewarded_buf = array_ref![src,offset+40, 1];
-            let rewarded = rewarded_buf[0] != 0;
+            let status_buf = array_ref![src,offset+40, 1];
+            let status = AwardStatus::try_from_primitive(status_buf[0])
+                .map_err(|_| ProgramError::InvalidAccountData)?;
             let timestamp_buf= array_ref![src,offset+41, 8];
             let timestamp= UnixTimestamp::from_le_bytes(*timestamp_buf);
+            let extensions = src[offset+49];
+            let deadline_buf= array_ref![src,offset+50, 8];
+            let deadline= UnixTimestamp::from_le_bytes(*deadline_buf);
             billboard.push(AwardBill{
                 account,
                 award,
-                rewarded,
+                status,
+                timestamp,
+                extensions,
+                deadline,
+            });
+        }
+
+        let mut sponsors = Vec::new();
+        let sponsor_offset:usize = 2+MAX_AWARD*(32+8+1+8+1+8);
+        let count_buf = array_ref![src, sponsor_offset, 2];
+        let count =  u16::from_le_bytes(*count_buf);
+        for i in 0..count {
+            let i = i as usize;
+            let offset:usize = sponsor_offset+2+i*(32+8+2+8);
+            let account_buf = array_ref![src,offset, 32];
+            let account= Pubkey::new_from_array(*account_buf);
+            let amount_buf= array_ref![src,offset+32, 8];
+            let amount = u64::from_le_bytes(*amount_buf);
+            let round_buf= array_ref![src,offset+40, 2];
+            let round = u16::from_le_bytes(*round_buf);
+            let timestamp_buf= array_ref![src,offset+42, 8];
+            let timestamp= UnixTimestamp::from_le_bytes(*timestamp_buf);
+            sponsors.push(SponsorBill{
+                account,
+                amount,
+                round,
                 timestamp,
             });
         }
 
+        let pool_offset:usize = sponsor_offset+2+MAX_SPONSOR*(32+8+2+8);
+        let pool_buf = array_ref![src, pool_offset, 32];
+        let pool = Pubkey::new_from_array(*pool_buf);
+
         Ok(AwardState {
             billboard,
+            sponsors,
+            pool,
         })
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
@@ -80,21 +1290,40 @@ impl Pack for AwardState {
         count_buf.copy_from_slice(&count.to_le_bytes());
         let mut i:usize=0;
         for val in &self.billboard{
-            let offset:usize = 2+i*(32+8+1+8);
+            let offset:usize = 2+i*(32+8+1+8+1+8);
             let account_buf = array_mut_ref![dst, offset, 32];
             account_buf.copy_from_slice(val.account.as_ref());
             let award_buf = array_mut_ref![dst, offset+32, 8];
             award_buf.copy_from_slice(&val.award.to_le_bytes());
-            let reward_buf = array_mut_ref![dst, offset+40, 1];
-            if val.rewarded {
-                reward_buf[0] = 1;
-            } else {
-                reward_buf[0] = 0;
-            }
+            let status_buf = array_mut_ref![dst, offset+40, 1];
+            status_buf[0] = val.status as u8;
             let timestamp_buf = array_mut_ref![dst, offset+41, 8];
             timestamp_buf.copy_from_slice(&val.timestamp.to_le_bytes());
+            dst[offset+49] = val.extensions;
+            let deadline_buf = array_mut_ref![dst, offset+50, 8];
+            deadline_buf.copy_from_slice(&val.deadline.to_le_bytes());
             i += 1;
         }
+
+        let sponsor_offset:usize = 2+MAX_AWARD*(32+8+1+8+1+8);
+        let count_buf = array_mut_ref![dst, sponsor_offset, 2];
+        let count:u16 = self.sponsors.len() as u16;
+        count_buf.copy_from_slice(&count.to_le_bytes());
+        for (i, val) in self.sponsors.iter().enumerate() {
+            let offset:usize = sponsor_offset+2+i*(32+8+2+8);
+            let account_buf = array_mut_ref![dst, offset, 32];
+            account_buf.copy_from_slice(val.account.as_ref());
+            let amount_buf = array_mut_ref![dst, offset+32, 8];
+            amount_buf.copy_from_slice(&val.amount.to_le_bytes());
+            let round_buf = array_mut_ref![dst, offset+40, 2];
+            round_buf.copy_from_slice(&val.round.to_le_bytes());
+            let timestamp_buf = array_mut_ref![dst, offset+42, 8];
+            timestamp_buf.copy_from_slice(&val.timestamp.to_le_bytes());
+        }
+
+        let pool_offset:usize = sponsor_offset+2+MAX_SPONSOR*(32+8+2+8);
+        let pool_buf = array_mut_ref![dst, pool_offset, 32];
+        pool_buf.copy_from_slice(self.pool.as_ref());
     }
 }
 
@@ -104,13 +1333,418 @@ mod test {
 
     #[test]
     fn test_instruction_lottery_state() {
- 
+        let check = LotteryState{
+            fund: 0,
+            billboard: Pubkey::new_from_array([0u8;32]),
+            rollover_bps: 0,
+            rollover_cap: 0,
+            claim_window: 0,
+            price: 0,
+            fee_bps: 0,
+            prize_fee_bps: 0,
+            treasury: Pubkey::new_from_array([0u8;32]),
+            fees: 0,
+            min_balance: 0,
+            min_age_slots: 0,
+            utc_offset: 0,
+            streak_schedule: [StreakTier::default(); STREAK_TIERS],
+            round: 0,
+            max_tickets_per_round: 0,
+            max_daily_spend: 0,
+            paused: false,
+            gm_delay: 0,
+            pending_fund: 0,
+            pending_price: 0,
+            pending_at: 0,
+            admin_threshold: 0,
+            admins: [Pubkey::default(); MAX_ADMINS],
+            roller: Pubkey::default(),
+            treasurer: Pubkey::default(),
+            pauser: Pubkey::default(),
+            campaign_id: 0,
+            roll_interval: 0,
+            last_roll: 0,
+            mint: Pubkey::default(),
+            vault: Pubkey::default(),
+            raffle: false,
+            lotto_picks: 0,
+            lotto_range: 0,
+            lotto_tier_bps: [0; MAX_LOTTO_PICKS],
+            lotto_drawn: 0,
+            lotto_fund: 0,
+            lotto_cursor: 0,
+            lotto_paying: false,
+            lotto_winners: [0; MAX_LOTTO_PICKS],
+            scratch_tiers: [ScratchTier::default(); MAX_SCRATCH_TIERS],
+            scratch_liability: 0,
+            cancelled: false,
+            paid_entries: 0,
+            min_players: 0,
+            min_tickets: 0,
+            threshold_extension: 0,
+            extended_until: 0,
+            round_extensions: 0,
+            round_players: 0,
+            referral_tickets: 0,
+            referral_ticket_cap: 0,
+            referral_bps: 0,
+            referral_cap: 0,
+            max_extensions: 0,
+            lotto_slot: 0,
+            player_count: 0,
+        };
+        let mut packed = vec![0u8;LOTTERY_STATE_LEN];
+        check.pack_into_slice(&mut packed);
+        let expect = vec![0u8;LOTTERY_STATE_LEN];
+        assert_eq!(packed, expect);
+        let unpacked = LotteryState::unpack_from_slice(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert_eq!(LotteryState::unpack_entries(&expect).unwrap(), Vec::new());
+
+        let check = LotteryState{
+            fund: 10_000_000_000u64,
+            billboard: Pubkey::new_from_array([1u8;32]),
+            rollover_bps: 1000,
+            rollover_cap: 1_000_000_000u64,
+            claim_window: 86400,
+            price: 9527,
+            fee_bps: 250,
+            prize_fee_bps: 100,
+            treasury: Pubkey::new_from_array([4u8;32]),
+            fees: 10_000,
+            min_balance: 1_000_000_000u64,
+            min_age_slots: 9527,
+            utc_offset: 28800,
+            streak_schedule: [
+                StreakTier{days: 7, bonus: 1},
+                StreakTier{days: 30, bonus: 3},
+                StreakTier::default(),
+                StreakTier::default(),
+            ],
+            round: 9527,
+            max_tickets_per_round: 10,
+            max_daily_spend: 1_000_000_000u64,
+            paused: true,
+            gm_delay: 86400,
+            pending_fund: 10_000_000_000u64,
+            pending_price: 9527,
+            pending_at: 1608273769,
+            admin_threshold: 2,
+            admins: [
+                Pubkey::new_from_array([5u8;32]),
+                Pubkey::new_from_array([6u8;32]),
+                Pubkey::new_from_array([7u8;32]),
+                Pubkey::default(),
+                Pubkey::default(),
+            ],
+            roller: Pubkey::new_from_array([8u8;32]),
+            treasurer: Pubkey::new_from_array([9u8;32]),
+            pauser: Pubkey::new_from_array([10u8;32]),
+            campaign_id: 7,
+            roll_interval: 86400,
+            last_roll: 1608273769,
+            mint: Pubkey::new_from_array([11u8;32]),
+            vault: Pubkey::new_from_array([12u8;32]),
+            raffle: true,
+            lotto_picks: 6,
+            lotto_range: 49,
+            lotto_tier_bps: [0, 0, 500, 1000, 2000, 5000, 0, 0],
+            lotto_drawn: 0b111111,
+            lotto_fund: 10_000,
+            lotto_cursor: 9527,
+            lotto_paying: true,
+            lotto_winners: [0, 0, 1, 2, 0, 0, 0, 0],
+            scratch_tiers: [
+                ScratchTier{odds: 10, multiplier: 2},
+                ScratchTier{odds: 1000, multiplier: 100},
+                ScratchTier::default(),
+                ScratchTier::default(),
+            ],
+            scratch_liability: 10_000,
+            cancelled: true,
+            paid_entries: 1,
+            min_players: 3,
+            min_tickets: 10,
+            threshold_extension: 3600,
+            extended_until: 1608273769,
+            round_extensions: 2,
+            round_players: 2,
+            referral_tickets: 3,
+            referral_ticket_cap: 30,
+            referral_bps: 500,
+            referral_cap: 9527,
+            max_extensions: 3,
+            lotto_slot: 7,
+            player_count: 2,
+        };
+        let mut packed = vec![0u8;LOTTERY_STATE_LEN];
+        check.pack_into_slice(&mut packed);
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[0, 228, 11, 84, 2, 0, 0, 0]);
+        expect.extend_from_slice(&[1;32]);
+        expect.extend_from_slice(&[232, 3]);
+        expect.extend_from_slice(&[0, 202, 154, 59, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[128, 81, 1, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[250, 0]);
+        expect.extend_from_slice(&[100, 0]);
+        expect.extend_from_slice(&[4;32]);
+        expect.extend_from_slice(&[16, 39, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[0, 202, 154, 59, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[128, 112, 0, 0]);
+        expect.extend_from_slice(&[7, 0, 1, 0, 30, 0, 3, 0]);
+        expect.extend_from_slice(&[0u8;8]);
+        expect.extend_from_slice(&[55, 37, 0, 0]);
+        expect.extend_from_slice(&[10, 0, 0, 0]);
+        expect.extend_from_slice(&[0, 202, 154, 59, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[1u8]);
+        expect.extend_from_slice(&[128, 81, 1, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[0, 228, 11, 84, 2, 0, 0, 0]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[105, 79, 220, 95, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[2u8]);
+        expect.extend_from_slice(&[5;32]);
+        expect.extend_from_slice(&[6;32]);
+        expect.extend_from_slice(&[7;32]);
+        expect.extend_from_slice(&[0;64]);
+        expect.extend_from_slice(&[8;32]);
+        expect.extend_from_slice(&[9;32]);
+        expect.extend_from_slice(&[10;32]);
+        expect.extend_from_slice(&[7, 0, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[128, 81, 1, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[105, 79, 220, 95, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[11;32]);
+        expect.extend_from_slice(&[12;32]);
+        expect.extend_from_slice(&[1u8]);
+        expect.extend_from_slice(&[6u8, 49]);
+        expect.extend_from_slice(&[0, 0, 0, 0, 244, 1, 232, 3, 208, 7, 136, 19, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[63, 0, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[16, 39, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[55, 37]);
+        expect.extend_from_slice(&[1u8]);
+        expect.extend_from_slice(&[0, 0, 0, 0, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[10, 0, 0, 0, 2, 0, 0, 0, 232, 3, 0, 0, 100, 0, 0, 0]);
+        expect.extend_from_slice(&[0u8;16]);
+        expect.extend_from_slice(&[16, 39, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[1u8]);
+        expect.extend_from_slice(&[1u8,0]);
+        expect.extend_from_slice(&[3u8,0]);
+        expect.extend_from_slice(&[10, 0, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[16, 14, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[105, 79, 220, 95, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[2u8]);
+        expect.extend_from_slice(&[2u8,0]);
+        expect.extend_from_slice(&[3u8,0]);
+        expect.extend_from_slice(&[30, 0, 0, 0]);
+        expect.extend_from_slice(&[244, 1]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[3u8]);
+        expect.extend_from_slice(&[7, 0, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[2u8,0]);
+        expect.extend_from_slice(&vec![0u8;LOTTERY_STATE_LEN-LotteryState::LEN]);
+        assert_eq!(packed, expect);
+        let entries = vec![
+            TicketEntry{owner: Pubkey::new_from_array([2;32]), cumulative: 1, picks: 5, paid: 9527, refunded: true, referral: 30, fee: 14},
+            TicketEntry{owner: Pubkey::new_from_array([3;32]), cumulative: 3, picks: 0, paid: 0, refunded: false, referral: 0, fee: 0},
+        ];
+        for (i, entry) in entries.iter().enumerate() {
+            entry.pack_into_slice(&mut expect[LotteryState::entry_offset(i)..]);
+        }
+        assert_eq!(&expect[LotteryState::LEN..LotteryState::LEN+74], &[
+            [2u8;32].as_ref(), &[1, 0, 0, 0, 0, 0, 0, 0], &[5, 0, 0, 0, 0, 0, 0, 0], &[55, 37, 0, 0, 0, 0, 0, 0], &[1],
+            &[30, 0, 0, 0, 0, 0, 0, 0], &[14, 0, 0, 0, 0, 0, 0, 0], &[3],
+        ].concat()[..]);
+        let unpacked = LotteryState::unpack_from_slice(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert_eq!(LotteryState::unpack_entries(&expect).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_lottery_state_rollover_fund() {
+        let mut state = LotteryState{
+            fund: 1_000_000,
+            fees: u64::MAX,
+            ..LotteryState::default()
+        };
+        assert_eq!(state.rollover_fund(), 1_000_000);
+
+        state.rollover_bps = 1000;
+        assert_eq!(state.rollover_fund(), 1_100_000);
+
+        // growth is limited to the fees that pay for it
+        state.fees = 40_000;
+        assert_eq!(state.rollover_fund(), 1_040_000);
+        let mut next = state.clone();
+        assert_eq!(next.roll_over(), 1_040_000);
+        assert_eq!(next.fund, 1_040_000);
+        assert_eq!(next.fees, 0);
+        assert_eq!(next.roll_over(), 1_040_000);
+        state.fees = u64::MAX;
+
+        state.rollover_cap = 1_050_000;
+        assert_eq!(state.rollover_fund(), 1_050_000);
+
+        state.rollover_cap = 500_000;
+        assert_eq!(state.rollover_fund(), 1_000_000);
+
+        state.fund = u64::MAX;
+        state.rollover_cap = 0;
+        assert_eq!(state.rollover_fund(), u64::MAX);
+    }
+
+    #[test]
+    fn test_player_state() {
+        let check = PlayerState{
+            is_initialized: true,
+            first_seen_slot: 9527,
+            last_signin_day: 18614,
+            streak: 7,
+            round: 9527,
+            round_tickets: 3,
+            spend_day: 18614,
+            day_spent: 1_000_000_000u64,
+            balance: 200,
+            scratch_slot: 9528,
+            scratch_cards: 3,
+            scratch_price: 100,
+            played_round: 9528,
+            referrer: Pubkey::new_from_array([5u8;32]),
+            referrals: 2,
+            bonus_tickets: 3,
+            referral_tickets: 6,
+            referral_earned: 10,
+        };
+        let mut packed = vec![0u8;PlayerState::LEN];
+        PlayerState::pack(check.clone(), &mut packed).unwrap();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[1u8]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[182, 72, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[7, 0, 0, 0]);
+        expect.extend_from_slice(&[55, 37, 0, 0]);
+        expect.extend_from_slice(&[3, 0, 0, 0]);
+        expect.extend_from_slice(&[182, 72, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[0, 202, 154, 59, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[200, 0, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[56, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[3, 0]);
+        expect.extend_from_slice(&[100, 0, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[56, 37, 0, 0]);
+        expect.extend_from_slice(&[5u8;32]);
+        expect.extend_from_slice(&[2, 0, 0, 0]);
+        expect.extend_from_slice(&[3, 0]);
+        expect.extend_from_slice(&[6, 0, 0, 0]);
+        expect.extend_from_slice(&[10, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = PlayerState::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        assert!(PlayerState::unpack(&[0u8;PlayerState::LEN]).is_err());
+        assert!(PlayerState::unpack_unchecked(&[2u8;PlayerState::LEN]).is_err());
+    }
+
+    #[test]
+    fn test_player_state_allowance() {
+        let mut pool = LotteryState{
+            round: 3,
+            ..LotteryState::default()
+        };
+        let mut record = PlayerState{
+            is_initialized: true,
+            round: 3,
+            round_tickets: 4,
+            spend_day: 18614,
+            day_spent: 400,
+            ..PlayerState::default()
+        };
+        assert_eq!(record.tickets_left(&pool), None);
+        assert_eq!(record.spend_left(&pool, 18614), None);
+
+        pool.max_tickets_per_round = 10;
+        pool.max_daily_spend = 1000;
+        assert_eq!(record.tickets_left(&pool), Some(6));
+        assert_eq!(record.spend_left(&pool, 18614), Some(600));
+        // counters of a past round or day do not count
+        assert_eq!(record.spend_left(&pool, 18615), Some(1000));
+        pool.round = 4;
+        assert_eq!(record.tickets_left(&pool), Some(10));
+
+        record.round = 4;
+        record.round_tickets = 12;
+        assert_eq!(record.tickets_left(&pool), Some(0));
+    }
+
+    #[test]
+    fn test_lottery_state_split_fee() {
+        assert_eq!(LotteryState::split_fee(1_000_000, 0), (1_000_000, 0));
+        assert_eq!(LotteryState::split_fee(1_000_000, 250), (975_000, 25_000));
+        assert_eq!(LotteryState::split_fee(999, 250), (975, 24));
+        assert_eq!(LotteryState::split_fee(1_000_000, 10_000), (0, 1_000_000));
+        assert_eq!(LotteryState::split_fee(u64::MAX, 10_000), (0, u64::MAX));
+    }
+
+    #[test]
+    fn test_lottery_state_day_of() {
+        let mut state = LotteryState::default();
+        // 2020-12-18 06:42:49 UTC
+        assert_eq!(state.day_of(1608273769), 18614);
+        assert_eq!(state.day_of(0), 0);
+        assert_eq!(state.day_of(-1), -1);
+
+        // Beijing, 2020-12-18 14:42:49 and 2020-12-19 00:00:00
+        state.utc_offset = 28800;
+        assert_eq!(state.day_of(1608273769), 18614);
+        assert_eq!(state.day_of(1608307200), 18615);
+        assert_eq!(state.day_of(1608307199), 18614);
+
+        state.utc_offset = -28800;
+        assert_eq!(state.day_of(1608273769), 18613);
+    }
+
+    #[test]
+    fn test_lottery_state_streak_bonus() {
+        let mut state = LotteryState::default();
+        assert_eq!(state.streak_bonus(100), 0);
+
+        state.streak_schedule[0] = StreakTier{days: 30, bonus: 3};
+        state.streak_schedule[1] = StreakTier{days: 7, bonus: 1};
+        state.streak_schedule[2] = StreakTier{days: 0, bonus: 9};
+        assert_eq!(state.streak_bonus(1), 0);
+        assert_eq!(state.streak_bonus(6), 0);
+        assert_eq!(state.streak_bonus(7), 1);
+        assert_eq!(state.streak_bonus(29), 1);
+        assert_eq!(state.streak_bonus(30), 3);
+        assert_eq!(state.streak_bonus(u32::MAX), 3);
+    }
+
+    #[test]
+    fn test_lottery_state_claim_deadline() {
+        let mut state = LotteryState::default();
+        assert_eq!(state.claim_deadline(1608273769), 0);
+
+        state.claim_window = 86400;
+        assert_eq!(state.claim_deadline(1608273769), 1608273769+86400);
+        assert_eq!(state.claim_deadline(i64::MAX), i64::MAX);
+    }
+
+    #[test]
+    fn test_award_bill_is_expired() {
+        let mut bill = AwardBill::default();
+        assert!(!bill.is_expired(i64::MAX));
+
+        bill.deadline = 1608273769+86400;
+        assert!(!bill.is_expired(1608273769));
+        assert!(!bill.is_expired(1608273769+86399));
+        assert!(bill.is_expired(1608273769+86400));
     }
 
     #[test]
     fn test_instruction_award_state() {
         let check = AwardState{
             billboard: Vec::new(),
+            sponsors: Vec::new(),
+            pool: Pubkey::default(),
         };
         let mut packed = [0u8;AwardState::LEN];
         check.pack_into_slice(&mut packed);
@@ -127,12 +1761,16 @@ mod test {
         let b = AwardBill {
             account: Pubkey::new(&[0u8;32]),
             award:0u64,
-            rewarded:false,
+            status:AwardStatus::Unclaimed,
             timestamp:0,
+            extensions: 0,
+            deadline: 0,
         };
         billboard.push(b);
         let check = AwardState{
             billboard,
+            sponsors: Vec::new(),
+            pool: Pubkey::default(),
         };
         let mut packed = [0u8;AwardState::LEN];
         check.pack_into_slice(&mut packed);
@@ -142,7 +1780,9 @@ mod test {
         expect.extend_from_slice(&[0;8]);
         expect.extend_from_slice(&[0]);
         expect.extend_from_slice(&[0;8]);
-        expect.extend_from_slice(&[0u8;AwardState::LEN-(2+49)]);
+        expect.extend_from_slice(&[0]);
+        expect.extend_from_slice(&[0;8]);
+        expect.extend_from_slice(&[0u8;AwardState::LEN-(2+58)]);
         assert_eq!(packed.to_vec(), expect);
         let unpacked = AwardState::unpack_from_slice(&expect).unwrap();
         assert_eq!(unpacked, check); 
@@ -151,21 +1791,27 @@ mod test {
         let b = AwardBill {
             account: Pubkey::new(&[0u8;32]),
             award:0u64,
-            rewarded:false,
+            status:AwardStatus::Unclaimed,
             timestamp: 1608273769,
+            extensions: 0,
+            deadline: 0,
         };
         billboard.push(b);
         let b = AwardBill {
             account: Pubkey::new(&[1u8;32]),
             award:10_000_000_000u64,
-            rewarded:true,
+            status:AwardStatus::Rewarded,
             timestamp: 1608273769,
+            extensions: 2,
+            deadline: 1608273769+86400,
         };
         billboard.push(b);
 
 
         let check = AwardState{
             billboard,
+            sponsors: Vec::new(),
+            pool: Pubkey::default(),
         };
         let mut packed = [0u8;AwardState::LEN];
         check.pack_into_slice(&mut packed);
@@ -175,15 +1821,277 @@ mod test {
         expect.extend_from_slice(&[0;8]);
         expect.extend_from_slice(&[0]);
         expect.extend_from_slice(&[105, 79, 220, 95, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[0]);
+        expect.extend_from_slice(&[0;8]);
         expect.extend_from_slice(&[1;32]);
         expect.extend_from_slice(&[0, 228, 11, 84, 2, 0, 0, 0]);
         expect.extend_from_slice(&[1]);
         expect.extend_from_slice(&[105, 79, 220, 95, 0, 0, 0, 0]);
-        expect.extend_from_slice(&[0u8;AwardState::LEN-(2+49*2)]);
+        expect.extend_from_slice(&[2]);
+        expect.extend_from_slice(&[233, 160, 221, 95, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[0u8;AwardState::LEN-(2+58*2)]);
         assert_eq!(packed.to_vec(), expect);
         let unpacked = AwardState::unpack_from_slice(&expect).unwrap();
         assert_eq!(unpacked, check); 
 
     }
 
+    #[test]
+    fn test_instruction_award_state_sponsors() {
+        let billboard = vec![AwardBill {
+            account: Pubkey::new_from_array([1u8;32]),
+            award:10_000_000_000u64,
+            status:AwardStatus::Unclaimed,
+            timestamp: 1608273769,
+            extensions: 0,
+            deadline: 0,
+        }];
+        let sponsors = vec![SponsorBill {
+            account: Pubkey::new_from_array([2u8;32]),
+            amount:9527u64,
+            round:1,
+            timestamp: 1608273769,
+        }];
+        let check = AwardState{
+            billboard,
+            sponsors,
+            pool: Pubkey::new_from_array([3u8;32]),
+        };
+        let mut packed = vec![0u8;AwardState::LEN];
+        check.pack_into_slice(&mut packed);
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[1u8,0]);
+        expect.extend_from_slice(&[1;32]);
+        expect.extend_from_slice(&[0, 228, 11, 84, 2, 0, 0, 0]);
+        expect.extend_from_slice(&[0]);
+        expect.extend_from_slice(&[105, 79, 220, 95, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[0]);
+        expect.extend_from_slice(&[0;8]);
+        expect.extend_from_slice(&vec![0u8;(MAX_AWARD-1)*58]);
+        expect.extend_from_slice(&[1u8,0]);
+        expect.extend_from_slice(&[2;32]);
+        expect.extend_from_slice(&[55, 37, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[1u8,0]);
+        expect.extend_from_slice(&[105, 79, 220, 95, 0, 0, 0, 0]);
+        expect.extend_from_slice(&vec![0u8;(MAX_SPONSOR-1)*50]);
+        expect.extend_from_slice(&[3;32]);
+        assert_eq!(packed, expect);
+        let unpacked = AwardState::unpack_from_slice(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_admin_approvals() {
+        let pool = LotteryState{
+            admin_threshold: 2,
+            admins: [
+                Pubkey::new_from_array([5u8;32]),
+                Pubkey::new_from_array([6u8;32]),
+                Pubkey::new_from_array([7u8;32]),
+                Pubkey::default(),
+                Pubkey::default(),
+            ],
+            ..LotteryState::default()
+        };
+        assert_eq!(pool.admin_approvals(&[]), 0);
+        assert_eq!(pool.admin_approvals(&[Pubkey::new_from_array([5u8;32])]), 1);
+        // the same signer counts once , strangers and the unused slots not at all
+        assert_eq!(pool.admin_approvals(&[
+            Pubkey::new_from_array([5u8;32]),
+            Pubkey::new_from_array([5u8;32]),
+            Pubkey::new_from_array([8u8;32]),
+            Pubkey::default(),
+        ]), 1);
+        assert_eq!(pool.admin_approvals(&[
+            Pubkey::new_from_array([7u8;32]),
+            Pubkey::new_from_array([6u8;32]),
+        ]), 2);
+    }
+
+    #[test]
+    fn test_can_roll() {
+        let mut pool = LotteryState{
+            last_roll: 1608273769,
+            ..LotteryState::default()
+        };
+        assert!(pool.can_roll(0));
+        pool.roll_interval = 86400;
+        assert!(!pool.can_roll(1608273769));
+        assert!(!pool.can_roll(1608273769+86399));
+        assert!(pool.can_roll(1608273769+86400));
+        // an extended round waits for the extension to run out
+        pool.extended_until = 1608273769+90000;
+        assert!(!pool.can_roll(1608273769+86400));
+        assert!(pool.can_roll(1608273769+90000));
+    }
+
+    #[test]
+    fn test_referral_rewards() {
+        let mut pool = LotteryState{
+            referral_tickets: 3,
+            referral_bps: 500,
+            ..LotteryState::default()
+        };
+        assert_eq!(pool.referral_bonus(1000), 3);
+        assert_eq!(pool.referral_share(1000, 1_000_000), 50);
+        // the last rewards are cut down to the caps
+        pool.referral_ticket_cap = 10;
+        pool.referral_cap = 120;
+        assert_eq!(pool.referral_bonus(6), 3);
+        assert_eq!(pool.referral_bonus(8), 2);
+        assert_eq!(pool.referral_bonus(12), 0);
+        assert_eq!(pool.referral_share(1000, 50), 50);
+        assert_eq!(pool.referral_share(1000, 100), 20);
+        assert_eq!(pool.referral_share(1000, 120), 0);
+    }
+
+    #[test]
+    fn test_round_threshold() {
+        let mut pool = LotteryState{
+            round: 3,
+            min_players: 2,
+            min_tickets: 5,
+            ..LotteryState::default()
+        };
+        let mut record = PlayerState::default();
+        assert!(record.join_round(&pool));
+        assert!(!record.join_round(&pool));
+        pool.round_players = 1;
+        assert!(!pool.meets_threshold(10));
+        pool.round_players = 2;
+        assert!(!pool.meets_threshold(4));
+        assert!(pool.meets_threshold(5));
+        // a player joins each round once , round 0 included
+        pool.round = 0;
+        assert!(record.join_round(&pool));
+        assert!(!record.join_round(&pool));
+    }
+
+    #[test]
+    fn test_pool_address() {
+        let program_id = Pubkey::new_from_array([1u8;32]);
+        let (daily, _) = LotteryState::find_address(&program_id, 1);
+        let (weekly, _) = LotteryState::find_address(&program_id, 2);
+        assert_ne!(daily, weekly);
+        assert_eq!(LotteryState::find_address(&program_id, 1).0, daily);
+    }
+
+    #[test]
+    fn test_raffle_state() {
+        let check = RaffleState{
+            is_initialized: true,
+            pool: Pubkey::new_from_array([1u8;32]),
+            sponsor: Pubkey::new_from_array([2u8;32]),
+            status: RaffleStatus::Drawn,
+            prizes: vec![
+                RafflePrize{
+                    mint: Pubkey::new_from_array([3u8;32]),
+                    escrow: Pubkey::new_from_array([4u8;32]),
+                    winner: Pubkey::new_from_array([5u8;32]),
+                    round: 9527,
+                    status: PrizeStatus::Won,
+                },
+                RafflePrize{
+                    mint: Pubkey::new_from_array([6u8;32]),
+                    escrow: Pubkey::new_from_array([7u8;32]),
+                    winner: Pubkey::new_from_array([8u8;32]),
+                    round: 9528,
+                    status: PrizeStatus::Claimed,
+                },
+            ],
+        };
+        let mut packed = vec![0u8;RaffleState::LEN];
+        RaffleState::pack(check.clone(), &mut packed).unwrap();
+        let mut expect = Vec::new();
+        expect.extend_from_slice(&[1u8]);
+        expect.extend_from_slice(&[1;32]);
+        expect.extend_from_slice(&[2;32]);
+        expect.extend_from_slice(&[1u8, 2]);
+        expect.extend_from_slice(&[3;32]);
+        expect.extend_from_slice(&[4;32]);
+        expect.extend_from_slice(&[5;32]);
+        expect.extend_from_slice(&[55, 37, 1]);
+        expect.extend_from_slice(&[6;32]);
+        expect.extend_from_slice(&[7;32]);
+        expect.extend_from_slice(&[8;32]);
+        expect.extend_from_slice(&[56, 37, 2]);
+        expect.extend_from_slice(&vec![0u8;(MAX_RAFFLE_PRIZES-2)*RafflePrize::LEN]);
+        assert_eq!(packed, expect);
+        let unpacked = RaffleState::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(!unpacked.is_settled());
+
+        assert!(RaffleState::unpack(&[0u8;RaffleState::LEN]).is_err());
+        expect[66] = MAX_RAFFLE_PRIZES as u8 + 1;
+        assert!(RaffleState::unpack(&expect).is_err());
+        expect[66] = 2;
+        expect[67+RafflePrize::LEN-1] = 4;
+        assert!(RaffleState::unpack(&expect).is_err());
+    }
+
+    #[test]
+    fn test_lotto_mask() {
+        let pool = LotteryState{
+            lotto_picks: 3,
+            lotto_range: 49,
+            ..LotteryState::default()
+        };
+        assert_eq!(pool.lotto_mask(&[1, 3, 49, 0, 0, 0, 0, 0]), Some(1 | 1 << 2 | 1 << 48));
+        assert_eq!(pool.lotto_mask(&[49, 3, 1, 0, 0, 0, 0, 0]), Some(1 | 1 << 2 | 1 << 48));
+        // wrong count , duplicates and out of range numbers
+        assert_eq!(pool.lotto_mask(&[1, 3, 0, 0, 0, 0, 0, 0]), None);
+        assert_eq!(pool.lotto_mask(&[1, 3, 5, 7, 0, 0, 0, 0]), None);
+        assert_eq!(pool.lotto_mask(&[1, 3, 3, 0, 0, 0, 0, 0]), None);
+        assert_eq!(pool.lotto_mask(&[1, 3, 50, 0, 0, 0, 0, 0]), None);
+
+        let pool = LotteryState{
+            lotto_picks: 8,
+            lotto_range: MAX_LOTTO_RANGE,
+            ..LotteryState::default()
+        };
+        assert_eq!(pool.lotto_mask(&[64, 63, 62, 61, 60, 59, 58, 57]), Some(0xff << 56));
+    }
+
+    #[test]
+    fn test_lotto_tier_share() {
+        let mut pool = LotteryState{
+            lotto_picks: 3,
+            lotto_range: 10,
+            lotto_tier_bps: [0, 2000, 5000, 0, 0, 0, 0, 0],
+            lotto_drawn: 0b111,
+            lotto_fund: 1000,
+            ..LotteryState::default()
+        };
+        assert_eq!(pool.lotto_tier(0b111), Some(2));
+        assert_eq!(pool.lotto_tier(0b1011), Some(1));
+        // one match pays nothing , neither does none
+        assert_eq!(pool.lotto_tier(0b11001), None);
+        assert_eq!(pool.lotto_tier(0b111000), None);
+
+        assert_eq!(pool.lotto_share(2), 0);
+        pool.lotto_winners = [0, 3, 1, 0, 0, 0, 0, 0];
+        assert_eq!(pool.lotto_share(1), 66);
+        assert_eq!(pool.lotto_share(2), 500);
+    }
+
+    #[test]
+    fn test_scratch_tiers() {
+        let mut pool = LotteryState{
+            price: 100,
+            ..LotteryState::default()
+        };
+        assert!(!pool.is_scratch());
+        assert_eq!(pool.scratch_max_payout(100), Some(0));
+
+        pool.scratch_tiers[1] = ScratchTier{odds: 10, multiplier: 2};
+        pool.scratch_tiers[2] = ScratchTier{odds: 1000, multiplier: 100};
+        assert!(pool.is_scratch());
+        assert_eq!(pool.scratch_max_payout(100), Some(10_000));
+        assert_eq!(pool.scratch_max_payout(u64::MAX), None);
+
+        assert_eq!(pool.scratch_tiers[0].chance(), 0);
+        assert_eq!(pool.scratch_tiers[1].chance(), 100_000);
+        assert_eq!(pool.scratch_tiers[2].chance(), 1000);
+        assert_eq!(ScratchTier{odds: 3, multiplier: 2}.chance(), 333_333);
+    }
 }
@@ -0,0 +1,526 @@
+//! Integration tests of the solong-lottery program, run against a local bank
+//! by `solana-program-test` with the SPL token program loaded.
+//!
+//! The test files share the [Env](struct.Env.html) fixture: a bank running the
+//! program with a pool created by CreatePool on its campaign address , grown
+//! by ExtendPool and initialized by Initialize , so every test starts from the
+//! state a deployment reaches.
+
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    entrypoint::MAX_PERMITTED_DATA_INCREASE,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+use solong_lottery::{
+    instruction::LotteryInstruction,
+    processor::Processor,
+    state::{
+        AwardState, LotteryState, PlayerState, ScratchTier, TicketEntry, LOTTERY_STATE_LEN, MAX_ADMINS,
+        MAX_LOTTO_PICKS, MAX_SCRATCH_TIERS,
+    },
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+/// Campaign of the pool every env starts with.
+pub const CAMPAIGN_ID: u64 = 1;
+
+/// ExtendPool instructions sent in one transaction while growing a pool.
+const EXTENSIONS_PER_TRANSACTION: usize = 16;
+
+/// A bank running the lottery program, with an admin set of one generated
+/// admin and the pool and billboard of the env.
+pub struct Env {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    pub admin: Keypair,
+    pub pool: Pubkey,
+    pub billboard: Pubkey,
+}
+
+/// Starts a bank with a fresh lamport pool selling tickets at `price`, whose
+/// starting prize `fund` is sent to the pool before Initialize.
+pub async fn setup(price: u64, fund: u64) -> Env {
+    let mut env = Env::start().await;
+    let admin = env.admin();
+    let payer = env.context.payer.pubkey();
+    if fund > 0 {
+        env.send(system_instruction::transfer(&payer, &env.pool, fund), &[]).await.unwrap();
+    }
+    env.send(env.initialize(&env.pool, fund, price), &[&admin]).await.unwrap();
+    env
+}
+
+/// Custom error code a transaction failed with.
+pub fn error_code(error: TransportError) -> Option<u32> {
+    match error {
+        TransportError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code))) => Some(code),
+        _ => None,
+    }
+}
+
+impl Env {
+    /// Starts a bank with the pool of `CAMPAIGN_ID` created at full size and
+    /// an empty billboard , neither initialized yet.
+    pub async fn start() -> Env {
+        let program_id = Pubkey::new_unique();
+        let mut test = ProgramTest::new("solong_lottery", program_id, processor!(Processor::process));
+        let admin = Keypair::new();
+        test.add_account(admin.pubkey(), Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        });
+
+        let context = test.start_with_context().await;
+        let mut env = Env {
+            context,
+            program_id,
+            admin,
+            pool: Pubkey::default(),
+            billboard: Pubkey::default(),
+        };
+        env.pool = env.create_pool(CAMPAIGN_ID).await;
+        env.billboard = env.create_billboard().await;
+        env
+    }
+
+    /// A copy of the admin keypair, to sign next to borrows of the env.
+    pub fn admin(&self) -> Keypair {
+        Keypair::from_bytes(&self.admin.to_bytes()).unwrap()
+    }
+
+    /// Sends `instruction` under a fresh blockhash, so a repeated instruction
+    /// is not taken for the last one.
+    pub async fn send(&mut self, instruction: Instruction, signers: &[&Keypair]) -> Result<(), TransportError> {
+        self.send_all(&[instruction], signers).await
+    }
+
+    /// Sends `instructions` in one transaction under a fresh blockhash.
+    pub async fn send_all(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), TransportError> {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let payer = Keypair::from_bytes(&self.context.payer.to_bytes()).unwrap();
+        let mut all = vec![&payer];
+        all.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &all, blockhash);
+        self.context.banks_client.process_transaction(transaction).await.map_err(Into::into)
+    }
+
+    /// Creates the pool of `campaign_id` and grows it to its full size,
+    /// administered by the env's admin.
+    pub async fn create_pool(&mut self, campaign_id: u64) -> Pubkey {
+        let (pool, _) = LotteryState::find_address(&self.program_id, campaign_id);
+        let payer = self.context.payer.pubkey();
+        let create = Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::CreatePool { campaign_id }.pack(),
+            vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new(pool, false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        );
+        self.send(create, &[]).await.unwrap();
+        let extend = Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::ExtendPool.pack(),
+            vec![AccountMeta::new(pool, false)],
+        );
+        let len = self.context.banks_client.get_account(pool).await.unwrap().unwrap().data.len();
+        let extensions = (LOTTERY_STATE_LEN - len).div_ceil(MAX_PERMITTED_DATA_INCREASE);
+        for count in (0..extensions).step_by(EXTENSIONS_PER_TRANSACTION) {
+            let batch = vec![extend.clone(); EXTENSIONS_PER_TRANSACTION.min(extensions - count)];
+            self.send_all(&batch, &[]).await.unwrap();
+        }
+
+        // stands in for SetAdmins , which a created pool only takes from the
+        // program's admin key
+        let mut account = self.context.banks_client.get_account(pool).await.unwrap().unwrap();
+        let mut state = LotteryState::unpack_from_slice(&account.data).unwrap();
+        let mut admins = [Pubkey::default(); MAX_ADMINS];
+        admins[0] = self.admin.pubkey();
+        state.admin_threshold = 1;
+        state.admins = admins;
+        state.pack_into_slice(&mut account.data);
+        self.context.set_account(&pool, &account.into());
+        pool
+    }
+
+    /// Creates an empty billboard owned by the program.
+    pub async fn create_billboard(&mut self) -> Pubkey {
+        let billboard = Keypair::new();
+        let payer = self.context.payer.pubkey();
+        let create = system_instruction::create_account(
+            &payer,
+            &billboard.pubkey(),
+            Rent::default().minimum_balance(AwardState::LEN),
+            AwardState::LEN as u64,
+            &self.program_id,
+        );
+        self.send(create, &[&billboard]).await.unwrap();
+        billboard.pubkey()
+    }
+
+    /// Creates a funded wallet.
+    pub async fn create_player(&mut self) -> Keypair {
+        let player = Keypair::new();
+        let payer = self.context.payer.pubkey();
+        self.send(system_instruction::transfer(&payer, &player.pubkey(), 1_000_000_000), &[])
+            .await
+            .unwrap();
+        player
+    }
+
+    /// Creates a mint with `decimals` whose authority is the payer.
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.context.payer.pubkey();
+        let instructions = [
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                Rent::default().minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &payer, None, decimals).unwrap(),
+        ];
+        self.send_all(&instructions, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// Creates a token account of `mint` owned by `owner`, holding `amount`
+    /// minted by the payer.
+    pub async fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let account = Keypair::new();
+        let payer = self.context.payer.pubkey();
+        let mut instructions = vec![
+            system_instruction::create_account(
+                &payer,
+                &account.pubkey(),
+                Rent::default().minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(&spl_token::id(), &account.pubkey(), mint, owner).unwrap(),
+        ];
+        if amount > 0 {
+            instructions.push(
+                spl_token::instruction::mint_to(&spl_token::id(), mint, &account.pubkey(), &payer, &[], amount).unwrap(),
+            );
+        }
+        self.send_all(&instructions, &[&account]).await.unwrap();
+        account.pubkey()
+    }
+
+    /// Moves the bank clock `seconds` forward.
+    pub async fn advance(&mut self, seconds: i64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    /// Moves the bank past the current slot, so its hash is recorded.
+    pub async fn next_slot(&mut self) {
+        let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        self.context.warp_to_slot(clock.slot + 2).unwrap();
+    }
+
+    pub async fn pool_state(&mut self) -> LotteryState {
+        let account = self.context.banks_client.get_account(self.pool).await.unwrap().unwrap();
+        LotteryState::unpack_from_slice(&account.data).unwrap()
+    }
+
+    pub async fn billboard_state(&mut self) -> AwardState {
+        let account = self.context.banks_client.get_account(self.billboard).await.unwrap().unwrap();
+        AwardState::unpack_unchecked(&account.data).unwrap()
+    }
+
+    /// Owners and ticket counts of the entries of the current round.
+    pub async fn entries(&mut self) -> Vec<(Pubkey, u64)> {
+        let account = self.context.banks_client.get_account(self.pool).await.unwrap().unwrap();
+        let entries: Vec<TicketEntry> = LotteryState::unpack_entries(&account.data).unwrap();
+        let mut cumulative = 0;
+        entries
+            .iter()
+            .map(|entry| {
+                let tickets = entry.cumulative - cumulative;
+                cumulative = entry.cumulative;
+                (entry.owner, tickets)
+            })
+            .collect()
+    }
+
+    pub async fn record(&mut self, player: &Pubkey) -> PlayerState {
+        let account = self.context.banks_client.get_account(self.record_address(player)).await.unwrap().unwrap();
+        PlayerState::unpack(&account.data).unwrap()
+    }
+
+    pub async fn balance(&mut self, key: &Pubkey) -> u64 {
+        self.context.banks_client.get_balance(*key).await.unwrap()
+    }
+
+    pub async fn token_balance(&mut self, account: &Pubkey) -> u64 {
+        let account = self.context.banks_client.get_account(*account).await.unwrap().unwrap();
+        TokenAccount::unpack(&account.data).unwrap().amount
+    }
+
+    pub fn record_address(&self, player: &Pubkey) -> Pubkey {
+        PlayerState::find_address(&self.program_id, &self.pool, player).0
+    }
+
+    pub fn vault_authority(&self) -> Pubkey {
+        LotteryState::find_vault_authority(&self.program_id, &self.pool).0
+    }
+
+    /// An instruction the admin set approves on its own , taking only the pool.
+    fn configure(&self, instruction: LotteryInstruction) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &instruction.pack(),
+            vec![
+                AccountMeta::new_readonly(self.admin.pubkey(), true),
+                AccountMeta::new(self.pool, false),
+            ],
+        )
+    }
+
+    pub fn initialize(&self, pool: &Pubkey, fund: u64, price: u64) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::Initialize { fund, price }.pack(),
+            vec![
+                AccountMeta::new_readonly(self.admin.pubkey(), true),
+                AccountMeta::new(*pool, false),
+                AccountMeta::new(self.billboard, false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+            ],
+        )
+    }
+
+    pub fn gm(&self, fund: u64, price: u64) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::GM { fund, price }.pack(),
+            vec![
+                AccountMeta::new_readonly(self.admin.pubkey(), true),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        )
+    }
+
+    /// Sets the fees , collected for `treasury`.
+    pub fn set_fee(&self, fee_bps: u16, prize_fee_bps: u16, treasury: &Pubkey) -> Instruction {
+        let mut instruction = self.configure(LotteryInstruction::SetFee { fee_bps, prize_fee_bps });
+        instruction.accounts.push(AccountMeta::new_readonly(*treasury, false));
+        instruction
+    }
+
+    pub fn set_rollover(&self, growth_bps: u16, cap: u64) -> Instruction {
+        self.configure(LotteryInstruction::SetRollover { growth_bps, cap })
+    }
+
+    pub fn set_claim_window(&self, seconds: i64) -> Instruction {
+        self.configure(LotteryInstruction::SetClaimWindow { seconds })
+    }
+
+    pub fn set_threshold(&self, min_players: u16, min_tickets: u64, extension: i64, max_extensions: u8) -> Instruction {
+        self.configure(LotteryInstruction::SetThreshold { min_players, min_tickets, extension, max_extensions })
+    }
+
+    pub fn set_referral(&self, tickets: u16, ticket_cap: u32, bps: u16, cap: u64) -> Instruction {
+        self.configure(LotteryInstruction::SetReferral { tickets, ticket_cap, bps, cap })
+    }
+
+    pub fn set_lotto(&self, picks: u8, range: u8, tier_bps: [u16; MAX_LOTTO_PICKS]) -> Instruction {
+        self.configure(LotteryInstruction::SetLotto { picks, range, tier_bps })
+    }
+
+    pub fn set_scratch(&self, tiers: [ScratchTier; MAX_SCRATCH_TIERS]) -> Instruction {
+        self.configure(LotteryInstruction::SetScratch { tiers })
+    }
+
+    /// Denominates the pool in the mint of `vault`.
+    pub fn set_mint(&self, vault: &Pubkey) -> Instruction {
+        let mut instruction = self.configure(LotteryInstruction::SetMint);
+        instruction.accounts.push(AccountMeta::new_readonly(*vault, false));
+        instruction.accounts.push(AccountMeta::new_readonly(self.billboard, false));
+        instruction
+    }
+
+    /// Signs in `player`, referred by `referrer` if any.
+    pub fn sign_in(&self, player: &Pubkey, referrer: Option<&Pubkey>) -> Instruction {
+        let mut accounts = vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.record_address(player), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        let instruction = match referrer {
+            Some(referrer) => {
+                accounts.push(AccountMeta::new(self.record_address(referrer), false));
+                LotteryInstruction::ReferredSignIn { referrer: *referrer }
+            }
+            None => LotteryInstruction::SignIn,
+        };
+        Instruction::new_with_bytes(self.program_id, &instruction.pack(), accounts)
+    }
+
+    /// Buys a ticket for `player`, passing the record of their referrer if any.
+    pub fn buy(&self, player: &Pubkey, referrer: Option<&Pubkey>) -> Instruction {
+        let mut instruction = self.purchase(LotteryInstruction::Buy, player);
+        if let Some(referrer) = referrer {
+            instruction.accounts.push(AccountMeta::new(self.record_address(referrer), false));
+        }
+        instruction
+    }
+
+    /// A purchase of `player`, a Buy , BuyLotto or BuyScratch.
+    pub fn purchase(&self, instruction: LotteryInstruction, player: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &instruction.pack(),
+            vec![
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(*player, true),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new(self.record_address(player), false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+            ],
+        )
+    }
+
+    pub fn fund(&self, sponsor: &Pubkey, amount: u64) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::Fund { amount }.pack(),
+            vec![
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(*sponsor, true),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new(self.billboard, false),
+            ],
+        )
+    }
+
+    pub fn roll(&self) -> Instruction {
+        self.settlement(LotteryInstruction::Roll, &self.admin.pubkey())
+    }
+
+    pub fn cancel_round(&self) -> Instruction {
+        self.settlement(LotteryInstruction::CancelRound, &self.admin.pubkey())
+    }
+
+    /// Settles up to `count` lotto tickets , any signer may.
+    pub fn settle(&self, caller: &Pubkey, count: u16) -> Instruction {
+        let mut instruction = self.settlement(LotteryInstruction::SettleLotto { count }, caller);
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(sysvar::slot_hashes::id(), false));
+        instruction
+    }
+
+    pub fn expire(&self, caller: &Pubkey, round: u16) -> Instruction {
+        self.settlement(LotteryInstruction::Expire { round }, caller)
+    }
+
+    /// An instruction signed by `signer` moving the pool and its billboard.
+    fn settlement(&self, instruction: LotteryInstruction, signer: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &instruction.pack(),
+            vec![
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new_readonly(*signer, true),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new(self.billboard, false),
+            ],
+        )
+    }
+
+    pub fn reward(&self, winner: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::Reward.pack(),
+            vec![
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(self.admin.pubkey(), true),
+                AccountMeta::new(*winner, false),
+                AccountMeta::new(self.billboard, false),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+            ],
+        )
+    }
+
+    pub fn claim(&self, winner: &Pubkey, round: u16) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::Claim { round }.pack(),
+            vec![
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new(*winner, true),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new(self.billboard, false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+            ],
+        )
+    }
+
+    /// Refunds entry `index` of `player`, passing their record and their
+    /// referrer's if the entry paid a referral share.
+    pub fn refund(&self, index: u16, player: &Pubkey, referrer: Option<&Pubkey>) -> Instruction {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.billboard, false),
+            AccountMeta::new(*player, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ];
+        if let Some(referrer) = referrer {
+            accounts.push(AccountMeta::new_readonly(self.record_address(player), false));
+            accounts.push(AccountMeta::new(self.record_address(referrer), false));
+        }
+        Instruction::new_with_bytes(self.program_id, &LotteryInstruction::Refund { index }.pack(), accounts)
+    }
+
+    pub fn reveal_scratch(&self, player: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::RevealScratch.pack(),
+            vec![
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new_readonly(*player, false),
+                AccountMeta::new(self.record_address(player), false),
+            ],
+        )
+    }
+
+    pub fn claim_scratch(&self, player: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::ClaimScratch.pack(),
+            vec![
+                AccountMeta::new(*player, true),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new(self.record_address(player), false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+            ],
+        )
+    }
+}
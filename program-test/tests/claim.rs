@@ -0,0 +1,69 @@
+//! Claim: the winner signs for an award before the deadline recorded on its
+//! bill , a later SetClaimWindow does not move the deadline of drawn awards.
+
+use solana_sdk::signature::Signer;
+use solong_lottery::{error::LotteryError, state::AwardStatus};
+use solong_lottery_program_test::{error_code, Env};
+
+const PRICE: u64 = 1000;
+const WINDOW: i64 = 3600;
+
+/// Starts a bank with a fresh lamport pool whose awards expire after an hour.
+async fn setup() -> Env {
+    let mut env = solong_lottery_program_test::setup(PRICE, 0).await;
+    let admin = env.admin();
+    env.send(env.set_claim_window(WINDOW), &[&admin]).await.unwrap();
+    env
+}
+
+#[tokio::test]
+async fn test_winner_claims_before_deadline() {
+    let mut env = setup().await;
+    let admin = env.admin();
+
+    let player = env.create_player().await;
+    env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    let bill = env.billboard_state().await.billboard[0].clone();
+    assert_eq!(bill.deadline, bill.timestamp + WINDOW);
+
+    // only the winner can sign for the award
+    let stranger = env.create_player().await;
+    let error = env.send(env.claim(&stranger.pubkey(), 0), &[&stranger]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidPermission as u32));
+
+    let before = env.balance(&player.pubkey()).await;
+    env.send(env.claim(&player.pubkey(), 0), &[&player]).await.unwrap();
+    assert_eq!(env.balance(&player.pubkey()).await, before + PRICE);
+    assert_eq!(env.billboard_state().await.billboard[0].status, AwardStatus::Rewarded);
+
+    let error = env.send(env.claim(&player.pubkey(), 0), &[&player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidPermission as u32));
+}
+
+#[tokio::test]
+async fn test_deadline_is_fixed_at_the_draw() {
+    let mut env = setup().await;
+    let admin = env.admin();
+
+    let player = env.create_player().await;
+    env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+    env.send(env.roll(), &[&admin]).await.unwrap();
+
+    // widening the window later does not save an award drawn under the old one
+    env.send(env.set_claim_window(WINDOW * 10), &[&admin]).await.unwrap();
+    env.advance(WINDOW).await;
+    let error = env.send(env.claim(&player.pubkey(), 0), &[&player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::AwardExpired as u32));
+    env.send(env.expire(&player.pubkey(), 0), &[&player]).await.unwrap();
+    assert_eq!(env.billboard_state().await.billboard[0].status, AwardStatus::Expired);
+
+    // and turning expiry off does not free awards drawn before
+    env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    let bill = env.billboard_state().await.billboard[1].clone();
+    assert_eq!(bill.deadline, bill.timestamp + WINDOW * 10);
+    env.send(env.set_claim_window(0), &[&admin]).await.unwrap();
+    env.advance(WINDOW * 10).await;
+    env.send(env.expire(&player.pubkey(), 1), &[&player]).await.unwrap();
+}
@@ -0,0 +1,74 @@
+//! Sponsorship: Fund moves a sponsor's lamports into the pool and raises the
+//! prize of the current round , which Reward later pays out of the pool.
+//! It is the only way to raise the prize , GM can only lower it.
+
+use solana_sdk::signature::Signer;
+use solong_lottery::{error::LotteryError, state::AwardStatus};
+use solong_lottery_program_test::{error_code, setup};
+
+const PRICE: u64 = 100;
+
+#[tokio::test]
+async fn test_fund_and_reward() {
+    let mut env = setup(PRICE, 0).await;
+    let admin = env.admin();
+    let sponsor = env.create_player().await;
+    let pool = env.pool;
+    let before = env.balance(&pool).await;
+    // a sponsorship is worth a ticket at least
+    for amount in [0, PRICE - 1] {
+        let error = env.send(env.fund(&sponsor.pubkey(), amount), &[&sponsor]).await.unwrap_err();
+        assert_eq!(error_code(error), Some(LotteryError::SponsorTooSmall as u32));
+    }
+    env.send(env.fund(&sponsor.pubkey(), 500), &[&sponsor]).await.unwrap();
+    env.send(env.fund(&sponsor.pubkey(), 300), &[&sponsor]).await.unwrap();
+    assert_eq!(env.balance(&pool).await, before + 800);
+    assert_eq!(env.pool_state().await.fund, 800);
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.sponsors.len(), 2);
+    assert_eq!(billboard.sponsors[0].account, sponsor.pubkey());
+    assert_eq!(billboard.sponsors[0].amount, 500);
+    assert_eq!(billboard.sponsors[1].round, 0);
+
+    // the sponsored prize is paid out of the pool
+    let player = env.create_player().await;
+    env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.billboard[0].account, player.pubkey());
+    assert_eq!(billboard.billboard[0].award, 800 + PRICE);
+    let before = env.balance(&player.pubkey()).await;
+    env.send(env.reward(&player.pubkey()), &[&admin]).await.unwrap();
+    assert_eq!(env.balance(&player.pubkey()).await, before + 800 + PRICE);
+    assert_eq!(env.billboard_state().await.billboard[0].status, AwardStatus::Rewarded);
+
+    // sponsoring the next round drops the records of the last one
+    env.send(env.fund(&sponsor.pubkey(), 200), &[&sponsor]).await.unwrap();
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.sponsors.len(), 1);
+    assert_eq!(billboard.sponsors[0].round, 1);
+    assert_eq!(billboard.sponsors[0].amount, 200);
+}
+
+#[tokio::test]
+async fn test_gm_only_lowers_fund() {
+    let mut env = setup(PRICE, 0).await;
+    let admin = env.admin();
+    let pool = env.pool;
+    let reserve = env.balance(&pool).await;
+    let sponsor = env.create_player().await;
+    env.send(env.fund(&sponsor.pubkey(), 500), &[&sponsor]).await.unwrap();
+
+    // raising the prize without lamports behind it is refused
+    let error = env.send(env.gm(600, PRICE), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::FundNotBacked as u32));
+    assert_eq!(env.pool_state().await.fund, 500);
+
+    // the cut moves into the fees , every lamport of the pool stays accounted for
+    env.send(env.gm(400, PRICE), &[&admin]).await.unwrap();
+    let state = env.pool_state().await;
+    assert_eq!((state.fund, state.fees), (400, 100));
+    assert_eq!(env.balance(&pool).await, reserve + state.fund + state.fees);
+    let error = env.send(env.gm(500, PRICE), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::FundNotBacked as u32));
+}
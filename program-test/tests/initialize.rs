@@ -0,0 +1,55 @@
+//! Initialization: a created pool is initialized once , with a starting prize
+//! no larger than the lamports it holds above rent , and a billboard no other
+//! pool uses.
+
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey, signature::Signer, system_instruction};
+use solong_lottery::{error::LotteryError, state::AwardState};
+use solong_lottery_program_test::{error_code, Env, CAMPAIGN_ID};
+
+const PRICE: u64 = 100;
+const FUND: u64 = 500;
+
+/// Starts a bank with two created but not initialized pools holding `FUND`
+/// above rent, returns it and the second pool.
+async fn setup() -> (Env, Pubkey) {
+    let mut env = Env::start().await;
+    let other = env.create_pool(CAMPAIGN_ID + 1).await;
+    let payer = env.context.payer.pubkey();
+    for pool in [env.pool, other] {
+        env.send(system_instruction::transfer(&payer, &pool, FUND), &[]).await.unwrap();
+    }
+    (env, other)
+}
+
+#[tokio::test]
+async fn test_initialize_once() {
+    let (mut env, _) = setup().await;
+    let admin = env.admin();
+    let error = env.send(env.initialize(&env.pool, FUND + 1, PRICE), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::FundNotBacked as u32));
+
+    env.send(env.initialize(&env.pool, FUND, PRICE), &[&admin]).await.unwrap();
+    let pool = env.pool_state().await;
+    assert_eq!(pool.fund, FUND);
+    assert_eq!(pool.price, PRICE);
+    assert_eq!(pool.billboard, env.billboard);
+    assert_eq!(pool.admins[0], admin.pubkey());
+
+    // a second Initialize would wipe the pool , even with the same arguments
+    let error = env.send(env.initialize(&env.pool, FUND, PRICE), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::AlreadyInitialized as u32));
+    assert_eq!(env.pool_state().await.fund, FUND);
+}
+
+#[tokio::test]
+async fn test_billboard_serves_one_pool() {
+    let (mut env, other) = setup().await;
+    let admin = env.admin();
+    env.send(env.initialize(&env.pool, FUND, PRICE), &[&admin]).await.unwrap();
+
+    // another pool on the same billboard could settle the first pool's rounds
+    let error = env.send(env.initialize(&other, FUND, PRICE), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::BillboardInUse as u32));
+    let account = env.context.banks_client.get_account(env.billboard).await.unwrap().unwrap();
+    assert_eq!(AwardState::unpack_unchecked(&account.data).unwrap().pool, env.pool);
+}
@@ -0,0 +1,146 @@
+//! A pick-your-numbers lotto: tickets pick numbers, a roll closes sales,
+//! SettleLotto draws the winning set from a later slot hash and pays each
+//! tier in batches.
+
+use solana_sdk::{signature::Signer, sysvar};
+use solong_lottery::{
+    draw::{lotto_numbers, slot_hash},
+    error::LotteryError,
+    instruction::LotteryInstruction,
+    state::{AwardStatus, MAX_AWARD, MAX_LOTTO_PICKS},
+};
+use solong_lottery_program_test::{error_code, Env};
+
+const PRICE: u64 = 100;
+
+/// Empty rounds rolled in one transaction while filling the billboard.
+const ROLLS_PER_TRANSACTION: usize = 50;
+
+/// Starts a bank with a fresh lamport pool administered by a generated admin set,
+/// whose billboard already holds `rounds` rolled over rounds.
+async fn setup(rounds: usize) -> Env {
+    let mut env = solong_lottery_program_test::setup(PRICE, 0).await;
+    let admin = env.admin();
+    for count in (0..rounds).step_by(ROLLS_PER_TRANSACTION) {
+        let rolls = vec![env.roll(); ROLLS_PER_TRANSACTION.min(rounds - count)];
+        env.send_all(&rolls, &[&admin]).await.unwrap();
+    }
+    env
+}
+
+fn numbers(picked: &[u8]) -> LotteryInstruction {
+    let mut numbers = [0u8; MAX_LOTTO_PICKS];
+    numbers[..picked.len()].copy_from_slice(picked);
+    LotteryInstruction::BuyLotto { numbers }
+}
+
+#[tokio::test]
+async fn test_lotto_settles_in_batches() {
+    let mut env = setup(0).await;
+    let admin = env.admin();
+    let payer = env.context.payer.pubkey();
+    // pick 2 of 3: whatever is drawn , one of the three tickets below matches
+    // both numbers and the other two match one
+    let mut tier_bps = [0u16; MAX_LOTTO_PICKS];
+    tier_bps[0] = 2000;
+    tier_bps[1] = 6000;
+    env.send(env.set_lotto(2, 3, tier_bps), &[&admin]).await.unwrap();
+
+    let mut players = Vec::new();
+    for picked in [[1u8, 2], [1, 3], [3, 2]] {
+        let player = env.create_player().await;
+        env.send(env.purchase(numbers(&picked), &player.pubkey()), &[&player]).await.unwrap();
+        players.push(player);
+    }
+    let player = &players[0];
+    let error = env.send(env.purchase(numbers(&[1, 1]), &player.pubkey()), &[player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidPicks as u32));
+    let error = env.send(env.purchase(LotteryInstruction::Buy, &player.pubkey()), &[player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+
+    // the roll only closes sales , nothing is drawn yet
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    let pool = env.pool_state().await;
+    assert_eq!(pool.lotto_drawn, 0);
+    assert_ne!(pool.lotto_slot, 0);
+    assert_eq!(pool.lotto_fund, PRICE * 3);
+
+    // the round is frozen until settled
+    let error = env.send(env.purchase(numbers(&[1, 2]), &player.pubkey()), &[player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::SettlementPending as u32));
+    let error = env.send(env.roll(), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::SettlementPending as u32));
+
+    // the hash of the closing slot is not known within it
+    let error = env.send(env.settle(&payer, 2), &[]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::RevealTooEarly as u32));
+
+    env.next_slot().await;
+    let hashes = env.context.banks_client.get_account(sysvar::slot_hashes::id()).await.unwrap().unwrap();
+    let hash = slot_hash(&hashes.data, pool.lotto_slot).unwrap();
+    let drawn = lotto_numbers(&hash, 2, 3);
+    assert_eq!(drawn.count_ones(), 2);
+
+    // two entries per call: draw and count , count , pay , pay
+    for _ in 0..3 {
+        env.send(env.settle(&payer, 2), &[]).await.unwrap();
+        assert_eq!(env.pool_state().await.lotto_drawn, drawn);
+    }
+    env.send(env.settle(&payer, 2), &[]).await.unwrap();
+
+    let pool = env.pool_state().await;
+    assert_eq!(pool.lotto_drawn, 0);
+    assert_eq!(pool.lotto_slot, 0);
+    assert_eq!(pool.player_count, 0);
+    assert_eq!(pool.round, 3);
+    // 60% to the jackpot , 20% split by the two single matches , the rest stays
+    assert_eq!(pool.fund, PRICE * 3 - 180 - 30 - 30);
+    let billboard = env.billboard_state().await;
+    let mut awards: Vec<u64> = billboard.billboard.iter().map(|bill| bill.award).collect();
+    awards.sort_unstable();
+    assert_eq!(awards, vec![30, 30, 180]);
+    assert!(billboard.billboard.iter().all(|bill| bill.status == AwardStatus::Unclaimed));
+    let jackpot = billboard.billboard.iter().find(|bill| bill.award == 180).unwrap();
+    let winner = [[1u8, 2], [1, 3], [3, 2]]
+        .iter()
+        .position(|picked| picked.iter().all(|n| drawn & (1 << (n - 1)) != 0))
+        .unwrap();
+    assert_eq!(jackpot.account, players[winner].pubkey());
+
+    let error = env.send(env.settle(&payer, 2), &[]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+}
+
+#[tokio::test]
+async fn test_lotto_sales_stop_at_billboard_room() {
+    // room for two more bills
+    let mut env = setup(MAX_AWARD - 2).await;
+    let admin = env.admin();
+    let payer = env.context.payer.pubkey();
+    let mut tier_bps = [0u16; MAX_LOTTO_PICKS];
+    tier_bps[0] = 2000;
+    tier_bps[1] = 6000;
+    env.send(env.set_lotto(2, 3, tier_bps), &[&admin]).await.unwrap();
+    for picked in [[1u8, 2], [1, 3]] {
+        let player = env.create_player().await;
+        env.send(env.purchase(numbers(&picked), &player.pubkey()), &[&player]).await.unwrap();
+    }
+
+    // a third ticket could win a bill there is no room for
+    let player = env.create_player().await;
+    let error = env.send(env.purchase(numbers(&[3, 2]), &player.pubkey()), &[&player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::BillboardFull as u32));
+
+    // whatever is drawn both tickets win , and the round settles into the last bills
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    env.next_slot().await;
+    for _ in 0..2 {
+        env.send(env.settle(&payer, 2), &[]).await.unwrap();
+    }
+    let pool = env.pool_state().await;
+    assert_eq!(pool.lotto_drawn, 0);
+    assert_eq!(pool.round as usize, MAX_AWARD);
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.billboard.len(), MAX_AWARD);
+    assert!(billboard.billboard[MAX_AWARD - 2..].iter().all(|bill| bill.status == AwardStatus::Unclaimed));
+}
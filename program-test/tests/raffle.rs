@@ -0,0 +1,250 @@
+//! An NFT raffle: the sponsor escrows NFTs with the pool, a roll draws them
+//! to ticket holders who claim them, a cancelled raffle returns them.
+
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program, sysvar,
+};
+use solong_lottery::{
+    error::LotteryError,
+    instruction::LotteryInstruction,
+    state::{AwardStatus, PrizeStatus, RaffleState, RaffleStatus},
+};
+use solong_lottery_program_test::{error_code, setup, Env};
+use spl_token::instruction::AuthorityType;
+
+const PRICE: u64 = 100;
+
+/// Creates a mint with `decimals` and `supply` tokens held by `sponsor`,
+/// revoking its mint authority if `fixed`, returns the mint and the
+/// sponsor's token account.
+async fn create_mint(env: &mut Env, sponsor: &Pubkey, decimals: u8, supply: u64, fixed: bool) -> (Pubkey, Pubkey) {
+    let mint = env.create_mint(decimals).await;
+    let token = env.create_token_account(&mint, sponsor, supply).await;
+    if fixed {
+        let payer = env.context.payer.pubkey();
+        let revoke = spl_token::instruction::set_authority(&spl_token::id(), &mint, None, AuthorityType::MintTokens, &payer, &[])
+            .unwrap();
+        env.send(revoke, &[]).await.unwrap();
+    }
+    (mint, token)
+}
+
+/// Escrows a new NFT of `sponsor` with the pool, returns its mint , the
+/// sponsor's token account and the escrow.
+async fn deposit_nft(env: &mut Env, sponsor: &Keypair) -> (Pubkey, Pubkey, Pubkey) {
+    let (mint, source) = create_mint(env, &sponsor.pubkey(), 0, 1, true).await;
+    let authority = env.vault_authority();
+    let escrow = env.create_token_account(&mint, &authority, 0).await;
+    env.send(env.deposit_prize(&sponsor.pubkey(), &mint, &source, &escrow), &[sponsor]).await.unwrap();
+    (mint, source, escrow)
+}
+
+async fn raffle_state(env: &mut Env) -> RaffleState {
+    let account = env.context.banks_client.get_account(env.raffle()).await.unwrap().unwrap();
+    RaffleState::unpack(&account.data).unwrap()
+}
+
+/// The raffle instructions , signed by the admin set or the sponsor.
+trait RafflePool {
+    fn raffle(&self) -> Pubkey;
+    fn create_raffle(&self, sponsor: &Pubkey) -> Instruction;
+    fn deposit_prize(&self, sponsor: &Pubkey, mint: &Pubkey, source: &Pubkey, escrow: &Pubkey) -> Instruction;
+    fn cancel_raffle(&self) -> Instruction;
+    fn claim_prize(&self, index: u8, winner: &Pubkey, escrow: &Pubkey, destination: &Pubkey) -> Instruction;
+    fn withdraw_prize(&self, index: u8, sponsor: &Pubkey, escrow: &Pubkey, destination: &Pubkey) -> Instruction;
+    fn draw_raffle(&self) -> Instruction;
+}
+
+impl RafflePool for Env {
+    fn raffle(&self) -> Pubkey {
+        RaffleState::find_address(&self.program_id, &self.pool).0
+    }
+
+    fn create_raffle(&self, sponsor: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::CreateRaffle.pack(),
+            vec![
+                AccountMeta::new_readonly(self.admin.pubkey(), true),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new(self.raffle(), false),
+                AccountMeta::new(*sponsor, true),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    }
+
+    fn deposit_prize(&self, sponsor: &Pubkey, mint: &Pubkey, source: &Pubkey, escrow: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::DepositPrize.pack(),
+            vec![
+                AccountMeta::new_readonly(*sponsor, true),
+                AccountMeta::new_readonly(self.pool, false),
+                AccountMeta::new(self.raffle(), false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(*source, false),
+                AccountMeta::new(*escrow, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+        )
+    }
+
+    fn cancel_raffle(&self) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::CancelRaffle.pack(),
+            vec![
+                AccountMeta::new_readonly(self.admin.pubkey(), true),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new(self.raffle(), false),
+            ],
+        )
+    }
+
+    fn claim_prize(&self, index: u8, winner: &Pubkey, escrow: &Pubkey, destination: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::ClaimPrize { index }.pack(),
+            vec![
+                AccountMeta::new_readonly(*winner, true),
+                AccountMeta::new_readonly(self.pool, false),
+                AccountMeta::new(self.raffle(), false),
+                AccountMeta::new(self.billboard, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(*escrow, false),
+                AccountMeta::new_readonly(self.vault_authority(), false),
+                AccountMeta::new(*destination, false),
+            ],
+        )
+    }
+
+    fn withdraw_prize(&self, index: u8, sponsor: &Pubkey, escrow: &Pubkey, destination: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &LotteryInstruction::WithdrawPrize { index }.pack(),
+            vec![
+                AccountMeta::new_readonly(*sponsor, true),
+                AccountMeta::new_readonly(self.pool, false),
+                AccountMeta::new(self.raffle(), false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(*escrow, false),
+                AccountMeta::new_readonly(self.vault_authority(), false),
+                AccountMeta::new(*destination, false),
+            ],
+        )
+    }
+
+    /// A Roll passing the raffle , which draws its prizes.
+    fn draw_raffle(&self) -> Instruction {
+        let mut instruction = self.roll();
+        instruction.accounts.push(AccountMeta::new(self.raffle(), false));
+        instruction
+    }
+}
+
+#[tokio::test]
+async fn test_raffle_draw_and_claim() {
+    let mut env = setup(PRICE, 0).await;
+    let admin = env.admin();
+    let sponsor = env.create_player().await;
+    env.send(env.create_raffle(&sponsor.pubkey()), &[&admin, &sponsor]).await.unwrap();
+
+    let mut prizes = Vec::new();
+    for _ in 0..2 {
+        prizes.push(deposit_nft(&mut env, &sponsor).await);
+    }
+    assert_eq!(env.token_balance(&prizes[0].2).await, 1);
+
+    // a fungible token is no prize , nor is a token that can still be minted
+    let authority = env.vault_authority();
+    for (decimals, fixed) in [(6, true), (0, false)] {
+        let (mint, source) = create_mint(&mut env, &sponsor.pubkey(), decimals, 1, fixed).await;
+        let escrow = env.create_token_account(&mint, &authority, 0).await;
+        let error = env.send(env.deposit_prize(&sponsor.pubkey(), &mint, &source, &escrow), &[&sponsor]).await.unwrap_err();
+        assert_eq!(error_code(error), Some(LotteryError::NotNft as u32));
+    }
+
+    let mut players = Vec::new();
+    for _ in 0..3 {
+        let player = env.create_player().await;
+        env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+        players.push(player);
+    }
+    env.send(env.draw_raffle(), &[&admin]).await.unwrap();
+
+    let raffle = raffle_state(&mut env).await;
+    assert_eq!(raffle.status, RaffleStatus::Drawn);
+    let pool = env.pool_state().await;
+    assert!(!pool.raffle);
+    assert_eq!(pool.round, 2);
+    assert_eq!(pool.player_count, 0);
+    // the ticket sales stay for the next round
+    assert_eq!(pool.fund, PRICE * 3);
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.billboard.len(), 2);
+    for (i, prize) in raffle.prizes.iter().enumerate() {
+        assert_eq!(prize.status, PrizeStatus::Won);
+        assert_eq!(prize.round as usize, i);
+        assert_eq!(billboard.billboard[i].account, prize.winner);
+        assert_eq!(billboard.billboard[i].status, AwardStatus::Prize);
+    }
+
+    let winner = players.iter().find(|player| player.pubkey() == raffle.prizes[0].winner).unwrap();
+    let loser = players.iter().find(|player| player.pubkey() != raffle.prizes[0].winner).unwrap();
+    let (mint, _, escrow) = prizes[0];
+
+    // only the winner claims , into their own token account
+    let destination = env.create_token_account(&mint, &loser.pubkey(), 0).await;
+    let error = env.send(env.claim_prize(0, &loser.pubkey(), &escrow, &destination), &[loser]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidPermission as u32));
+    let error = env.send(env.claim_prize(0, &winner.pubkey(), &escrow, &destination), &[winner]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidTokenAccount as u32));
+
+    let destination = env.create_token_account(&mint, &winner.pubkey(), 0).await;
+    env.send(env.claim_prize(0, &winner.pubkey(), &escrow, &destination), &[winner]).await.unwrap();
+    assert_eq!(env.token_balance(&destination).await, 1);
+    assert_eq!(env.token_balance(&escrow).await, 0);
+    assert_eq!(raffle_state(&mut env).await.prizes[0].status, PrizeStatus::Claimed);
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.billboard[0].status, AwardStatus::Rewarded);
+
+    // a prize is claimed once
+    let error = env.send(env.claim_prize(0, &winner.pubkey(), &escrow, &destination), &[winner]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidPermission as u32));
+}
+
+#[tokio::test]
+async fn test_raffle_cancel_and_withdraw() {
+    let mut env = setup(PRICE, 0).await;
+    let admin = env.admin();
+    let sponsor = env.create_player().await;
+    env.send(env.create_raffle(&sponsor.pubkey()), &[&admin, &sponsor]).await.unwrap();
+    let (_, source, escrow) = deposit_nft(&mut env, &sponsor).await;
+
+    // nothing comes back before the raffle is cancelled
+    let error = env.send(env.withdraw_prize(0, &sponsor.pubkey(), &escrow, &source), &[&sponsor]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+
+    env.send(env.cancel_raffle(), &[&admin]).await.unwrap();
+    assert!(!env.pool_state().await.raffle);
+
+    env.send(env.withdraw_prize(0, &sponsor.pubkey(), &escrow, &source), &[&sponsor]).await.unwrap();
+    assert_eq!(env.token_balance(&source).await, 1);
+    let raffle = raffle_state(&mut env).await;
+    assert_eq!(raffle.status, RaffleStatus::Cancelled);
+    assert_eq!(raffle.prizes[0].status, PrizeStatus::Withdrawn);
+    let error = env.send(env.withdraw_prize(0, &sponsor.pubkey(), &escrow, &source), &[&sponsor]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+
+    // a settled raffle makes room for the next one
+    env.send(env.create_raffle(&sponsor.pubkey()), &[&admin, &sponsor]).await.unwrap();
+    let raffle = raffle_state(&mut env).await;
+    assert_eq!(raffle.status, RaffleStatus::Open);
+    assert!(raffle.prizes.is_empty());
+}
@@ -0,0 +1,112 @@
+//! Referrals: a player's first sign in names a referrer, who earns bonus
+//! tickets for the next sign in and a share of the player's purchases,
+//! taken back when a purchase is refunded.
+
+use solana_sdk::{pubkey::Pubkey, rent::Rent, signature::Signer};
+use solong_lottery::{error::LotteryError, state::LOTTERY_STATE_LEN};
+use solong_lottery_program_test::{error_code, setup};
+
+const PRICE: u64 = 1000;
+
+#[tokio::test]
+async fn test_referral_rewards() {
+    let mut env = setup(PRICE, 0).await;
+    let admin = env.admin();
+    // 3 bonus tickets per referral up to 4 , 5% of purchases up to 80
+    env.send(env.set_referral(3, 4, 500, 80), &[&admin]).await.unwrap();
+
+    let referrer = env.create_player().await;
+    env.send(env.buy(&referrer.pubkey(), None), &[&referrer]).await.unwrap();
+    env.next_slot().await;
+
+    let player = env.create_player().await;
+    let error = env.send(env.sign_in(&player.pubkey(), Some(&player.pubkey())), &[&player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::SelfReferral as u32));
+    // a referrer who never played can not refer
+    let stranger = Pubkey::new_unique();
+    let error = env.send(env.sign_in(&player.pubkey(), Some(&stranger)), &[&player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidReferrer as u32));
+
+    env.send(env.sign_in(&player.pubkey(), Some(&referrer.pubkey())), &[&player]).await.unwrap();
+    let record = env.record(&player.pubkey()).await;
+    assert_eq!(record.referrer, referrer.pubkey());
+    let record = env.record(&referrer.pubkey()).await;
+    assert_eq!(record.referrals, 1);
+    assert_eq!(record.bonus_tickets, 3);
+    assert_eq!(record.referral_tickets, 3);
+
+    // the referrer joined first , so it can not be referred back
+    let error = env.send(env.sign_in(&referrer.pubkey(), Some(&player.pubkey())), &[&referrer]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidReferrer as u32));
+
+    // purchases pay the referrer 5% until the cap
+    env.send(env.buy(&player.pubkey(), Some(&referrer.pubkey())), &[&player]).await.unwrap();
+    env.send(env.buy(&player.pubkey(), Some(&referrer.pubkey())), &[&player]).await.unwrap();
+    let record = env.record(&referrer.pubkey()).await;
+    assert_eq!(record.referral_earned, 80);
+    assert_eq!(record.balance, 80);
+    assert_eq!(env.pool_state().await.fund, PRICE * 3 - 80);
+
+    // a second referral is cut down to the ticket cap
+    let other = env.create_player().await;
+    env.send(env.sign_in(&other.pubkey(), Some(&referrer.pubkey())), &[&other]).await.unwrap();
+    let record = env.record(&referrer.pubkey()).await;
+    assert_eq!(record.referrals, 2);
+    assert_eq!(record.bonus_tickets, 4);
+    assert_eq!(record.referral_tickets, 4);
+
+    // the bonus tickets join the referrer's next sign in
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    env.send(env.sign_in(&referrer.pubkey(), None), &[&referrer]).await.unwrap();
+    assert_eq!(env.entries().await, vec![(referrer.pubkey(), 5)]);
+    assert_eq!(env.record(&referrer.pubkey()).await.bonus_tickets, 0);
+
+    let before = env.balance(&referrer.pubkey()).await;
+    env.send(env.claim_scratch(&referrer.pubkey()), &[&referrer]).await.unwrap();
+    assert_eq!(env.balance(&referrer.pubkey()).await, before + 80);
+}
+
+#[tokio::test]
+async fn test_referral_refund() {
+    let mut env = setup(PRICE, 0).await;
+    let admin = env.admin();
+    // 10% of purchases , no cap
+    env.send(env.set_referral(0, 0, 1000, 0), &[&admin]).await.unwrap();
+    let sponsor = env.create_player().await;
+    env.send(env.fund(&sponsor.pubkey(), PRICE), &[&sponsor]).await.unwrap();
+
+    let referrer = env.create_player().await;
+    env.send(env.buy(&referrer.pubkey(), None), &[&referrer]).await.unwrap();
+    env.next_slot().await;
+    let player = env.create_player().await;
+    env.send(env.sign_in(&player.pubkey(), Some(&referrer.pubkey())), &[&player]).await.unwrap();
+
+    // the first share is claimed before the round is cancelled , the second is not
+    env.send(env.buy(&player.pubkey(), Some(&referrer.pubkey())), &[&player]).await.unwrap();
+    env.send(env.claim_scratch(&referrer.pubkey()), &[&referrer]).await.unwrap();
+    env.send(env.buy(&player.pubkey(), Some(&referrer.pubkey())), &[&player]).await.unwrap();
+    assert_eq!(env.record(&referrer.pubkey()).await.balance, 100);
+    assert_eq!(env.pool_state().await.fund, PRICE * 4 - 200);
+    env.send(env.cancel_round(), &[&admin]).await.unwrap();
+
+    // a purchase that paid a share is not refunded without the referrer's record
+    env.send(env.refund(2, &player.pubkey(), None), &[]).await.unwrap_err();
+    env.send(env.refund(0, &referrer.pubkey(), None), &[]).await.unwrap();
+
+    // the unclaimed share is taken back , the claimed one comes out of the fund
+    let before = env.balance(&player.pubkey()).await;
+    env.send(env.refund(2, &player.pubkey(), Some(&referrer.pubkey())), &[]).await.unwrap();
+    let record = env.record(&referrer.pubkey()).await;
+    assert_eq!(record.balance, 0);
+    assert_eq!(record.referral_earned, 100);
+    env.send(env.refund(3, &player.pubkey(), Some(&referrer.pubkey())), &[]).await.unwrap();
+    assert_eq!(env.balance(&player.pubkey()).await, before + PRICE * 2);
+    assert_eq!(env.record(&referrer.pubkey()).await.balance, 0);
+
+    let pool = env.pool;
+    let state = env.pool_state().await;
+    assert!(!state.cancelled);
+    assert_eq!(state.fund, PRICE - 100);
+    let rent = Rent::default().minimum_balance(LOTTERY_STATE_LEN);
+    assert_eq!(env.balance(&pool).await, rent + state.fund);
+}
@@ -0,0 +1,78 @@
+//! Cancelling a round: sales and rolls stop, every paid ticket is refunded
+//! and the last refund starts the next round.
+
+use solana_sdk::signature::Signer;
+use solong_lottery::{error::LotteryError, state::AwardStatus};
+use solong_lottery_program_test::{error_code, setup};
+
+const PRICE: u64 = 100;
+
+#[tokio::test]
+async fn test_cancel_round_and_refund() {
+    let mut env = setup(PRICE, 0).await;
+    let admin = env.admin();
+    let alice = env.create_player().await;
+    let bob = env.create_player().await;
+    for player in [&alice, &bob, &alice] {
+        env.send(env.buy(&player.pubkey(), None), &[player]).await.unwrap();
+    }
+    assert_eq!(env.pool_state().await.paid_entries, 3);
+
+    env.send(env.cancel_round(), &[&admin]).await.unwrap();
+    let pool = env.pool_state().await;
+    assert!(pool.cancelled);
+    assert_eq!(pool.fund, PRICE * 3);
+    let error = env.send(env.buy(&bob.pubkey(), None), &[&bob]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::RoundCancelled as u32));
+    let error = env.send(env.roll(), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::RoundCancelled as u32));
+    let error = env.send(env.cancel_round(), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::RoundCancelled as u32));
+
+    // the price goes back to the owner of the entry only , and only once
+    let error = env.send(env.refund(0, &bob.pubkey(), None), &[]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidPermission as u32));
+    let before = env.balance(&alice.pubkey()).await;
+    env.send(env.refund(0, &alice.pubkey(), None), &[]).await.unwrap();
+    assert_eq!(env.balance(&alice.pubkey()).await, before + PRICE);
+    let error = env.send(env.refund(0, &alice.pubkey(), None), &[]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::NotRefundable as u32));
+    let pool = env.pool_state().await;
+    assert_eq!(pool.fund, PRICE * 2);
+    assert_eq!(pool.paid_entries, 2);
+
+    env.send(env.refund(2, &alice.pubkey(), None), &[]).await.unwrap();
+    env.send(env.refund(1, &bob.pubkey(), None), &[]).await.unwrap();
+
+    // the last refund records the round and opens the next one
+    let pool = env.pool_state().await;
+    assert!(!pool.cancelled);
+    assert_eq!(pool.fund, 0);
+    assert_eq!(pool.round, 1);
+    assert_eq!(pool.player_count, 0);
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.billboard.len(), 1);
+    assert_eq!(billboard.billboard[0].status, AwardStatus::Cancelled);
+    env.send(env.buy(&bob.pubkey(), None), &[&bob]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_refund_reverses_the_fee_paid() {
+    let mut env = setup(PRICE, 0).await;
+    let admin = env.admin();
+    env.send(env.set_fee(2000, 0, &admin.pubkey()), &[&admin]).await.unwrap();
+    let alice = env.create_player().await;
+    for _ in 0..2 {
+        env.send(env.buy(&alice.pubkey(), None), &[&alice]).await.unwrap();
+    }
+    env.send(env.cancel_round(), &[&admin]).await.unwrap();
+
+    // a fee changed after the purchase does not change what the ticket paid
+    env.send(env.set_fee(0, 0, &admin.pubkey()), &[&admin]).await.unwrap();
+    let before = env.balance(&alice.pubkey()).await;
+    env.send(env.refund(0, &alice.pubkey(), None), &[]).await.unwrap();
+    assert_eq!(env.balance(&alice.pubkey()).await, before + PRICE);
+    let pool = env.pool_state().await;
+    assert_eq!(pool.fund, PRICE - 20);
+    assert_eq!(pool.fees, 20);
+}
@@ -0,0 +1,58 @@
+//! Rollover: an empty round carries its prize over , growing it out of the
+//! collected fees , so the grown prize can still be paid out of the pool.
+
+use solana_sdk::signature::Signer;
+use solong_lottery::state::AwardStatus;
+use solong_lottery_program_test::Env;
+
+const PRICE: u64 = 1000;
+
+/// Starts a bank with a fresh lamport pool taking 20% of sales as fees and
+/// growing rolled over prizes by 10%.
+async fn setup() -> Env {
+    let mut env = solong_lottery_program_test::setup(PRICE, 0).await;
+    let admin = env.admin();
+    env.send(env.set_fee(2000, 0, &admin.pubkey()), &[&admin]).await.unwrap();
+    env.send(env.set_rollover(1000, 0), &[&admin]).await.unwrap();
+    env
+}
+
+#[tokio::test]
+async fn test_rollover_growth_is_paid() {
+    let mut env = setup().await;
+    let admin = env.admin();
+    let pool = env.pool;
+    let reserve = env.balance(&pool).await;
+
+    // a first round collects 200 in fees
+    let player = env.create_player().await;
+    env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    env.send(env.reward(&player.pubkey()), &[&admin]).await.unwrap();
+    let state = env.pool_state().await;
+    assert_eq!(state.fund, 0);
+    assert_eq!(state.fees, 200);
+
+    // empty rounds grow the sponsored prize until the fees run out
+    let sponsor = env.create_player().await;
+    env.send(env.fund(&sponsor.pubkey(), 1000), &[&sponsor]).await.unwrap();
+    for (fund, fees) in [(1100, 100), (1200, 0), (1200, 0)] {
+        env.send(env.roll(), &[&admin]).await.unwrap();
+        let state = env.pool_state().await;
+        assert_eq!((state.fund, state.fees), (fund, fees));
+    }
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.billboard[1].status, AwardStatus::RolledOver);
+    assert_eq!(billboard.billboard[3].award, 1200);
+
+    // the grown prize is paid in full , leaving only the new fees behind
+    env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.billboard[4].award, 2000);
+    let before = env.balance(&player.pubkey()).await;
+    env.send(env.reward(&player.pubkey()), &[&admin]).await.unwrap();
+    assert_eq!(env.balance(&player.pubkey()).await, before + 2000);
+    assert_eq!(env.pool_state().await.fees, 200);
+    assert_eq!(env.balance(&pool).await, reserve + 200);
+}
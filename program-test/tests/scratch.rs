@@ -0,0 +1,96 @@
+//! Instant win scratch cards: a purchase commits to the hash of its slot, a
+//! later reveal credits the winnings, and the player claims them.
+
+use solana_sdk::{signature::Signer, sysvar};
+use solong_lottery::{
+    draw::{scratch_random, scratch_tier, slot_hash},
+    error::LotteryError,
+    instruction::LotteryInstruction,
+    state::{ScratchTier, MAX_SCRATCH_TIERS},
+};
+use solong_lottery_program_test::error_code;
+
+const PRICE: u64 = 100;
+const BANKROLL: u64 = 100_000;
+
+fn odds_table() -> [ScratchTier; MAX_SCRATCH_TIERS] {
+    [
+        ScratchTier { odds: 10, multiplier: 2 },
+        ScratchTier { odds: 1000, multiplier: 100 },
+        ScratchTier::default(),
+        ScratchTier::default(),
+    ]
+}
+
+#[tokio::test]
+async fn test_scratch_reveal_and_claim() {
+    let mut env = solong_lottery_program_test::setup(PRICE, BANKROLL).await;
+    let admin = env.admin();
+    // a table paying more than it takes is refused
+    let mut generous = odds_table();
+    generous[0].multiplier = 10;
+    let error = env.send(env.set_scratch(generous), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+    env.send(env.set_scratch(odds_table()), &[&admin]).await.unwrap();
+
+    let player = env.create_player().await;
+    let error = env.send(env.purchase(LotteryInstruction::Buy, &player.pubkey()), &[&player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+
+    env.send(env.purchase(LotteryInstruction::BuyScratch { cards: 5 }, &player.pubkey()), &[&player])
+        .await
+        .unwrap();
+    let record = env.record(&player.pubkey()).await;
+    assert_eq!(record.scratch_cards, 5);
+    let pool = env.pool_state().await;
+    assert_eq!(pool.fund, BANKROLL + PRICE * 5);
+    assert_eq!(pool.scratch_liability, PRICE * 100 * 5);
+
+    // the bankroll cannot back 16 more top prizes
+    let other = env.create_player().await;
+    let error = env.send(env.purchase(LotteryInstruction::BuyScratch { cards: 16 }, &other.pubkey()), &[&other])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::LiabilityExceeded as u32));
+    let error = env.send(env.purchase(LotteryInstruction::BuyScratch { cards: 1 }, &player.pubkey()), &[&player])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::CardsPending as u32));
+
+    // the hash of the purchase slot is not known within it
+    let error = env.send(env.reveal_scratch(&player.pubkey()), &[]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::RevealTooEarly as u32));
+
+    env.next_slot().await;
+    let hashes = env.context.banks_client.get_account(sysvar::slot_hashes::id()).await.unwrap().unwrap();
+    let hash = slot_hash(&hashes.data, record.scratch_slot).unwrap();
+    let expected: u64 = (0..5)
+        .filter_map(|card| scratch_tier(scratch_random(&hash, &player.pubkey(), card), &odds_table()))
+        .map(|tier| PRICE * odds_table()[tier].multiplier as u64)
+        .sum();
+    env.send(env.reveal_scratch(&player.pubkey()), &[]).await.unwrap();
+
+    let record = env.record(&player.pubkey()).await;
+    assert_eq!(record.scratch_cards, 0);
+    assert_eq!(record.balance, expected);
+    let pool = env.pool_state().await;
+    assert_eq!(pool.fund, BANKROLL + PRICE * 5 - expected);
+    assert_eq!(pool.scratch_liability, 0);
+    let error = env.send(env.reveal_scratch(&player.pubkey()), &[]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+
+    if expected == 0 {
+        let error = env.send(env.claim_scratch(&player.pubkey()), &[&player]).await.unwrap_err();
+        assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+    } else {
+        let before = env.balance(&player.pubkey()).await;
+        env.send(env.claim_scratch(&player.pubkey()), &[&player]).await.unwrap();
+        assert_eq!(env.balance(&player.pubkey()).await, before + expected);
+        assert_eq!(env.record(&player.pubkey()).await.balance, 0);
+    }
+
+    // the next purchase goes through once revealed
+    env.send(env.purchase(LotteryInstruction::BuyScratch { cards: 1 }, &player.pubkey()), &[&player])
+        .await
+        .unwrap();
+}
@@ -0,0 +1,38 @@
+//! Sign in: one free entry per calendar day , whether or not the wallet
+//! already holds bought tickets or entries from earlier days of the round.
+
+use solana_sdk::signature::Signer;
+use solong_lottery::error::LotteryError;
+use solong_lottery_program_test::{error_code, setup};
+
+const PRICE: u64 = 100;
+const DAY: i64 = 86400;
+
+#[tokio::test]
+async fn test_sign_in_after_buy() {
+    let mut env = setup(PRICE, 0).await;
+
+    let player = env.create_player().await;
+    env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+    env.send(env.sign_in(&player.pubkey(), None), &[&player]).await.unwrap();
+    assert_eq!(env.entries().await, vec![(player.pubkey(), 1), (player.pubkey(), 1)]);
+
+    let error = env.send(env.sign_in(&player.pubkey(), None), &[&player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::AlreadySignedInToday as u32));
+}
+
+#[tokio::test]
+async fn test_sign_in_every_day_of_a_round() {
+    let mut env = setup(PRICE, 0).await;
+
+    let player = env.create_player().await;
+    env.send(env.sign_in(&player.pubkey(), None), &[&player]).await.unwrap();
+    let error = env.send(env.sign_in(&player.pubkey(), None), &[&player]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::AlreadySignedInToday as u32));
+
+    // no roll in between , day 2 adds a second entry and keeps the streak
+    env.advance(DAY).await;
+    env.send(env.sign_in(&player.pubkey(), None), &[&player]).await.unwrap();
+    assert_eq!(env.entries().await.len(), 2);
+    assert_eq!(env.record(&player.pubkey()).await.streak, 2);
+}
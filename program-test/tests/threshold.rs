@@ -0,0 +1,71 @@
+//! Minimum participation: a round short of its minimum players or tickets is
+//! extended a limited number of times , then cancelled and refunded , instead
+//! of drawn.
+
+use solana_sdk::signature::Signer;
+use solong_lottery::{error::LotteryError, state::AwardStatus};
+use solong_lottery_program_test::{error_code, setup};
+
+const PRICE: u64 = 100;
+const EXTENSION: i64 = 3600;
+
+#[tokio::test]
+async fn test_round_under_threshold() {
+    let mut env = setup(PRICE, 0).await;
+    let admin = env.admin();
+    let error = env.send(env.set_threshold(2, 0, -1, 1), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+    // an extension needs a limit
+    let error = env.send(env.set_threshold(2, 0, EXTENSION, 0), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+    env.send(env.set_threshold(2, 0, EXTENSION, 1), &[&admin]).await.unwrap();
+
+    // one player buying twice is still one player
+    let player = env.create_player().await;
+    env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+    env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+    let pool = env.pool_state().await;
+    assert_eq!(pool.round_players, 1);
+    assert_eq!(pool.player_count, 2);
+
+    // the roll extends the round instead of drawing it
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    let pool = env.pool_state().await;
+    assert_eq!(pool.round, 0);
+    assert_eq!(pool.round_extensions, 1);
+    assert!(pool.extended_until > 0);
+    let error = env.send(env.roll(), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::RollTooEarly as u32));
+
+    // still short once the only extension runs out , so it is cancelled
+    env.advance(EXTENSION).await;
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    let pool = env.pool_state().await;
+    assert!(pool.cancelled);
+    assert_eq!(pool.paid_entries, 2);
+
+    for index in 0..2 {
+        env.send(env.refund(index, &player.pubkey(), None), &[]).await.unwrap();
+    }
+    let pool = env.pool_state().await;
+    assert!(!pool.cancelled);
+    assert_eq!(pool.round, 1);
+    assert_eq!(pool.round_players, 0);
+    assert_eq!(pool.round_extensions, 0);
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.billboard[0].status, AwardStatus::Cancelled);
+    assert_eq!(billboard.billboard[0].extensions, 1);
+
+    // two players reach the minimum and the round is drawn
+    let other = env.create_player().await;
+    env.send(env.buy(&player.pubkey(), None), &[&player]).await.unwrap();
+    env.send(env.buy(&other.pubkey(), None), &[&other]).await.unwrap();
+    assert_eq!(env.pool_state().await.round_players, 2);
+    env.send(env.roll(), &[&admin]).await.unwrap();
+    let pool = env.pool_state().await;
+    assert_eq!(pool.round, 2);
+    assert_eq!(pool.round_players, 0);
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.billboard[1].status, AwardStatus::Unclaimed);
+    assert_eq!(billboard.billboard[1].extensions, 0);
+}
@@ -0,0 +1,148 @@
+//! A pool denominated in an SPL mint: tickets and sponsorships move tokens
+//! into the vault, awards move them out to the winner's token account.
+
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use solong_lottery::{error::LotteryError, state::AwardStatus};
+use solong_lottery_program_test::{error_code, Env};
+
+const PRICE: u64 = 100;
+const SPONSOR: u64 = 500;
+
+/// Starts a bank with a fresh pool , a mint whose authority is the payer and
+/// an empty vault the pool can sign for, returns it with the mint and vault.
+async fn setup() -> (Env, Pubkey, Pubkey) {
+    let mut env = solong_lottery_program_test::setup(PRICE, 0).await;
+    let mint = env.create_mint(6).await;
+    let authority = env.vault_authority();
+    let vault = env.create_token_account(&mint, &authority, 0).await;
+    (env, mint, vault)
+}
+
+/// Creates a funded wallet holding `amount` tokens of `mint`, returns it and its token account.
+async fn create_holder(env: &mut Env, mint: &Pubkey, amount: u64) -> (Keypair, Pubkey) {
+    let player = env.create_player().await;
+    let token = env.create_token_account(mint, &player.pubkey(), amount).await;
+    (player, token)
+}
+
+/// The instructions of a token pool , which pass the token accounts after
+/// the lamport pool's.
+trait TokenPool {
+    fn buy_with(&self, player: &Pubkey, token: &Pubkey, vault: &Pubkey) -> Instruction;
+    fn fund_with(&self, sponsor: &Pubkey, token: &Pubkey, vault: &Pubkey, amount: u64) -> Instruction;
+    fn reward_to(&self, winner: &Pubkey, token: &Pubkey, vault: &Pubkey) -> Instruction;
+}
+
+impl TokenPool for Env {
+    fn buy_with(&self, player: &Pubkey, token: &Pubkey, vault: &Pubkey) -> Instruction {
+        let mut instruction = self.buy(player, None);
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(*token, false),
+            AccountMeta::new(*vault, false),
+        ]);
+        instruction
+    }
+
+    fn fund_with(&self, sponsor: &Pubkey, token: &Pubkey, vault: &Pubkey, amount: u64) -> Instruction {
+        let mut instruction = self.fund(sponsor, amount);
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(*token, false),
+            AccountMeta::new(*vault, false),
+        ]);
+        instruction
+    }
+
+    fn reward_to(&self, winner: &Pubkey, token: &Pubkey, vault: &Pubkey) -> Instruction {
+        let mut instruction = self.reward(winner);
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(*vault, false),
+            AccountMeta::new_readonly(self.vault_authority(), false),
+            AccountMeta::new(*token, false),
+        ]);
+        instruction
+    }
+}
+
+#[tokio::test]
+async fn test_token_pool() {
+    let (mut env, mint, vault) = setup().await;
+    let admin = env.admin();
+    env.send(env.set_mint(&vault), &[&admin]).await.unwrap();
+    let pool = env.pool_state().await;
+    assert_eq!(pool.mint, mint);
+    assert_eq!(pool.vault, vault);
+
+    // a ticket costs tokens , not lamports
+    let (player, player_token) = create_holder(&mut env, &mint, 1_000).await;
+    env.send(env.buy_with(&player.pubkey(), &player_token, &vault), &[&player]).await.unwrap();
+    assert_eq!(env.token_balance(&player_token).await, 1_000 - PRICE);
+    assert_eq!(env.token_balance(&vault).await, PRICE);
+
+    let (sponsor, sponsor_token) = create_holder(&mut env, &mint, SPONSOR).await;
+    env.send(env.fund_with(&sponsor.pubkey(), &sponsor_token, &vault, SPONSOR), &[&sponsor]).await.unwrap();
+    assert_eq!(env.token_balance(&vault).await, PRICE + SPONSOR);
+    assert_eq!(env.pool_state().await.fund, PRICE + SPONSOR);
+
+    env.send(env.roll(), &[&admin]).await.unwrap();
+
+    // the award only goes to a token account of the winner
+    let (_, other_token) = create_holder(&mut env, &mint, 0).await;
+    let error = env.send(env.reward_to(&player.pubkey(), &other_token, &vault), &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidTokenAccount as u32));
+
+    env.send(env.reward_to(&player.pubkey(), &player_token, &vault), &[&admin]).await.unwrap();
+    assert_eq!(env.token_balance(&player_token).await, 1_000 - PRICE + PRICE + SPONSOR);
+    assert_eq!(env.token_balance(&vault).await, 0);
+    let billboard = env.billboard_state().await;
+    assert_eq!(billboard.billboard[0].account, player.pubkey());
+    assert_eq!(billboard.billboard[0].status, AwardStatus::Rewarded);
+}
+
+#[tokio::test]
+async fn test_set_mint_checks_vault() {
+    let (mut env, mint, _) = setup().await;
+    let admin = env.admin();
+
+    // a vault the program can not sign for is refused
+    let stranger = Keypair::new();
+    let vault = env.create_token_account(&mint, &stranger.pubkey(), 0).await;
+    let error = env.send(env.set_mint(&vault), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidTokenAccount as u32));
+
+    // and so is anyone but the admins
+    let authority = env.vault_authority();
+    let vault = env.create_token_account(&mint, &authority, 0).await;
+    let mut instruction = env.set_mint(&vault);
+    instruction.accounts[0] = AccountMeta::new_readonly(stranger.pubkey(), true);
+    let error = env.send(instruction, &[&stranger]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidPermission as u32));
+    assert_eq!(env.pool_state().await.mint, Pubkey::default());
+}
+
+#[tokio::test]
+async fn test_set_mint_refuses_sponsored_pool() {
+    let (mut env, _, vault) = setup().await;
+    let admin = env.admin();
+
+    // lamports sponsored before the switch would be owed in tokens
+    let sponsor = env.create_player().await;
+    env.send(env.fund(&sponsor.pubkey(), SPONSOR), &[&sponsor]).await.unwrap();
+    let error = env.send(env.set_mint(&vault), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+
+    // lowering the prize does not forget the sponsor
+    env.send(env.gm(0, PRICE), &[&admin]).await.unwrap();
+    assert_eq!(env.pool_state().await.fund, 0);
+    let error = env.send(env.set_mint(&vault), &[&admin]).await.unwrap_err();
+    assert_eq!(error_code(error), Some(LotteryError::InvalidInstruction as u32));
+    assert_eq!(env.pool_state().await.mint, Pubkey::default());
+}